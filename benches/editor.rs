@@ -0,0 +1,64 @@
+//! Benchmarks of typing, pasting and redrawing through the `TestBackend`,
+//! run with `cargo bench`, which compares them with the last run.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use minime::{
+    backend::TestBackend,
+    crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    editor::{keybindings::NormalKeybinding, Editor},
+    renderer::full::CrosstermRenderer,
+};
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+/// Read from a terminal that plays back the events that `setup` queued,
+/// ending with `Esc` to submit.
+fn read(contents: &str, setup: impl Fn(&mut TestBackend)) {
+    let mut backend = TestBackend::new(80, 24);
+    setup(&mut backend);
+    backend.push_event(key(KeyCode::Esc));
+
+    let mut editor = Editor::default();
+    editor.set_contents(contents.as_bytes()).unwrap();
+    let renderer = CrosstermRenderer::render_with(&mut backend);
+    editor.read(NormalKeybinding, renderer).unwrap();
+}
+
+fn typing(c: &mut Criterion) {
+    let text = "the quick brown fox jumps over the lazy dog ".repeat(10);
+    c.bench_function("typing", |b| {
+        b.iter(|| read("", |backend| backend.push_keys(black_box(&text))))
+    });
+}
+
+fn paste(c: &mut Criterion) {
+    let lines = numbered_lines();
+    c.bench_function("paste", |b| {
+        b.iter(|| read("", |backend| backend.push_paste(black_box(&lines))))
+    });
+}
+
+fn redraw(c: &mut Criterion) {
+    let lines = numbered_lines();
+    c.bench_function("redraw", |b| {
+        b.iter(|| {
+            read(black_box(&lines), |backend| {
+                for _ in 0..100 {
+                    backend.push_event(key(KeyCode::Up));
+                }
+            })
+        })
+    });
+}
+
+fn numbered_lines() -> String {
+    (0..200)
+        .map(|i| format!("let line_{} = {};", i, i))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+criterion_group!(benches, typing, paste, redraw);
+criterion_main!(benches);
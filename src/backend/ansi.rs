@@ -0,0 +1,516 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+
+use super::{key_events, paste_text, Backend, Input};
+use crate::Result;
+
+/// Time to wait for the rest of an escape sequence after `Esc`,
+/// before taking it as the `Esc` key on its own.
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+/// Time to wait for the terminal to report the position of the cursor.
+const REPORT_TIMEOUT: Duration = Duration::from_secs(2);
+/// Longest escape sequence that is waited on for its final byte.
+const MAX_SEQUENCE: usize = 64;
+/// Sequence that ends a bracketed paste.
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Backend that talks to the terminal only through the bytes that are
+/// written to it and read from it, such as a pseudo-terminal that is
+/// reached over the network, without asking the console of the process.
+///
+/// The input is read on a thread of its own, which ends when the input
+/// does, and turned into events by a [`KeyParser`]. The terminal has to
+/// be in raw mode already, since enabling raw mode does nothing here,
+/// and its size is whatever it was last set to, since it cannot be asked
+/// for.
+pub struct AnsiBackend<W> {
+    write: W,
+    buffer: Vec<u8>,
+    written: u64,
+    input: Receiver<io::Result<Vec<u8>>>,
+    parser: KeyParser,
+    events: VecDeque<Input>,
+    size: (u16, u16),
+}
+
+impl<W: Write> AnsiBackend<W> {
+    /// Draw to `write` and read the input from `read`, for a terminal
+    /// of `columns` by `rows` cells.
+    pub fn new<R>(read: R, write: W, columns: u16, rows: u16) -> Self
+    where
+        R: Read + Send + 'static,
+    {
+        let (sender, input) = mpsc::channel();
+        thread::spawn(move || {
+            let mut read = read;
+            let mut bytes = [0; 1024];
+            loop {
+                let sent = match read.read(&mut bytes) {
+                    Ok(0) => break,
+                    Ok(n) => sender.send(Ok(bytes[..n].to_vec())),
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        let _ = sender.send(Err(e));
+                        break;
+                    }
+                };
+                if sent.is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            write,
+            buffer: Vec::new(),
+            written: 0,
+            input,
+            parser: KeyParser::new(),
+            events: VecDeque::new(),
+            size: (columns, rows),
+        }
+    }
+
+    /// Change the size of the terminal, such as when the other end of the
+    /// connection tells that it was resized, which is read as a resize event.
+    pub fn resize(&mut self, columns: u16, rows: u16) {
+        self.size = (columns, rows);
+        self.events
+            .push_back(Input::Event(Event::Resize(columns, rows)));
+    }
+
+    /// Read the input until there is an event, or until `timeout` passed.
+    fn wait_input(&mut self, timeout: Option<Duration>) -> Result<()> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        while self.events.is_empty() {
+            let left = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            // A lone `Esc` is only taken as the key once nothing follows it.
+            let wait = match (self.parser.is_pending(), left) {
+                (true, Some(left)) => Some(left.min(ESCAPE_TIMEOUT)),
+                (true, None) => Some(ESCAPE_TIMEOUT),
+                (false, left) => left,
+            };
+            let received = match wait {
+                Some(wait) => self.input.recv_timeout(wait),
+                None => self.input.recv().map_err(RecvTimeoutError::from),
+            };
+            match received {
+                Ok(bytes) => self.events.extend(self.parser.feed(&bytes?)),
+                Err(RecvTimeoutError::Timeout) if wait == Some(ESCAPE_TIMEOUT) => {
+                    self.events.extend(self.parser.flush());
+                }
+                Err(RecvTimeoutError::Timeout) => return Ok(()),
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.events.extend(self.parser.flush());
+                    if self.events.is_empty() {
+                        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for AnsiBackend<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        self.written += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.write.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
+        self.write.flush()
+    }
+}
+
+impl<W: Write> Backend for AnsiBackend<W> {
+    /// Pastes are read as a key for each character.
+    fn read_event(&mut self) -> Result<Event> {
+        loop {
+            match self.read_input()? {
+                Input::Event(event) => return Ok(event),
+                Input::Paste(text) => {
+                    let keys: Vec<_> = key_events(&text).map(Input::Event).collect();
+                    for input in keys.into_iter().rev() {
+                        self.events.push_front(input);
+                    }
+                }
+            }
+        }
+    }
+
+    fn read_input(&mut self) -> Result<Input> {
+        self.flush()?;
+        self.wait_input(None)?;
+        Ok(self.events.pop_front().expect("waited for an event"))
+    }
+
+    fn poll_event(&mut self, timeout: Duration) -> Result<bool> {
+        self.flush()?;
+        self.wait_input(Some(timeout))?;
+        Ok(!self.events.is_empty())
+    }
+
+    fn size(&self) -> Result<(u16, u16)> {
+        Ok(self.size)
+    }
+
+    /// Ask the terminal where the cursor is, keeping the events that arrive
+    /// before its answer for later.
+    fn cursor_position(&mut self) -> Result<(u16, u16)> {
+        self.write_all(b"\x1b[6n")?;
+        self.flush()?;
+        let deadline = Instant::now() + REPORT_TIMEOUT;
+        loop {
+            if let Some(position) = self.parser.positions.pop_front() {
+                return Ok(position);
+            }
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.input.recv_timeout(left) {
+                Ok(bytes) => self.events.extend(self.parser.feed(&bytes?)),
+                Err(RecvTimeoutError::Timeout) => {
+                    let message = "the terminal did not report the cursor position";
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, message).into());
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                }
+            }
+        }
+    }
+
+    fn enable_raw_mode(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<()> {
+        self.flush()?;
+        Ok(())
+    }
+
+    fn supports_bracketed_paste(&self) -> bool {
+        true
+    }
+
+    fn bytes_written(&self) -> Option<u64> {
+        Some(self.written)
+    }
+}
+
+/// Parser of the bytes that a terminal sends in raw mode into inputs,
+/// which understands the keys of `xterm` and the terminals that follow it,
+/// mouse events in the SGR encoding and bracketed pastes, which are
+/// parsed into [`Input::Paste`].
+///
+/// Sequences that are cut off are kept until the rest of their bytes is
+/// fed, and sequences that are not understood are left out.
+#[derive(Debug, Clone, Default)]
+pub struct KeyParser {
+    pending: Vec<u8>,
+    /// Text of a bracketed paste that has not ended yet.
+    paste: Option<Vec<u8>>,
+    /// Positions of the cursor that the terminal reported.
+    positions: VecDeque<(u16, u16)>,
+}
+
+/// What a sequence of bytes at the start of the input stands for.
+enum Parsed {
+    Event(Event),
+    /// Report of the position of the cursor as `(column, row)`.
+    Position(u16, u16),
+    PasteStart,
+    /// Sequence that is not understood.
+    Unknown,
+}
+
+impl KeyParser {
+    /// Create a parser that is waiting on nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the bytes that the terminal sent after the ones that were
+    /// fed before, returning the inputs that are complete.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Input> {
+        self.pending.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        let mut start = 0;
+        loop {
+            let rest = &self.pending[start..];
+            if let Some(paste) = &mut self.paste {
+                match rest
+                    .windows(PASTE_END.len())
+                    .position(|end| end == PASTE_END)
+                {
+                    Some(len) => {
+                        paste.extend_from_slice(&rest[..len]);
+                        start += len + PASTE_END.len();
+                        events.push(Input::Paste(paste_text(&String::from_utf8_lossy(paste))));
+                        self.paste = None;
+                        continue;
+                    }
+                    None => {
+                        // The end of the paste may be cut off.
+                        let len = rest.len().saturating_sub(PASTE_END.len() - 1);
+                        paste.extend_from_slice(&rest[..len]);
+                        start += len;
+                        break;
+                    }
+                }
+            }
+            if rest.is_empty() {
+                break;
+            }
+            match parse(rest) {
+                Some((parsed, len)) => {
+                    start += len;
+                    match parsed {
+                        Parsed::Event(event) => events.push(Input::Event(event)),
+                        Parsed::Position(column, row) => self.positions.push_back((column, row)),
+                        Parsed::PasteStart => self.paste = Some(Vec::new()),
+                        Parsed::Unknown => {}
+                    }
+                }
+                None => break,
+            }
+        }
+        self.pending.drain(..start);
+        events
+    }
+
+    /// Whether some bytes are waiting for the rest of their sequence,
+    /// such as an `Esc` that may start one.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty() && self.paste.is_none()
+    }
+
+    /// Stop waiting for the rest of the sequence that is cut off, taking
+    /// an `Esc` that starts it as the key, such as once nothing followed
+    /// it for a while.
+    pub fn flush(&mut self) -> Vec<Input> {
+        if !self.is_pending() {
+            return Vec::new();
+        }
+        match std::mem::take(&mut self.pending).split_first() {
+            Some((0x1b, rest)) => {
+                let mut events = vec![Input::Event(key(KeyCode::Esc, KeyModifiers::NONE))];
+                events.extend(self.feed(rest));
+                events
+            }
+            // A character that is cut off is left out.
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+    Event::Key(KeyEvent::new(code, modifiers))
+}
+
+/// Parse the first key or sequence of the bytes, along with the number of
+/// bytes that it takes up, or `None` if it is cut off.
+fn parse(bytes: &[u8]) -> Option<(Parsed, usize)> {
+    let event = |code| Some((Parsed::Event(key(code, KeyModifiers::NONE)), 1));
+    let ctrl = |c| {
+        let event = key(KeyCode::Char(c), KeyModifiers::CONTROL);
+        Some((Parsed::Event(event), 1))
+    };
+    match bytes[0] {
+        0x1b => match bytes.get(1)? {
+            b'[' => parse_csi(bytes),
+            b'O' => Some((parse_ss3(*bytes.get(2)?), 3)),
+            0x1b => event(KeyCode::Esc),
+            // Alt is sent as `Esc` before the key.
+            _ => match parse(&bytes[1..])? {
+                (Parsed::Event(Event::Key(mut key)), len) => {
+                    key.modifiers |= KeyModifiers::ALT;
+                    Some((Parsed::Event(Event::Key(key)), len + 1))
+                }
+                (parsed, len) => Some((parsed, len + 1)),
+            },
+        },
+        b'\r' | b'\n' => event(KeyCode::Enter),
+        b'\t' => event(KeyCode::Tab),
+        0x7f => event(KeyCode::Backspace),
+        0x00 => ctrl(' '),
+        b @ 0x01..=0x1a => ctrl((b - 0x01 + b'a') as char),
+        b @ 0x1c..=0x1f => ctrl((b - 0x1c + b'4') as char),
+        first => {
+            let len = match first {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            };
+            if bytes.len() < len {
+                return None;
+            }
+            match std::str::from_utf8(&bytes[..len])
+                .ok()
+                .and_then(|s| s.chars().next())
+            {
+                Some(c) => {
+                    let modifiers = match c.is_uppercase() {
+                        true => KeyModifiers::SHIFT,
+                        false => KeyModifiers::NONE,
+                    };
+                    Some((Parsed::Event(key(KeyCode::Char(c), modifiers)), len))
+                }
+                None => Some((Parsed::Unknown, 1)),
+            }
+        }
+    }
+}
+
+/// Parse the key of `Esc O`, which terminals send for some keys
+/// in their application mode.
+fn parse_ss3(end: u8) -> Parsed {
+    match cursor_key(end) {
+        Some(code) => Parsed::Event(key(code, KeyModifiers::NONE)),
+        None => Parsed::Unknown,
+    }
+}
+
+/// Key that a sequence ending with a letter stands for.
+fn cursor_key(end: u8) -> Option<KeyCode> {
+    Some(match end {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'P' => KeyCode::F(1),
+        b'Q' => KeyCode::F(2),
+        b'R' => KeyCode::F(3),
+        b'S' => KeyCode::F(4),
+        _ => return None,
+    })
+}
+
+/// Key of a sequence ending with `~`, by the number that it starts with.
+fn tilde_key(number: u16) -> Option<KeyCode> {
+    Some(match number {
+        1 | 7 => KeyCode::Home,
+        2 => KeyCode::Insert,
+        3 => KeyCode::Delete,
+        4 | 8 => KeyCode::End,
+        5 => KeyCode::PageUp,
+        6 => KeyCode::PageDown,
+        11..=15 => KeyCode::F((number - 10) as u8),
+        17..=21 => KeyCode::F((number - 11) as u8),
+        23 | 24 => KeyCode::F((number - 12) as u8),
+        _ => return None,
+    })
+}
+
+/// Parse a control sequence `Esc [ params end`.
+fn parse_csi(bytes: &[u8]) -> Option<(Parsed, usize)> {
+    let end = match bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b)) {
+        Some(end) => end + 2,
+        None if bytes.len() > MAX_SEQUENCE => return Some((Parsed::Unknown, bytes.len())),
+        None => return None,
+    };
+    let params = String::from_utf8_lossy(&bytes[2..end]);
+    let len = end + 1;
+    if let Some(params) = params.strip_prefix('<') {
+        return Some((parse_mouse(params, bytes[end]), len));
+    }
+    let args: Vec<u16> = params
+        .split(';')
+        .map(|arg| arg.parse().unwrap_or(0))
+        .collect();
+    let arg = |i: usize| args.get(i).copied().unwrap_or(0);
+    // Modifiers are sent as one more than the bits of the keys held down.
+    let bits = arg(1).saturating_sub(1);
+    let mut modifiers = KeyModifiers::NONE;
+    for (bit, modifier) in [
+        (1, KeyModifiers::SHIFT),
+        (2, KeyModifiers::ALT),
+        (4, KeyModifiers::CONTROL),
+    ] {
+        if bits & bit != 0 {
+            modifiers |= modifier;
+        }
+    }
+
+    let code = match bytes[end] {
+        b'R' if args.len() == 2 && arg(0) > 0 => {
+            let position = Parsed::Position(arg(1).saturating_sub(1), arg(0) - 1);
+            return Some((position, len));
+        }
+        b'Z' => {
+            let event = key(KeyCode::BackTab, modifiers | KeyModifiers::SHIFT);
+            return Some((Parsed::Event(event), len));
+        }
+        b'~' if arg(0) == 200 => return Some((Parsed::PasteStart, len)),
+        b'~' => tilde_key(arg(0)),
+        end => cursor_key(end),
+    };
+    match code {
+        Some(code) => Some((Parsed::Event(key(code, modifiers)), len)),
+        None => Some((Parsed::Unknown, len)),
+    }
+}
+
+/// Parse a mouse event in the SGR encoding, `Esc [ < button ; x ; y end`,
+/// which ends with `M` for presses and `m` for releases.
+fn parse_mouse(params: &str, end: u8) -> Parsed {
+    let args: Vec<u16> = params
+        .split(';')
+        .map(|arg| arg.parse().unwrap_or(0))
+        .collect();
+    let (code, column, row) = match args[..] {
+        [code, column, row] => (code, column.saturating_sub(1), row.saturating_sub(1)),
+        _ => return Parsed::Unknown,
+    };
+    let button = match code & 0b11 {
+        1 => MouseButton::Middle,
+        2 => MouseButton::Right,
+        _ => MouseButton::Left,
+    };
+    let kind = if code & 64 != 0 {
+        match code & 1 {
+            0 => MouseEventKind::ScrollUp,
+            _ => MouseEventKind::ScrollDown,
+        }
+    } else if code & 32 != 0 {
+        match code & 0b11 {
+            3 => MouseEventKind::Moved,
+            _ => MouseEventKind::Drag(button),
+        }
+    } else if end == b'm' {
+        MouseEventKind::Up(button)
+    } else {
+        MouseEventKind::Down(button)
+    };
+    let mut modifiers = KeyModifiers::NONE;
+    for (bit, modifier) in [
+        (4, KeyModifiers::SHIFT),
+        (8, KeyModifiers::ALT),
+        (16, KeyModifiers::CONTROL),
+    ] {
+        if code & bit != 0 {
+            modifiers |= modifier;
+        }
+    }
+    Parsed::Event(Event::Mouse(MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers,
+    }))
+}
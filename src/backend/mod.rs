@@ -0,0 +1,858 @@
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    io::{self, stderr, stdout, Stderr, Stdout, Write},
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    cursor::position,
+    event::{poll, read, Event, KeyCode, KeyEvent, KeyEventKind},
+    terminal::{disable_raw_mode, enable_raw_mode, size},
+};
+
+use crate::{util::char_width, Result};
+
+mod ansi;
+
+pub use ansi::{AnsiBackend, KeyParser};
+
+/// What is read from the terminal, which is an event, or text that the
+/// terminal marked as pasted once bracketed paste is enabled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    /// An event that was not part of a paste.
+    Event(Event),
+    /// Text that was pasted, with its line breaks as `\n`.
+    Paste(String),
+}
+
+impl From<Event> for Input {
+    /// Pastes become [`Input::Paste`], and every other event stays one.
+    fn from(event: Event) -> Self {
+        match event {
+            Event::Paste(text) => Input::Paste(paste_text(&text)),
+            event => Input::Event(event),
+        }
+    }
+}
+
+/// Text of a paste with its line breaks as `\n`, which terminals send as `\r`.
+pub(crate) fn paste_text(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Standard stream of the process that a backend draws on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Console {
+    /// The standard output.
+    Stdout,
+    /// The standard error.
+    Stderr,
+}
+
+/// The terminal that the editor is drawn to and reads events from.
+///
+/// Cursor movement and clearing are `crossterm` commands queued onto
+/// the backend as output, so a backend only has to decide where that
+/// output goes and where events come from.
+pub trait Backend: Write {
+    /// Read the next event, blocking until there is one.
+    fn read_event(&mut self) -> Result<Event>;
+
+    /// Read the next input, blocking until there is one. Defaults to
+    /// reading an event, for backends that cannot tell pastes apart from
+    /// typing.
+    fn read_input(&mut self) -> Result<Input> {
+        self.read_event().map(Input::Event)
+    }
+
+    /// Wait up to `timeout` for an event to be available to read.
+    fn poll_event(&mut self, timeout: Duration) -> Result<bool>;
+
+    /// Get the size of the terminal as `(columns, rows)`.
+    fn size(&self) -> Result<(u16, u16)>;
+
+    /// Get the position of the cursor on the terminal as `(column, row)`.
+    fn cursor_position(&mut self) -> Result<(u16, u16)>;
+
+    /// Stop the terminal from echoing and line-buffering input.
+    fn enable_raw_mode(&mut self) -> Result<()>;
+
+    /// Undo [`Backend::enable_raw_mode`].
+    fn disable_raw_mode(&mut self) -> Result<()>;
+
+    /// Whether escape sequences that are written as text, such as by
+    /// a highlighter, are understood. Defaults to `true`.
+    fn supports_ansi(&self) -> bool {
+        true
+    }
+
+    /// Whether pastes are read as [`Input::Paste`] once the terminal marks
+    /// them with bracketed paste, which is only turned on where they are.
+    /// Defaults to `false`.
+    fn supports_bracketed_paste(&self) -> bool {
+        false
+    }
+
+    /// Whether [`Backend::suspend`] stops the process. Defaults to `false`.
+    fn supports_suspend(&self) -> bool {
+        false
+    }
+
+    /// Stop the process until it is continued, like job control does on
+    /// `Ctrl-Z`, once the terminal is restored and out of raw mode.
+    /// Defaults to doing nothing, for terminals that are not the one of
+    /// the process, such as a pseudo-terminal over the network.
+    fn suspend(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Standard stream that the backend draws on, when raw mode is that
+    /// of the console of the process, for a panic hook to restore the
+    /// terminal on before the panic message is printed. Defaults to `None`,
+    /// for backends that are only restored once their renderer is dropped
+    /// while unwinding.
+    fn console(&self) -> Option<Console> {
+        None
+    }
+
+    /// Whether the terminal holds off showing what is drawn between the
+    /// escape sequences that begin and end a synchronized update.
+    /// Defaults to `false`.
+    fn supports_synchronized_output(&self) -> bool {
+        false
+    }
+
+    /// Number of bytes written to the backend so far, which is how much
+    /// each draw writes is measured. Defaults to `None` for backends that
+    /// do not count them.
+    fn bytes_written(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl<B: Backend + ?Sized> Backend for &mut B {
+    fn read_event(&mut self) -> Result<Event> {
+        (**self).read_event()
+    }
+
+    fn read_input(&mut self) -> Result<Input> {
+        (**self).read_input()
+    }
+
+    fn poll_event(&mut self, timeout: Duration) -> Result<bool> {
+        (**self).poll_event(timeout)
+    }
+
+    fn size(&self) -> Result<(u16, u16)> {
+        (**self).size()
+    }
+
+    fn cursor_position(&mut self) -> Result<(u16, u16)> {
+        (**self).cursor_position()
+    }
+
+    fn enable_raw_mode(&mut self) -> Result<()> {
+        (**self).enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<()> {
+        (**self).disable_raw_mode()
+    }
+
+    fn supports_ansi(&self) -> bool {
+        (**self).supports_ansi()
+    }
+
+    fn supports_bracketed_paste(&self) -> bool {
+        (**self).supports_bracketed_paste()
+    }
+
+    fn supports_suspend(&self) -> bool {
+        (**self).supports_suspend()
+    }
+
+    fn suspend(&mut self) -> Result<()> {
+        (**self).suspend()
+    }
+
+    fn console(&self) -> Option<Console> {
+        (**self).console()
+    }
+
+    fn supports_synchronized_output(&self) -> bool {
+        (**self).supports_synchronized_output()
+    }
+
+    fn bytes_written(&self) -> Option<u64> {
+        (**self).bytes_written()
+    }
+}
+
+/// Backend that writes to any output and uses `crossterm`
+/// for the rest of the terminal.
+///
+/// Writes are collected until the output is flushed, so that a frame
+/// goes out in a single write instead of many small ones, which would
+/// flicker over slow connections. The output is also flushed before
+/// reading events or the cursor position, and when leaving raw mode.
+///
+/// On Windows, virtual terminal processing is enabled where the console
+/// has it. Legacy consoles without it have `crossterm` move the cursor
+/// through WinAPI calls, so the output is flushed after every write to
+/// keep the text in order with the cursor movement.
+///
+/// Pastes are read as [`Input::Paste`] where the terminal marks them with
+/// bracketed paste, which the Windows console does not, so that they
+/// arrive as keys there.
+///
+/// Synchronized output is assumed for terminals that are known to support
+/// it by their environment variables, since asking the terminal would mean
+/// waiting for a reply that other terminals never send.
+pub struct CrosstermBackend<W> {
+    write: W,
+    buffer: Vec<u8>,
+    written: u64,
+    console: Option<Console>,
+    legacy_console: bool,
+    synchronized_output: bool,
+    /// Inputs that were read while polling, or the keys of a paste that
+    /// is read as events.
+    pending: VecDeque<Input>,
+}
+
+impl<W: Write> CrosstermBackend<W> {
+    /// Draw to any output. Unlike [`CrosstermBackend::stdout`] and
+    /// [`CrosstermBackend::stderr`], the terminal is only restored from
+    /// a panic once the renderer is dropped, after the message is printed.
+    pub fn new(write: W) -> Self {
+        Self {
+            write,
+            buffer: Vec::new(),
+            written: 0,
+            console: None,
+            legacy_console: legacy_console(),
+            synchronized_output: synchronized_output(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Read an event into the pending inputs, leaving out the releases of
+    /// keys, which are only reported on Windows.
+    fn read_pending(&mut self) -> Result<()> {
+        match read()? {
+            Event::Key(KeyEvent {
+                kind: KeyEventKind::Release,
+                ..
+            }) => {}
+            event => self.pending.push_back(event.into()),
+        }
+        Ok(())
+    }
+}
+
+impl CrosstermBackend<Stdout> {
+    /// Draw to the standard output.
+    pub fn stdout() -> Self {
+        Self {
+            console: Some(Console::Stdout),
+            ..Self::new(stdout())
+        }
+    }
+}
+
+impl CrosstermBackend<Stderr> {
+    /// Draw to the standard error.
+    pub fn stderr() -> Self {
+        Self {
+            console: Some(Console::Stderr),
+            ..Self::new(stderr())
+        }
+    }
+}
+
+/// Whether the console cannot process escape sequences,
+/// after trying to enable them.
+#[cfg(windows)]
+fn legacy_console() -> bool {
+    !crossterm::ansi_support::supports_ansi()
+}
+
+#[cfg(not(windows))]
+fn legacy_console() -> bool {
+    false
+}
+
+/// Whether the terminal is one that is known to support synchronized output.
+fn synchronized_output() -> bool {
+    let var = |name| std::env::var(name).unwrap_or_default();
+    let (program, term) = (var("TERM_PROGRAM"), var("TERM"));
+    ["iTerm.app", "WezTerm", "ghostty", "contour"].contains(&program.as_str())
+        || ["kitty", "foot", "alacritty", "ghostty", "contour"]
+            .iter()
+            .any(|name| term.contains(name))
+}
+
+impl<W: Write> Write for CrosstermBackend<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.legacy_console {
+            let written = self.write.write(buf)?;
+            self.write.flush()?;
+            self.written += written as u64;
+            return Ok(written);
+        }
+        self.buffer.extend_from_slice(buf);
+        self.written += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.write.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
+        self.write.flush()
+    }
+}
+
+impl<W: Write> Backend for CrosstermBackend<W> {
+    /// Pastes are read as a key for each character.
+    fn read_event(&mut self) -> Result<Event> {
+        loop {
+            match self.read_input()? {
+                Input::Event(event) => return Ok(event),
+                Input::Paste(text) => {
+                    let keys: Vec<_> = key_events(&text).map(Input::Event).collect();
+                    for input in keys.into_iter().rev() {
+                        self.pending.push_front(input);
+                    }
+                }
+            }
+        }
+    }
+
+    fn read_input(&mut self) -> Result<Input> {
+        self.flush()?;
+        while self.pending.is_empty() {
+            self.read_pending()?;
+        }
+        Ok(self.pending.pop_front().expect("read an input"))
+    }
+
+    fn poll_event(&mut self, timeout: Duration) -> Result<bool> {
+        self.flush()?;
+        let deadline = Instant::now() + timeout;
+        while self.pending.is_empty() {
+            if !poll(deadline.saturating_duration_since(Instant::now()))? {
+                break;
+            }
+            self.read_pending()?;
+        }
+        Ok(!self.pending.is_empty())
+    }
+
+    fn size(&self) -> Result<(u16, u16)> {
+        Ok(size()?)
+    }
+
+    fn cursor_position(&mut self) -> Result<(u16, u16)> {
+        self.flush()?;
+        Ok(position()?)
+    }
+
+    fn enable_raw_mode(&mut self) -> Result<()> {
+        Ok(enable_raw_mode()?)
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<()> {
+        self.flush()?;
+        Ok(disable_raw_mode()?)
+    }
+
+    fn supports_ansi(&self) -> bool {
+        !self.legacy_console
+    }
+
+    fn supports_bracketed_paste(&self) -> bool {
+        cfg!(not(windows))
+    }
+
+    fn supports_suspend(&self) -> bool {
+        cfg!(unix)
+    }
+
+    /// Raise `SIGTSTP`, which returns once the shell continues the process.
+    fn suspend(&mut self) -> Result<()> {
+        #[cfg(unix)]
+        // SAFETY: raising a signal has no preconditions.
+        unsafe {
+            libc::raise(libc::SIGTSTP);
+        }
+        Ok(())
+    }
+
+    fn console(&self) -> Option<Console> {
+        self.console
+    }
+
+    fn supports_synchronized_output(&self) -> bool {
+        self.synchronized_output && !self.legacy_console
+    }
+
+    fn bytes_written(&self) -> Option<u64> {
+        Some(self.written)
+    }
+}
+
+/// Backend that plays back queued events and draws its output onto a
+/// grid of cells instead of a terminal, for testing what is rendered.
+///
+/// The output is interpreted like a terminal in raw mode would: a new-line
+/// only moves down a row, writing into the last column defers the wrap
+/// until the next character, and wide characters take up two cells.
+/// Render to a `&mut TestBackend` to look at the screen afterwards.
+#[derive(Debug, Clone)]
+pub struct TestBackend {
+    columns: u16,
+    rows: u16,
+    screen: Vec<Vec<String>>,
+    main_screen: Option<Vec<Vec<String>>>,
+    cursor: (u16, u16),
+    saved_cursor: (u16, u16),
+    wrap_pending: bool,
+    cursor_visible: bool,
+    cursor_style: u16,
+    raw_mode: bool,
+    mouse_capture: bool,
+    bracketed_paste: bool,
+    /// Inputs to read, and whether each one arrives along with the one before.
+    events: VecDeque<(Input, bool)>,
+    output: Vec<u8>,
+    written: u64,
+    unparsed: Vec<u8>,
+}
+
+impl TestBackend {
+    /// Create a blank screen of `columns` by `rows` cells.
+    pub fn new(columns: u16, rows: u16) -> Self {
+        Self {
+            columns: columns.max(1),
+            rows: rows.max(1),
+            screen: blank_screen(columns.max(1), rows.max(1)),
+            main_screen: None,
+            cursor: (0, 0),
+            saved_cursor: (0, 0),
+            wrap_pending: false,
+            cursor_visible: true,
+            cursor_style: 0,
+            raw_mode: false,
+            mouse_capture: false,
+            bracketed_paste: false,
+            events: VecDeque::new(),
+            output: Vec::new(),
+            written: 0,
+            unparsed: Vec::new(),
+        }
+    }
+
+    /// Queue up an event to be read, after the ones that are already queued.
+    ///
+    /// Each event arrives on its own, so the editor draws after reading it.
+    /// An [`Event::Paste`] is read like [`TestBackend::push_paste`] queues it.
+    pub fn push_event(&mut self, event: impl Into<Event>) {
+        self.events.push_back((Input::from(event.into()), false));
+    }
+
+    /// Queue up a key event for each character of the text, typed one
+    /// after the other, with `\n` pressing `Enter`.
+    pub fn push_keys(&mut self, text: &str) {
+        for event in key_events(text) {
+            self.push_event(event);
+        }
+    }
+
+    /// Queue up the text as a bracketed paste, which is read as an
+    /// [`Input::Paste`], or as a key for each character by
+    /// [`Backend::read_event`].
+    pub fn push_paste(&mut self, text: &str) {
+        self.events
+            .push_back((Input::Paste(text.to_string()), false));
+    }
+
+    /// Change the size of the screen, as if the terminal was resized.
+    ///
+    /// Cells that still fit are kept, but unlike most terminals
+    /// the rows are not rewrapped.
+    pub fn resize(&mut self, columns: u16, rows: u16) {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        for screen in std::iter::once(&mut self.screen).chain(&mut self.main_screen) {
+            screen.resize_with(rows.into(), Vec::new);
+            for row in screen.iter_mut() {
+                row.resize(columns.into(), String::from(" "));
+            }
+        }
+        self.columns = columns;
+        self.rows = rows;
+        self.cursor = (self.cursor.0.min(columns - 1), self.cursor.1.min(rows - 1));
+        self.wrap_pending = false;
+    }
+
+    /// Get the text of each row of the screen, without trailing spaces.
+    pub fn screen(&self) -> Vec<String> {
+        (0..self.rows).map(|row| self.row(row)).collect()
+    }
+
+    /// Get the text of a row of the screen, without trailing spaces.
+    pub fn row(&self, row: u16) -> String {
+        match self.screen.get(usize::from(row)) {
+            Some(cells) => cells.concat().trim_end_matches(' ').to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// Get the position of the cursor as `(column, row)`.
+    pub fn cursor(&self) -> (u16, u16) {
+        self.cursor
+    }
+
+    /// Whether the cursor is shown.
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Get the shape of the cursor, as the number that it was last set
+    /// to, with `0` for the shape that the terminal draws by default.
+    pub fn cursor_style(&self) -> u16 {
+        self.cursor_style
+    }
+
+    /// Whether the alternate screen is being shown.
+    pub fn altscreen(&self) -> bool {
+        self.main_screen.is_some()
+    }
+
+    /// Whether raw mode is enabled.
+    pub fn raw_mode(&self) -> bool {
+        self.raw_mode
+    }
+
+    /// Whether mouse events are reported.
+    pub fn mouse_capture(&self) -> bool {
+        self.mouse_capture
+    }
+
+    /// Whether pastes are marked as bracketed pastes.
+    pub fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
+    /// Get everything that was written to the backend,
+    /// including the escape sequences.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// Forget what was written so far, keeping the screen.
+    pub fn clear_output(&mut self) {
+        self.output.clear();
+    }
+
+    /// Interpret as much of the unparsed output as is complete.
+    fn parse(&mut self) {
+        let bytes = std::mem::take(&mut self.unparsed);
+        let (text, rest) = match std::str::from_utf8(&bytes) {
+            Ok(text) => (Cow::Borrowed(text), &[][..]),
+            // An incomplete character at the end is kept for later.
+            Err(e) if e.error_len().is_none() => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                (String::from_utf8_lossy(valid), rest)
+            }
+            Err(_) => (String::from_utf8_lossy(&bytes), &[][..]),
+        };
+        let valid = &*text;
+
+        let mut chars = valid.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            match c {
+                '\x1b' => match chars.next() {
+                    Some((_, '[')) => {
+                        let mut params = String::new();
+                        let mut end = None;
+                        for (_, c) in chars.by_ref() {
+                            if ('@'..='~').contains(&c) {
+                                end = Some(c);
+                                break;
+                            }
+                            params.push(c);
+                        }
+                        match end {
+                            Some(end) => self.control_sequence(&params, end),
+                            None => {
+                                self.unparsed.extend_from_slice(&valid.as_bytes()[start..]);
+                                break;
+                            }
+                        }
+                    }
+                    Some((_, '7')) => self.saved_cursor = self.cursor,
+                    Some((_, '8')) => self.move_cursor(self.saved_cursor),
+                    Some(_) => {}
+                    None => {
+                        self.unparsed.push(b'\x1b');
+                        break;
+                    }
+                },
+                '\n' => self.line_feed(),
+                '\r' => self.move_cursor((0, self.cursor.1)),
+                '\x08' => self.move_cursor((self.cursor.0.saturating_sub(1), self.cursor.1)),
+                c if c.is_control() => {}
+                c => self.print(c),
+            }
+        }
+        self.unparsed.extend_from_slice(rest);
+    }
+
+    /// Apply the escape sequence `ESC [ params end`.
+    fn control_sequence(&mut self, params: &str, end: char) {
+        let private = params.starts_with('?');
+        let args: Vec<u16> = params
+            .trim_start_matches('?')
+            .split(';')
+            .map(|arg| arg.parse().unwrap_or(0))
+            .collect();
+        let arg = |i: usize| args.get(i).copied().unwrap_or(0);
+        // Movement treats a missing or zero count as one.
+        let count = arg(0).max(1);
+        let (col, row) = self.cursor;
+
+        match end {
+            'A' => self.move_cursor((col, row.saturating_sub(count))),
+            'B' => self.move_cursor((col, row.saturating_add(count))),
+            'C' => self.move_cursor((col.saturating_add(count), row)),
+            'D' => self.move_cursor((col.saturating_sub(count), row)),
+            'E' => self.move_cursor((0, row.saturating_add(count))),
+            'F' => self.move_cursor((0, row.saturating_sub(count))),
+            'G' => self.move_cursor((count - 1, row)),
+            'd' => self.move_cursor((col, count - 1)),
+            'H' | 'f' => self.move_cursor((arg(1).max(1) - 1, count - 1)),
+            'J' => match arg(0) {
+                0 => {
+                    self.clear_row(row, col..self.columns);
+                    (row + 1..self.rows).for_each(|row| self.clear_row(row, 0..self.columns));
+                }
+                1 => {
+                    (0..row).for_each(|row| self.clear_row(row, 0..self.columns));
+                    self.clear_row(row, 0..col + 1);
+                }
+                _ => self.screen = blank_screen(self.columns, self.rows),
+            },
+            'K' => match arg(0) {
+                0 => self.clear_row(row, col..self.columns),
+                1 => self.clear_row(row, 0..col + 1),
+                _ => self.clear_row(row, 0..self.columns),
+            },
+            'S' => (0..count).for_each(|_| self.scroll_up()),
+            'q' if params.ends_with(' ') => {
+                self.cursor_style = params.trim_end().parse().unwrap_or(0);
+            }
+            's' => self.saved_cursor = self.cursor,
+            'u' => self.move_cursor(self.saved_cursor),
+            'h' | 'l' if private => {
+                let set = end == 'h';
+                for &mode in &args {
+                    match mode {
+                        25 => self.cursor_visible = set,
+                        1000 => self.mouse_capture = set,
+                        1049 => self.set_altscreen(set),
+                        2004 => self.bracketed_paste = set,
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn move_cursor(&mut self, (col, row): (u16, u16)) {
+        self.cursor = (col.min(self.columns - 1), row.min(self.rows - 1));
+        self.wrap_pending = false;
+    }
+
+    fn line_feed(&mut self) {
+        if self.cursor.1 + 1 == self.rows {
+            self.scroll_up();
+        } else {
+            self.cursor.1 += 1;
+        }
+        self.wrap_pending = false;
+    }
+
+    fn scroll_up(&mut self) {
+        self.screen.remove(0);
+        self.screen.push(blank_row(self.columns));
+    }
+
+    fn print(&mut self, c: char) {
+        let width = char_width(c) as u16;
+        if width == 0 {
+            // Combining characters join the cell before the cursor.
+            let (col, row) = self.cursor;
+            let col = if self.wrap_pending {
+                col
+            } else {
+                col.saturating_sub(1)
+            };
+            let cells = &mut self.screen[usize::from(row)];
+            let cell = cells[..=usize::from(col)]
+                .iter_mut()
+                .rev()
+                .find(|cell| !cell.is_empty());
+            if let Some(cell) = cell {
+                cell.push(c);
+            }
+            return;
+        }
+
+        if self.wrap_pending || self.cursor.0 + width > self.columns {
+            self.cursor.0 = 0;
+            self.line_feed();
+        }
+        let (col, row) = self.cursor;
+        self.clear_row(row, col..col + width);
+        let cells = &mut self.screen[usize::from(row)];
+        cells[usize::from(col)] = c.to_string();
+        for cell in &mut cells[usize::from(col) + 1..usize::from(col + width)] {
+            cell.clear();
+        }
+
+        if col + width == self.columns {
+            self.cursor.0 = self.columns - 1;
+            self.wrap_pending = true;
+        } else {
+            self.cursor.0 = col + width;
+        }
+    }
+
+    /// Blank out a range of columns of a row, along with
+    /// the rest of any wide character that the range cuts through.
+    fn clear_row(&mut self, row: u16, cols: std::ops::Range<u16>) {
+        let cells = &mut self.screen[usize::from(row)];
+        let start = usize::from(cols.start.min(self.columns));
+        let end = usize::from(cols.end.min(self.columns));
+        if start >= end {
+            return;
+        }
+        let start = cells[..=start]
+            .iter()
+            .rposition(|cell| !cell.is_empty())
+            .unwrap_or(start);
+        let end = end
+            + cells[end..]
+                .iter()
+                .take_while(|cell| cell.is_empty())
+                .count();
+        for cell in &mut cells[start..end] {
+            *cell = String::from(" ");
+        }
+    }
+
+    fn set_altscreen(&mut self, altscreen: bool) {
+        if altscreen && self.main_screen.is_none() {
+            let blank = blank_screen(self.columns, self.rows);
+            self.main_screen = Some(std::mem::replace(&mut self.screen, blank));
+        } else if let (false, Some(main_screen)) = (altscreen, self.main_screen.take()) {
+            self.screen = main_screen;
+        }
+    }
+}
+
+pub(crate) fn key_events(text: &str) -> impl Iterator<Item = Event> + '_ {
+    text.chars().map(|c| {
+        let code = match c {
+            '\n' => KeyCode::Enter,
+            '\t' => KeyCode::Tab,
+            c => KeyCode::Char(c),
+        };
+        Event::Key(KeyEvent::from(code))
+    })
+}
+
+fn blank_row(columns: u16) -> Vec<String> {
+    vec![String::from(" "); columns.into()]
+}
+
+fn blank_screen(columns: u16, rows: u16) -> Vec<Vec<String>> {
+    vec![blank_row(columns); rows.into()]
+}
+
+impl Write for TestBackend {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.extend_from_slice(buf);
+        self.written += buf.len() as u64;
+        self.unparsed.extend_from_slice(buf);
+        self.parse();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Backend for TestBackend {
+    fn read_event(&mut self) -> Result<Event> {
+        loop {
+            match self.read_input()? {
+                Input::Event(event) => return Ok(event),
+                Input::Paste(text) => {
+                    let keys: Vec<_> = key_events(&text).collect();
+                    for event in keys.into_iter().rev() {
+                        self.events.push_front((Input::Event(event), true));
+                    }
+                }
+            }
+        }
+    }
+
+    fn read_input(&mut self) -> Result<Input> {
+        self.events
+            .pop_front()
+            .map(|(input, _)| input)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof).into())
+    }
+
+    /// Events that were pushed separately only count as available
+    /// once there is time to wait for them.
+    fn poll_event(&mut self, timeout: Duration) -> Result<bool> {
+        Ok(match self.events.front() {
+            Some(&(_, batched)) => batched || timeout > Duration::from_secs(0),
+            None => false,
+        })
+    }
+
+    fn size(&self) -> Result<(u16, u16)> {
+        Ok((self.columns, self.rows))
+    }
+
+    fn cursor_position(&mut self) -> Result<(u16, u16)> {
+        Ok(self.cursor)
+    }
+
+    fn enable_raw_mode(&mut self) -> Result<()> {
+        self.raw_mode = true;
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<()> {
+        self.raw_mode = false;
+        Ok(())
+    }
+
+    fn supports_bracketed_paste(&self) -> bool {
+        true
+    }
+
+    fn bytes_written(&self) -> Option<u64> {
+        Some(self.written)
+    }
+}
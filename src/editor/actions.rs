@@ -0,0 +1,418 @@
+use super::{indent::TabBehavior, macros::Recorded, Editor, ReadResult};
+use crate::Error;
+
+/// An editing operation that a keybinding can dispatch to the editor.
+///
+/// Movement actions carry a flag that, when `true`, extends the
+/// selection instead of collapsing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Type a character at the cursor, pairing brackets and quotes
+    /// if the editor is set to. A space expands the snippet before it.
+    InsertChar(char),
+    /// Insert a new line at the cursor, indented by the indenter,
+    /// or submit if the editor is set to [`SubmitPolicy::AltEnter`].
+    InsertNewline,
+    /// Submit if the [`SubmitPolicy`] allows it and the validator
+    /// accepts the contents, otherwise insert a new line.
+    NewlineOrSubmit,
+    /// Insert a tab, or spaces up to the next tab stop.
+    Indent,
+    /// Expand the snippet before the cursor, accept the hint, or complete
+    /// the text before the cursor or cycle to the next candidate,
+    /// indenting if there is nothing to complete and the
+    /// [`TabBehavior`] allows it.
+    Complete,
+    /// Cycle to the previous completion candidate,
+    /// dedenting if there is no completion in progress.
+    CompletePrevious,
+    /// Remove up to one level of leading indentation.
+    Dedent,
+    /// Delete the character before the cursor, or the selection.
+    DeleteBackward,
+    /// Delete the character under the cursor, or the selection.
+    DeleteForward,
+    /// Delete back to the start of the previous word, or the selection.
+    DeleteWordBackward,
+    /// Delete up to the end of the next word, or the selection.
+    DeleteWordForward,
+    /// Cut from the cursor to the end of the line, or the new-line
+    /// if the cursor is already there.
+    KillToLineEnd,
+    /// Cut from the start of the line to the cursor.
+    KillToLineStart,
+    /// Cut up to the end of the next word.
+    KillWordForward,
+    /// Cut back to the start of the previous word.
+    KillWordBackward,
+    /// Swap the character before the cursor with the one under it.
+    TransposeChars,
+    /// Uppercase up to the end of the next word.
+    UpcaseWord,
+    /// Lowercase up to the end of the next word.
+    DowncaseWord,
+    /// Capitalize the next word.
+    CapitalizeWord,
+    MoveLeft(bool),
+    MoveRight(bool),
+    /// Move to the start of the previous word.
+    MoveWordLeft(bool),
+    /// Move to the end of the next word.
+    MoveWordRight(bool),
+    /// Move up, or to the previous history entry from the first line.
+    MoveUp(bool),
+    /// Move down, or to the next history entry from the last line.
+    MoveDown(bool),
+    /// Toggle between the first non-whitespace character and the line start.
+    MoveHome(bool),
+    MoveEnd(bool),
+    /// Move to the start of the line.
+    MoveLineStart(bool),
+    /// Swap the selected lines, or the cursor line, with the line above.
+    MoveLinesUp,
+    /// Swap the selected lines, or the cursor line, with the line below.
+    MoveLinesDown,
+    /// Insert a copy of the selected lines, or the cursor line, below them.
+    DuplicateLines,
+    /// Delete the selected lines, or the cursor line.
+    DeleteLines,
+    /// Join the cursor line with the next, collapsing the whitespace
+    /// between them to a single space.
+    JoinLines,
+    /// Break the cursor line at the cursor, keeping the cursor in place.
+    SplitLine,
+    /// Comment out the selected lines, or the cursor line, with the
+    /// comment prefix, or uncomment them if they all have it.
+    ToggleComment,
+    /// Move up by a page of the lines that are shown.
+    PageUp(bool),
+    /// Move down by a page of the lines that are shown.
+    PageDown(bool),
+    /// Move to the first line.
+    MoveToTop,
+    /// Move to the last line.
+    MoveToBottom,
+    /// Move to the bracket that pairs with the one under or before
+    /// the cursor, if there is one.
+    MoveToMatchingBracket(bool),
+    /// Replace the buffer with the previous history entry.
+    HistoryPrevious,
+    /// Replace the buffer with the next history entry.
+    HistoryNext,
+    /// Start a reverse search through the history, or jump to the next
+    /// older match. While searching, typing edits the query, `Enter`
+    /// accepts the match, `Submit` aborts, and other actions accept the
+    /// match before being applied.
+    SearchHistory,
+    /// Start an incremental search through the buffer, or jump to the
+    /// next match. While searching, typing edits the query,
+    /// `SearchHistory` jumps to the previous match, `Enter` and `Submit`
+    /// stop at the match, `Interrupt` goes back to where the search
+    /// started, and other actions stop at the match before being applied.
+    SearchBuffer,
+    /// Select the word under the cursor, or add a cursor at the next
+    /// occurrence of the selected text. Edits and movement are then
+    /// repeated at every cursor, and `Submit` goes back to one cursor.
+    AddNextMatch,
+    /// Undo the last group of edits.
+    Undo,
+    /// Redo the last group of undone edits.
+    Redo,
+    /// Toggle the fullscreen mode.
+    ToggleAltscreen,
+    /// Stop the process to go back to the shell on Unix, restoring the
+    /// terminal, and redraw the prompt once it is continued. Only
+    /// [`EmacsKeybinding`](super::keybindings::EmacsKeybinding) binds it to
+    /// `Ctrl-Z`, which undoes with the other keybindings.
+    ///
+    /// The terminal is only restored by this action. Raw mode keeps
+    /// `Ctrl-Z` from stopping the process, but a `SIGTSTP` sent to it from
+    /// elsewhere, such as with `kill -TSTP`, stops it in raw mode.
+    Suspend,
+    /// Edit the contents in the external editor of `$VISUAL` or `$EDITOR`,
+    /// replacing them with the file that it saves.
+    EditExternally,
+    /// Start recording the actions that follow as the keyboard macro.
+    StartMacro,
+    /// Stop recording the keyboard macro.
+    EndMacro,
+    /// Apply the actions of the keyboard macro again.
+    ReplayMacro,
+    /// Start or stop extending the selection with plain movement.
+    ToggleMark,
+    /// Copy the selection, or the current line, to the kill ring.
+    Copy,
+    /// Cut the selection, or the current line, to the kill ring.
+    Cut,
+    /// Insert the newest entry of the kill ring at the cursor.
+    /// Successive kills are joined into a single entry.
+    Paste,
+    /// Right after `Paste`, replace the text it inserted with the entry
+    /// before it in the kill ring, going further back each time.
+    YankPop,
+    /// Copy the selection, or the whole buffer, to the system clipboard.
+    #[cfg(feature = "clipboard")]
+    ClipboardCopy,
+    /// Insert the contents of the system clipboard at the cursor.
+    #[cfg(feature = "clipboard")]
+    ClipboardPaste,
+    /// Close the editor and submit the contents, once the submit
+    /// is confirmed if the editor asks for it.
+    Submit,
+    /// Copy the selection if there is one, otherwise do what the
+    /// editor is configured to do on an [`Interrupt`].
+    Interrupt,
+    /// End the input if the buffer is empty, otherwise do what the
+    /// editor is configured to do on [`EndOfInput`].
+    EndOfInput,
+    /// Add a digit to the numeric argument, which repeats the next
+    /// action that many times. Typed digits add to it as well.
+    DigitArgument(u8),
+    /// Start the numeric argument at four, or multiply it by four.
+    UniversalArgument,
+}
+
+/// What [`Action::Interrupt`] does without a selection to copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Interrupt {
+    /// Clear the buffer and keep reading.
+    Clear,
+    /// End the read with [`ReadResult::Cancelled`].
+    Cancel,
+    /// End the read with [`Error::Interrupted`].
+    Error,
+}
+
+/// What [`Action::EndOfInput`] does when the buffer is not empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndOfInput {
+    /// Delete the character under the cursor.
+    DeleteForward,
+    /// Submit the contents.
+    Submit,
+}
+
+/// When [`Action::NewlineOrSubmit`], bound to `Enter`, submits
+/// instead of inserting a new line.
+#[derive(Debug, Clone, Copy)]
+pub enum SubmitPolicy {
+    /// Submit when the cursor is on an empty last line, which is the
+    /// default.
+    EmptyLastLine,
+    /// Submit when the cursor is on a last line of only whitespace, such
+    /// as one that the [indenter](Editor::indenter) indented.
+    BlankLastLine,
+    /// Submit when the cursor is on a blank last line after another
+    /// blank line, so single blank lines can be typed.
+    TwoBlankLines,
+    /// Always submit, leaving [`Action::InsertNewline`] to insert new lines.
+    Enter,
+    /// Never submit, and submit with [`Action::InsertNewline`],
+    /// bound to `Alt-Enter`, instead.
+    AltEnter,
+    /// Submit when the function returns `true`.
+    Custom(fn(&Editor) -> bool),
+}
+
+impl Editor {
+    /// Whether `Enter` should submit the contents under the submit policy.
+    fn wants_submit(&self) -> bool {
+        let ln = self.selection.focus.ln;
+        let last_line = ln + 1 == self.line_count();
+        let blank = |ln| self.line(ln).chars().all(char::is_whitespace);
+        match self.submit_policy {
+            SubmitPolicy::EmptyLastLine => last_line && self.line(ln).is_empty(),
+            SubmitPolicy::BlankLastLine => last_line && blank(ln),
+            SubmitPolicy::TwoBlankLines => last_line && ln > 0 && blank(ln) && blank(ln - 1),
+            SubmitPolicy::Enter => true,
+            SubmitPolicy::AltEnter => false,
+            SubmitPolicy::Custom(submit) => submit(self),
+        }
+    }
+
+    /// Apply an action to the editor.
+    ///
+    /// Returns `false` if the action ends the read.
+    pub fn apply(&mut self, action: Action) -> bool {
+        self.record(Recorded::Action(action));
+        if let Some(proceed) = self.apply_confirmation(action) {
+            return proceed;
+        }
+        if let Some(proceed) = self.apply_search(action) {
+            return proceed;
+        }
+        if let Some(proceed) = self.apply_buffer_search(action) {
+            return proceed;
+        }
+        if let Some(proceed) = self.apply_argument(action) {
+            return proceed;
+        }
+        let action = match self.filter_input(action) {
+            Some(action) => action,
+            None => return true,
+        };
+        if !matches!(action, Action::Complete | Action::CompletePrevious) {
+            self.dismiss_completion();
+        }
+        self.validation_error = None;
+
+        // Repeated edits are undone together, unlike repeated undos.
+        let count = self.take_count(action);
+        let grouped = count > 1 && !matches!(action, Action::Undo | Action::Redo);
+        if grouped {
+            self.undo.begin(self.selection);
+        }
+        let mut proceed = true;
+        for _ in 0..count {
+            proceed = match self.apply_at_cursors(action) {
+                Some(proceed) => proceed,
+                None => self.dispatch(action),
+            };
+            self.kill_ring.settle(action);
+            if !proceed {
+                break;
+            }
+        }
+        if grouped {
+            self.end_edit();
+        }
+        self.restore_rejected();
+        proceed
+    }
+
+    fn dispatch(&mut self, action: Action) -> bool {
+        match action {
+            Action::InsertChar(' ') if self.expand_snippet_on_space() => {}
+            Action::InsertChar(c) => self.type_paired(c),
+            Action::InsertNewline if matches!(self.submit_policy, SubmitPolicy::AltEnter) => {
+                return !self.try_submit();
+            }
+            Action::InsertNewline => self.insert_newline(),
+            Action::NewlineOrSubmit => {
+                if self.wants_submit() {
+                    return !self.try_submit();
+                } else {
+                    self.insert_newline();
+                }
+            }
+            Action::Indent => self.indent(),
+            Action::Dedent => self.dedent(),
+            Action::Complete => {
+                let completed = self.expand_snippet() || self.accept_hint() || self.complete();
+                if !completed && self.tab_behavior != TabBehavior::Completion {
+                    self.indent();
+                }
+            }
+            Action::CompletePrevious => {
+                if !self.cycle_completion(false) {
+                    self.dedent();
+                }
+            }
+            Action::DeleteBackward => {
+                if !self.delete_pair() && !self.delete_soft_tab() {
+                    self.backspace()
+                }
+            }
+            Action::DeleteForward => self.delete(),
+            Action::DeleteWordBackward => self.delete_word_backward(),
+            Action::DeleteWordForward => self.delete_word_forward(),
+            Action::KillToLineEnd => self.kill_to_line_end(),
+            Action::KillToLineStart => self.kill_to_line_start(),
+            Action::KillWordForward => self.kill_word_forward(),
+            Action::KillWordBackward => self.kill_word_backward(),
+            Action::TransposeChars => self.transpose_chars(),
+            Action::UpcaseWord => self.upcase_word(),
+            Action::DowncaseWord => self.downcase_word(),
+            Action::CapitalizeWord => self.capitalize_word(),
+            Action::MoveLeft(anchored) => self.move_left(anchored),
+            Action::MoveRight(anchored) => {
+                if anchored || !self.accept_hint() {
+                    self.move_right(anchored)
+                }
+            }
+            Action::MoveWordLeft(anchored) => self.move_word_left(anchored),
+            Action::MoveWordRight(anchored) => self.move_word_right(anchored),
+            Action::MoveUp(anchored) => {
+                if anchored || self.selection.focus.ln > 0 || !self.history_previous() {
+                    self.move_up(anchored)
+                }
+            }
+            Action::MoveDown(anchored) => {
+                let last_line = self.selection.focus.ln + 1 == self.line_count();
+                if anchored || !last_line || !self.history_next() {
+                    self.move_down(anchored)
+                }
+            }
+            Action::HistoryPrevious => {
+                self.history_previous();
+            }
+            Action::HistoryNext => {
+                self.history_next();
+            }
+            Action::SearchHistory => self.search_history(),
+            Action::SearchBuffer => self.search_buffer(),
+            Action::AddNextMatch => self.add_next_match(),
+            Action::MoveHome(anchored) => self.move_home(anchored),
+            Action::MoveEnd(anchored) => {
+                if anchored || !self.accept_hint() {
+                    self.move_to_line_end(anchored)
+                }
+            }
+            Action::MoveLineStart(anchored) => self.move_to_col(0, anchored),
+            Action::MoveLinesUp => self.move_lines_up(),
+            Action::MoveLinesDown => self.move_lines_down(),
+            Action::DuplicateLines => self.duplicate_lines(),
+            Action::DeleteLines => self.delete_lines(),
+            Action::JoinLines => self.join_lines(),
+            Action::SplitLine => self.split_line(),
+            Action::ToggleComment => self.toggle_comment(),
+            Action::PageUp(anchored) => self.move_page_up(anchored),
+            Action::PageDown(anchored) => self.move_page_down(anchored),
+            Action::MoveToTop => self.move_to_top(),
+            Action::MoveToBottom => self.move_to_bottom(),
+            Action::MoveToMatchingBracket(anchored) => {
+                self.move_to_matching_bracket(anchored);
+            }
+            Action::Undo => {
+                self.undo();
+            }
+            Action::Redo => {
+                self.redo();
+            }
+            Action::ToggleAltscreen => self.altscreen = !self.altscreen,
+            Action::Suspend => self.suspend(),
+            Action::EditExternally => self.edit_externally(),
+            Action::StartMacro => self.start_macro(),
+            Action::EndMacro => self.end_macro(),
+            Action::ReplayMacro => return self.replay_macro(),
+            Action::ToggleMark => self.toggle_mark(),
+            Action::Copy => self.copy(),
+            Action::Cut => self.cut(),
+            Action::Paste => self.paste(),
+            Action::YankPop => self.yank_pop(),
+            #[cfg(feature = "clipboard")]
+            Action::ClipboardCopy => {
+                self.copy_to_clipboard();
+            }
+            #[cfg(feature = "clipboard")]
+            Action::ClipboardPaste => {
+                self.paste_from_clipboard();
+            }
+            Action::Submit => return self.ask_confirmation(),
+            Action::Interrupt => match self.interrupt {
+                _ if self.selection.anchor.is_some() => self.copy(),
+                Interrupt::Clear => self.replace_contents(""),
+                Interrupt::Cancel => return self.end_read(Ok(ReadResult::Cancelled)),
+                Interrupt::Error => return self.end_read(Err(Error::Interrupted)),
+            },
+            Action::EndOfInput => match self.end_of_input {
+                _ if self.buf.len_chars() == 0 => return self.end_read(Ok(ReadResult::Eof)),
+                EndOfInput::DeleteForward => self.delete(),
+                EndOfInput::Submit => return self.ask_confirmation(),
+            },
+            // Numeric arguments are taken before the action is dispatched.
+            Action::DigitArgument(_) | Action::UniversalArgument => {}
+        }
+        true
+    }
+}
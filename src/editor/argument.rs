@@ -0,0 +1,83 @@
+use super::{actions::Action, Editor};
+
+/// Largest number of times that an action is repeated.
+const MAX_COUNT: usize = 10_000;
+
+/// Numeric argument that is being typed before an action.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Argument {
+    /// Digits typed so far, if any.
+    digits: Option<usize>,
+    /// Product of the universal arguments typed so far.
+    universal: usize,
+}
+
+impl Argument {
+    /// Number of times to repeat the action, where universal
+    /// arguments without digits count four times each.
+    fn count(self) -> usize {
+        self.digits.unwrap_or(self.universal).min(MAX_COUNT)
+    }
+}
+
+/// Whether an action can be repeated by a numeric argument.
+fn repeatable(action: Action) -> bool {
+    !matches!(
+        action,
+        Action::NewlineOrSubmit
+            | Action::Complete
+            | Action::CompletePrevious
+            | Action::ToggleComment
+            | Action::SearchHistory
+            | Action::SearchBuffer
+            | Action::AddNextMatch
+            | Action::ToggleAltscreen
+            | Action::Suspend
+            | Action::EditExternally
+            | Action::StartMacro
+            | Action::EndMacro
+            | Action::ToggleMark
+            | Action::Copy
+            | Action::Cut
+            | Action::Submit
+            | Action::Interrupt
+            | Action::EndOfInput
+    )
+}
+
+impl Editor {
+    /// Get the numeric argument that the next action will be repeated by,
+    /// while it is being typed.
+    pub fn argument(&self) -> Option<usize> {
+        self.argument.map(Argument::count)
+    }
+
+    /// Apply an action that types the numeric argument, returning `None`
+    /// if it should be applied to the editor as usual. Digits typed after
+    /// a numeric argument add to it.
+    pub(crate) fn apply_argument(&mut self, action: Action) -> Option<bool> {
+        let digit = match action {
+            Action::DigitArgument(digit) => digit.min(9) as usize,
+            Action::InsertChar(c) if self.argument.is_some() => c.to_digit(10)? as usize,
+            Action::UniversalArgument => {
+                let argument = self.argument.get_or_insert_with(Argument::default);
+                argument.universal = argument.universal.max(1).saturating_mul(4);
+                return Some(true);
+            }
+            _ => return None,
+        };
+        let argument = self.argument.get_or_insert_with(Argument::default);
+        let digits = argument.digits.unwrap_or(0);
+        argument.digits = Some(digits.saturating_mul(10).saturating_add(digit));
+        Some(true)
+    }
+
+    /// Take the number of times to apply an action, which is one
+    /// without a numeric argument or if the action is not repeatable.
+    pub(crate) fn take_count(&mut self, action: Action) -> usize {
+        match self.argument.take() {
+            Some(argument) if repeatable(action) => argument.count(),
+            _ => 1,
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use super::{external::private_options, Editor};
+use crate::Result;
+
+/// Saving of the input to a recovery file while it is edited, so that a
+/// long input is not lost when the terminal or the connection dies before
+/// it is submitted.
+///
+/// The contents are saved once enough edits were made since they were
+/// last saved, or once the interval passed with edits left unsaved. The
+/// recovery file is removed when the read ends, unless the terminal fails
+/// it such as when the connection dies, so it is only left behind by a
+/// read that never ended. On unix, a recovery file that it creates can
+/// only be read by the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Autosave {
+    path: PathBuf,
+    every_edits: usize,
+    interval: Option<Duration>,
+    /// Edits made since the contents were last saved.
+    unsaved: usize,
+    saved_at: Option<Instant>,
+}
+
+impl Autosave {
+    /// Save to a recovery file after every edit.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            every_edits: 1,
+            interval: None,
+            unsaved: 0,
+            saved_at: None,
+        }
+    }
+
+    /// Save once this many edits were made since the last save.
+    pub fn every_edits(self, every_edits: usize) -> Self {
+        Self {
+            every_edits: every_edits.max(1),
+            ..self
+        }
+    }
+
+    /// Also save the edits that are left unsaved once this much time
+    /// passed since the last save, even while no keys are pressed.
+    pub fn interval(self, interval: Duration) -> Self {
+        Self {
+            interval: Some(interval),
+            ..self
+        }
+    }
+
+    /// Get the path of the recovery file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the input that a read left behind in the recovery file,
+    /// such as to ask whether to restore it.
+    pub fn recovered(&self) -> Result<Option<String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Remove the recovery file, if there is one.
+    pub fn discard(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Time left until the unsaved edits are due to be saved.
+    pub(crate) fn due_in(&self) -> Option<Duration> {
+        match (self.unsaved, self.interval) {
+            (0, _) | (_, None) => None,
+            (_, Some(interval)) => Some(match self.saved_at {
+                Some(saved_at) => interval.saturating_sub(saved_at.elapsed()),
+                None => Duration::from_secs(0),
+            }),
+        }
+    }
+
+    fn save(&mut self, contents: &str) -> Result<()> {
+        private_options()
+            .create(true)
+            .truncate(true)
+            .open(&self.path)?
+            .write_all(contents.as_bytes())?;
+        self.unsaved = 0;
+        self.saved_at = Some(Instant::now());
+        Ok(())
+    }
+}
+
+impl Editor {
+    /// Save the input to a recovery file while it is edited. Masked
+    /// input, such as a password, is never saved.
+    pub fn autosave(self, autosave: Autosave) -> Self {
+        Self {
+            autosave: Some(autosave),
+            ..self
+        }
+    }
+
+    /// Start with the input that a read left behind in the recovery file
+    /// of the autosave, if there is one, as an edit that can be undone to
+    /// start over without it.
+    ///
+    /// This has to come after [`Editor::autosave`], and does nothing
+    /// without it.
+    pub fn restore_autosave(mut self) -> Result<Self> {
+        let recovered = match &self.autosave {
+            Some(autosave) => autosave.recovered()?,
+            None => None,
+        };
+        if let Some(recovered) = recovered {
+            self.replace_contents(&recovered);
+            self.undo.seal();
+        }
+        Ok(self)
+    }
+
+    /// Count edits towards the next save, saving if enough were made.
+    pub(crate) fn autosave_edits(&mut self, edits: usize) -> Result<()> {
+        if edits == 0 || self.mask.is_some() {
+            return Ok(());
+        }
+        if let Some(autosave) = &mut self.autosave {
+            autosave.unsaved += edits;
+        }
+        match &self.autosave {
+            Some(autosave) if autosave.unsaved >= autosave.every_edits => self.save_autosave(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Save the unsaved edits if the interval passed since the last save.
+    pub(crate) fn autosave_due(&mut self) -> Result<()> {
+        match self.autosave.as_ref().and_then(Autosave::due_in) {
+            Some(left) if left == Duration::from_secs(0) => self.save_autosave(),
+            _ => Ok(()),
+        }
+    }
+
+    fn save_autosave(&mut self) -> Result<()> {
+        let contents = self.contents();
+        match &mut self.autosave {
+            Some(autosave) => autosave.save(&contents),
+            None => Ok(()),
+        }
+    }
+
+    /// Remove the recovery file once the read ended.
+    pub(crate) fn end_autosave(&mut self) -> Result<()> {
+        match &mut self.autosave {
+            Some(autosave) => {
+                autosave.unsaved = 0;
+                autosave.saved_at = None;
+                autosave.discard()
+            }
+            None => Ok(()),
+        }
+    }
+}
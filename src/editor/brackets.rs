@@ -0,0 +1,168 @@
+use super::{selection::Cursor, Editor};
+use crate::util::trimmed;
+
+/// Brackets and quotes that are paired by default.
+pub const DEFAULT_PAIRS: &[(char, char)] =
+    &[('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\'')];
+
+impl Editor {
+    /// Find the bracket under or before the cursor and the bracket that
+    /// it pairs with, which can be on another line.
+    ///
+    /// Quotes open and close with the same character, so they are never
+    /// matched.
+    pub fn bracket_match(&self) -> Option<(Cursor, Cursor)> {
+        let focus = self.selection.focus;
+        let line = trimmed(self.buf.line(focus.ln));
+        let col = focus.col.min(line.len_chars());
+        let under = Some(col).filter(|&col| col < line.len_chars());
+        let before = col.checked_sub(1);
+
+        let (col, pair, forward) = under.into_iter().chain(before).find_map(|col| {
+            let c = line.char(col);
+            self.pairs
+                .iter()
+                .filter(|(open, close)| open != close)
+                .find_map(|&(open, close)| match c {
+                    _ if c == open => Some((col, (open, close), true)),
+                    _ if c == close => Some((col, (open, close), false)),
+                    _ => None,
+                })
+        })?;
+
+        let from = self.buf.line_to_char(focus.ln) + col;
+        let (same, other) = if forward { pair } else { (pair.1, pair.0) };
+        let mut depth = 0usize;
+        let found = if forward {
+            self.buf
+                .chars_at(from + 1)
+                .enumerate()
+                .find(|&(_, c)| step(c, same, other, &mut depth))
+                .map(|(i, _)| from + 1 + i)
+        } else {
+            let mut chars = self.buf.chars_at(from);
+            std::iter::from_fn(|| chars.prev())
+                .enumerate()
+                .find(|&(_, c)| step(c, same, other, &mut depth))
+                .map(|(i, _)| from - 1 - i)
+        }?;
+
+        Some((Cursor { ln: focus.ln, col }, self.char_to_cursor(found)))
+    }
+
+    /// Move the cursor to the bracket that pairs with the one under or
+    /// before it, returning `false` if there is none.
+    pub fn move_to_matching_bracket(&mut self, anchored: bool) -> bool {
+        let (_, other) = match self.bracket_match() {
+            Some(brackets) => brackets,
+            None => return false,
+        };
+        self.selection.set_anchor(anchored);
+        self.selection.focus = other;
+        self.selection.fix_anchor();
+        true
+    }
+
+    /// Type a character, closing brackets and quotes that are opened,
+    /// typing over the closing ones that follow the cursor and
+    /// wrapping the selection if there is one.
+    pub(crate) fn type_paired(&mut self, c: char) {
+        if !self.auto_pair {
+            return self.type_char(c);
+        }
+        self.clamp();
+        let col = self.selection.focus.col;
+        let next = self.curr_ln_chars().nth(col);
+        let prev = col.checked_sub(1).and_then(|i| self.curr_ln_chars().nth(i));
+        let is_word = self.word_chars;
+
+        let closes = self.pairs.iter().any(|&(_, close)| close == c);
+        if self.selection.anchor.is_none() && closes && next == Some(c) {
+            self.selection.focus.col += 1;
+            return;
+        }
+
+        let (open, close) = match self.pairs.iter().find(|&&(open, _)| open == c) {
+            Some(&pair) => pair,
+            None => return self.type_char(c),
+        };
+        if let Some((start, end)) = self.selection.range() {
+            self.wrap_range(start, end, open, close);
+        } else if open != close || !(prev.is_some_and(is_word) || next.is_some_and(is_word)) {
+            // Quotes next to a word are more likely apostrophes.
+            self.undo.begin(self.selection);
+            self.type_char(open);
+            self.insert_char(0, close);
+            self.end_edit();
+        } else {
+            self.type_char(c);
+        }
+    }
+
+    /// Delete a pair of brackets or quotes that surrounds the cursor
+    /// with nothing in between.
+    ///
+    /// Returns `false` if there was no such pair.
+    pub(crate) fn delete_pair(&mut self) -> bool {
+        self.clamp();
+        let col = self.selection.focus.col;
+        if !self.auto_pair || self.selection.anchor.is_some() || col == 0 {
+            return false;
+        }
+        let surrounding = {
+            let mut chars = self.curr_ln_chars().skip(col - 1);
+            (chars.next(), chars.next())
+        };
+        let paired = self
+            .pairs
+            .iter()
+            .any(|&(open, close)| surrounding == (Some(open), Some(close)));
+        if paired {
+            self.undo.begin(self.selection);
+            self.delete_ln_range(col - 1, col + 1);
+            self.selection.focus.col = col - 1;
+            self.end_edit();
+        }
+        paired
+    }
+
+    fn wrap_range(&mut self, start: Cursor, end: Cursor, open: char, close: char) {
+        let clamp = |cursor: Cursor| Cursor {
+            col: cursor
+                .col
+                .min(trimmed(self.buf.line(cursor.ln)).len_chars()),
+            ..cursor
+        };
+        let (start, end) = (clamp(start), clamp(end));
+        // Keep the same text selected inside of the pair.
+        let shift = |cursor: Cursor| Cursor {
+            col: clamp(cursor).col + (cursor.ln == start.ln) as usize,
+            ..cursor
+        };
+        let focus = shift(self.selection.focus);
+        let anchor = self.selection.anchor.map(shift);
+
+        self.undo.begin(self.selection);
+        let end_idx = self.rope_idx(end, 0);
+        self.insert_text(end_idx, close.encode_utf8(&mut [0; 4]));
+        let start_idx = self.rope_idx(start, 0);
+        self.insert_text(start_idx, open.encode_utf8(&mut [0; 4]));
+        self.selection.focus = focus;
+        self.selection.anchor = anchor;
+        self.end_edit();
+    }
+}
+
+/// Track the nesting of a bracket, returning `true` on the one that
+/// closes the bracket that the search started from.
+fn step(c: char, same: char, other: char, depth: &mut usize) -> bool {
+    if c == same {
+        *depth += 1;
+    } else if c == other {
+        if *depth == 0 {
+            return true;
+        }
+        *depth -= 1;
+    }
+    false
+}
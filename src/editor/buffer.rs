@@ -0,0 +1,153 @@
+use super::{selection::Cursor, Editor};
+use crate::util::trimmed;
+
+/// A change that was applied to the buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delta {
+    /// Position where the change starts.
+    pub start: Cursor,
+    /// Text that was removed from `start`.
+    pub removed: String,
+    /// Text that was inserted at `start`.
+    pub inserted: String,
+}
+
+/// Handle for editing the buffer at any position, instead of at the cursor.
+///
+/// Every edit is recorded in the edit history and respects the read-only
+/// lines. The selection stays on the same text, moving along with it.
+/// Edits return `None` if they are out of range or rejected.
+pub struct Buffer<'e> {
+    editor: &'e mut Editor,
+}
+
+impl Buffer<'_> {
+    /// Insert text at a column of a line.
+    pub fn insert_str(&mut self, line: usize, col: usize, text: &str) -> Option<Delta> {
+        let at = self.cursor(line, col)?;
+        self.splice(at, at, text)
+    }
+
+    /// Delete the text from `start` up to `end`.
+    pub fn delete_range(&mut self, start: Cursor, end: Cursor) -> Option<Delta> {
+        let start = self.cursor(start.ln, start.col)?;
+        let end = self.cursor(end.ln, end.col)?;
+        self.splice(start.min(end), start.max(end), "")
+    }
+
+    /// Replace the contents of a line, keeping its new-line.
+    pub fn replace_line(&mut self, line: usize, text: &str) -> Option<Delta> {
+        let start = self.cursor(line, 0)?;
+        let end = self.cursor(line, usize::MAX)?;
+        self.splice(start, end, text)
+    }
+
+    /// Break a line in two at a column, dropping the whitespace around the
+    /// column and giving the new line the indentation of the line.
+    pub fn split_line(&mut self, line: usize, col: usize) -> Option<Delta> {
+        let at = self.cursor(line, col)?;
+        let chars: Vec<char> = self.editor.line(line).chars().collect();
+        let indent: String = chars.iter().take_while(|c| c.is_whitespace()).collect();
+        let before = chars[..at.col]
+            .iter()
+            .rev()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        let after = chars[at.col..]
+            .iter()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        // Whitespace that is all of the indentation stays put.
+        let before = if before == at.col { 0 } else { before };
+        let start = Cursor {
+            col: at.col - before,
+            ..at
+        };
+        let end = Cursor {
+            col: at.col + after,
+            ..at
+        };
+        self.splice(start, end, &format!("\n{}", indent))
+    }
+
+    /// Join a line with the line after it, collapsing the whitespace
+    /// between them to a single space. Nothing goes between them if
+    /// either of them is blank.
+    pub fn join_lines(&mut self, line: usize) -> Option<Delta> {
+        if line + 1 >= self.editor.line_count() {
+            return None;
+        }
+        let text = self.editor.line(line);
+        let kept = text.trim_end().chars().count();
+        let next = self.editor.line(line + 1);
+        let indent = next.chars().take_while(|c| c.is_whitespace()).count();
+        let blank = kept == 0 || next.trim().is_empty();
+        let start = self.cursor(line, kept)?;
+        let end = self.cursor(line + 1, indent)?;
+        self.splice(start, end, if blank { "" } else { " " })
+    }
+
+    /// Clamp a position to the line it is on.
+    fn cursor(&self, ln: usize, col: usize) -> Option<Cursor> {
+        if ln >= self.editor.line_count() {
+            return None;
+        }
+        let len = trimmed(self.editor.buf.line(ln)).len_chars();
+        Some(Cursor {
+            ln,
+            col: col.min(len),
+        })
+    }
+
+    fn splice(&mut self, start: Cursor, end: Cursor, text: &str) -> Option<Delta> {
+        let editor = &mut *self.editor;
+        let start_idx = editor.rope_idx(start, 0);
+        let end_idx = editor.rope_idx(end, 0);
+        let removed = editor.buf.slice(start_idx..end_idx).to_string();
+        let inserted = text.chars().count();
+
+        // Move the selection along with the text that it is on.
+        let shift = |editor: &Editor, cursor: Cursor| {
+            let cursor = Cursor {
+                col: cursor
+                    .col
+                    .min(trimmed(editor.buf.line(cursor.ln)).len_chars()),
+                ..cursor
+            };
+            match editor.rope_idx(cursor, 0) {
+                idx if idx >= end_idx => idx + inserted - (end_idx - start_idx),
+                idx if idx > start_idx => start_idx,
+                idx => idx,
+            }
+        };
+        let focus = shift(editor, editor.selection.focus);
+        let anchor = editor.selection.anchor.map(|anchor| shift(editor, anchor));
+
+        editor.undo.begin(editor.selection);
+        if start_idx < end_idx {
+            editor.remove_text(start_idx, end_idx);
+        }
+        if !text.is_empty() {
+            editor.insert_text(start_idx, text);
+        }
+        if !editor.end_edit() {
+            return None;
+        }
+
+        editor.selection.focus = editor.char_to_cursor(focus);
+        editor.selection.anchor = anchor.map(|anchor| editor.char_to_cursor(anchor));
+        editor.selection.fix_anchor();
+        Some(Delta {
+            start,
+            removed,
+            inserted: text.to_string(),
+        })
+    }
+}
+
+impl Editor {
+    /// Get a handle for editing the buffer at any position.
+    pub fn buffer_mut(&mut self) -> Buffer<'_> {
+        Buffer { editor: self }
+    }
+}
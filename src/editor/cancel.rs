@@ -0,0 +1,54 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use super::{Editor, ReadResult};
+
+/// Handle that cancels the read of the editor from another thread,
+/// such as when the connection that the input is for drops.
+///
+/// The handle can be cloned and sent to other threads.
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Cancel the read in progress, or the next one if the editor is not
+    /// reading. The read ends with [`ReadResult::Cancelled`] after clearing
+    /// the drawn editor, as if it were interrupted.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the cancellation has yet to end a read.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Editor {
+    /// Get a handle that cancels the read of the editor from another thread.
+    ///
+    /// Renderers have to support polling for events, as the editor checks
+    /// for a cancellation in between them.
+    pub fn cancellation_token(&mut self) -> CancellationToken {
+        let cancelled = self.cancelled.get_or_insert_with(Default::default);
+        CancellationToken {
+            cancelled: cancelled.clone(),
+        }
+    }
+
+    /// End the read if it was cancelled, returning `true` if it was.
+    pub(crate) fn check_cancelled(&mut self) -> bool {
+        let cancelled = match &self.cancelled {
+            Some(cancelled) => cancelled.swap(false, Ordering::SeqCst),
+            None => false,
+        };
+        if cancelled {
+            self.end_read(Ok(ReadResult::Cancelled));
+        }
+        cancelled
+    }
+}
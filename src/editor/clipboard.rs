@@ -0,0 +1,33 @@
+use arboard::Clipboard;
+
+use super::Editor;
+
+impl Editor {
+    /// Copy the selection, or the whole buffer, to the system clipboard.
+    ///
+    /// Returns `false` if the clipboard could not be reached.
+    pub fn copy_to_clipboard(&mut self) -> bool {
+        let text = match self.curr_sel() {
+            Some(text) => text.to_string(),
+            None => self.contents(),
+        };
+        Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text))
+            .is_ok()
+    }
+
+    /// Insert the contents of the system clipboard at the cursor.
+    ///
+    /// Returns `false` if the clipboard could not be reached
+    /// or does not contain text.
+    pub fn paste_from_clipboard(&mut self) -> bool {
+        match Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+            Ok(text) => {
+                self.selection.marking = false;
+                self.insert_str(&text.replace("\r\n", "\n"));
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use super::Editor;
+
+/// A candidate that completes the text before the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// Column of the current line where the completed text starts.
+    pub start: usize,
+    /// Text that replaces everything from `start` up to the cursor.
+    pub text: String,
+}
+
+/// Provides completions for the editor.
+pub trait Completer {
+    /// List the candidates for the text before the cursor.
+    fn complete(&self, editor: &Editor) -> Vec<Completion>;
+}
+
+impl<F: Fn(&Editor) -> Vec<Completion>> Completer for F {
+    fn complete(&self, editor: &Editor) -> Vec<Completion> {
+        self(editor)
+    }
+}
+
+/// Candidates that are being cycled through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionState {
+    candidates: Vec<Completion>,
+    selected: Option<usize>,
+    ln: usize,
+    /// Earliest column that any candidate replaces from.
+    start: usize,
+    /// Column after the inserted text.
+    end: usize,
+    /// Text that was typed between `start` and the cursor.
+    original: String,
+}
+
+impl CompletionState {
+    /// Get the candidates.
+    pub fn candidates(&self) -> &[Completion] {
+        &self.candidates
+    }
+
+    /// Get the index of the inserted candidate, or `None`
+    /// if only their common prefix was inserted.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+}
+
+fn common_prefix(candidates: &[Completion]) -> Option<&str> {
+    let first = candidates.first()?;
+    if candidates.iter().any(|c| c.start != first.start) {
+        return None;
+    }
+    let len = candidates[1..].iter().fold(first.text.len(), |len, c| {
+        first.text[..len]
+            .char_indices()
+            .zip(c.text.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(c.text.len()), |((i, _), _)| i)
+    });
+    Some(&first.text[..len])
+}
+
+impl Editor {
+    /// Complete the text before the cursor, or cycle to the next candidate
+    /// if already completing.
+    ///
+    /// Returns `false` if there was nothing to complete.
+    pub fn complete(&mut self) -> bool {
+        if self.completion.is_some() {
+            self.cycle_completion(true);
+            return true;
+        }
+
+        let candidates = match &self.completer {
+            Some(completer) => completer.complete(self),
+            None => return false,
+        };
+
+        self.clamp();
+        let col = self.selection.focus.col;
+        let start = match candidates.iter().map(|c| c.start.min(col)).min() {
+            Some(start) => start,
+            None => return false,
+        };
+        let original: String = self.curr_ln_chars().skip(start).take(col - start).collect();
+
+        if let [candidate] = candidates.as_slice() {
+            self.replace_ln_range(candidate.start.min(col), col, &candidate.text);
+            return true;
+        }
+
+        if let Some(prefix) = common_prefix(&candidates) {
+            let prefix = prefix.to_string();
+            self.replace_ln_range(start, col, &prefix);
+        }
+
+        self.completion = Some(CompletionState {
+            candidates,
+            selected: None,
+            ln: self.selection.focus.ln,
+            start,
+            end: self.selection.focus.col,
+            original,
+        });
+        true
+    }
+
+    /// Cycle through the candidates, inserting the one that is selected.
+    ///
+    /// Returns `false` if there is no completion in progress.
+    pub fn cycle_completion(&mut self, forward: bool) -> bool {
+        let mut state = match self.completion.take() {
+            Some(state) => state,
+            None => return false,
+        };
+
+        let len = state.candidates.len();
+        let selected = match (state.selected, forward) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(i), true) => (i + 1) % len,
+            (Some(i), false) => (i + len - 1) % len,
+        };
+
+        // Restore the typed text that comes before the candidate.
+        let candidate = &state.candidates[selected];
+        let mut text: String = state
+            .original
+            .chars()
+            .take(candidate.start.saturating_sub(state.start))
+            .collect();
+        text.push_str(&candidate.text);
+
+        self.selection.focus.ln = state.ln;
+        self.selection.anchor = None;
+        self.replace_ln_range(state.start, state.end, &text);
+
+        state.end = self.selection.focus.col;
+        state.selected = Some(selected);
+        self.completion = Some(state);
+        true
+    }
+
+    /// Get the completion in progress.
+    pub fn completion(&self) -> Option<&CompletionState> {
+        self.completion.as_ref()
+    }
+
+    /// Stop cycling through completions, keeping the inserted text.
+    pub fn dismiss_completion(&mut self) {
+        self.completion = None;
+    }
+
+    /// Replace a column range of the current line with text, moving the
+    /// cursor to the end of the inserted text.
+    fn replace_ln_range(&mut self, start: usize, end: usize, text: &str) {
+        self.undo.begin(self.selection);
+        self.selection.focus.col = end;
+        self.delete_ln_range(start, end);
+        self.selection.focus.col = start;
+        self.insert_str(text);
+        self.end_edit();
+    }
+}
@@ -0,0 +1,59 @@
+use super::{actions::Action, Editor};
+
+/// Decides whether submitting has to be confirmed first, giving the
+/// message that asks for it, such as how many lines are about to be run.
+pub trait Confirm {
+    /// Get the message that asks to confirm submitting the contents,
+    /// or `None` to submit them right away.
+    fn confirm(&self, editor: &Editor) -> Option<String>;
+}
+
+impl<F: Fn(&Editor) -> Option<String>> Confirm for F {
+    fn confirm(&self, editor: &Editor) -> Option<String> {
+        self(editor)
+    }
+}
+
+impl Editor {
+    /// Get the message that asks to confirm the submit in progress.
+    pub fn confirmation(&self) -> Option<&str> {
+        self.confirmation.as_deref()
+    }
+
+    /// Ask to confirm submitting the contents if the editor has to,
+    /// returning `true` if the submit waits for the answer.
+    pub(crate) fn ask_confirmation(&mut self) -> bool {
+        if self.confirmation.take().is_some() {
+            return false;
+        }
+        self.confirmation = self
+            .confirmer
+            .as_ref()
+            .and_then(|confirmer| confirmer.confirm(self));
+        self.confirmation.is_some()
+    }
+
+    /// Answer the confirmation in progress, returning `None` if there is
+    /// none. `Enter` or `y` submits, while `Submit`, `Interrupt` or `n`
+    /// go back to editing, and other actions are ignored.
+    pub(crate) fn apply_confirmation(&mut self, action: Action) -> Option<bool> {
+        self.confirmation.as_ref()?;
+        match action {
+            Action::InsertNewline
+            | Action::NewlineOrSubmit
+            | Action::InsertChar('y')
+            | Action::InsertChar('Y') => {
+                self.confirmation = None;
+                Some(false)
+            }
+            Action::Submit
+            | Action::Interrupt
+            | Action::InsertChar('n')
+            | Action::InsertChar('N') => {
+                self.confirmation = None;
+                Some(true)
+            }
+            _ => Some(true),
+        }
+    }
+}
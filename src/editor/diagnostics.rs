@@ -0,0 +1,134 @@
+use std::ops::Range;
+
+use ropey::Rope;
+
+use super::{
+    read_only::{moved_line, span},
+    selection::Cursor,
+    undo::Edit,
+    Editor,
+};
+
+/// How serious a diagnostic is, which decides its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Underlined in red.
+    Error,
+    /// Underlined in yellow.
+    Warning,
+    /// Underlined in blue.
+    Info,
+}
+
+/// A problem found in a range of the buffer, such as by a parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Characters that the diagnostic is about, up to the end cursor.
+    pub range: Range<Cursor>,
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// What the problem is, which the renderer can show after the line.
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic of the characters in a range.
+    pub fn new(range: Range<Cursor>, severity: Severity, message: &str) -> Self {
+        Self {
+            range,
+            severity,
+            message: message.to_string(),
+        }
+    }
+
+    /// Columns that the diagnostic covers on a line of `len` characters.
+    /// An empty range covers the character that it is at.
+    pub(crate) fn columns(&self, ln: usize, len: usize) -> Option<(usize, usize)> {
+        let Range { start, end } = self.range;
+        if ln < start.ln || end.ln < ln {
+            return None;
+        }
+        let from = if ln == start.ln {
+            start.col.min(len)
+        } else {
+            0
+        };
+        let to = if ln == end.ln { end.col.min(len) } else { len };
+        match (from, to) {
+            (from, to) if from < to => Some((from, to)),
+            (from, _) if start == end && from < len => Some((from, from + 1)),
+            _ => None,
+        }
+    }
+}
+
+/// Diagnostics of the buffer, which go away when their lines are edited.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Diagnostics {
+    list: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Remove the diagnostics of the lines that an edit about to be
+    /// applied changes, and move the rest along with their lines.
+    /// Returns the lines that the removed diagnostics were on.
+    pub fn shift(&mut self, buf: &Rope, edit: &Edit) -> Option<Range<usize>> {
+        if self.list.is_empty() {
+            return None;
+        }
+        let (start, end, whole_lines) = span(buf, edit);
+        let moved = moved_line(buf, edit);
+        let mut cleared: Option<Range<usize>> = None;
+        self.list.retain_mut(|diagnostic| {
+            let Range {
+                start: from,
+                end: to,
+            } = &mut diagnostic.range;
+            let edited = !whole_lines && from.ln <= end && start <= to.ln;
+            match (edited, moved(from.ln), moved(to.ln)) {
+                (false, Some(from_ln), Some(to_ln)) => {
+                    from.ln = from_ln;
+                    to.ln = to_ln;
+                    true
+                }
+                _ => {
+                    let lines = from.ln..to.ln + 1;
+                    cleared = Some(match cleared.take() {
+                        Some(cleared) => cleared.start.min(lines.start)..cleared.end.max(lines.end),
+                        None => lines,
+                    });
+                    false
+                }
+            }
+        });
+        cleared
+    }
+}
+
+impl Editor {
+    /// Set the diagnostics of the buffer, replacing the ones before.
+    ///
+    /// A diagnostic is removed as soon as one of its lines is edited,
+    /// and moves along with its lines when lines are added before it.
+    pub fn set_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
+        self.diagnostics.list = diagnostics;
+        self.mark_dirty(0..usize::MAX);
+    }
+
+    /// Get the diagnostics of the buffer.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics.list
+    }
+
+    /// Get the diagnostics that cover a line.
+    pub fn diagnostics_on(&self, ln: usize) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.list.iter().filter(move |diagnostic| {
+            diagnostic.range.start.ln <= ln && ln <= diagnostic.range.end.ln
+        })
+    }
+
+    /// Remove every diagnostic.
+    pub fn clear_diagnostics(&mut self) {
+        self.set_diagnostics(Vec::new());
+    }
+}
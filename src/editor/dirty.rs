@@ -0,0 +1,142 @@
+use std::{cmp::Ordering, ops::Range};
+
+use ropey::Rope;
+
+use super::{undo::Edit, Editor};
+
+/// Lines that changed since the editor was last drawn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Dirty {
+    lines: Option<Range<usize>>,
+    /// Column of the first line from which it changed.
+    col: usize,
+    /// Number of changes made to the buffer, which only ever grows.
+    revision: usize,
+    /// First line of the lines that moved up or down along with edits
+    /// that added or removed lines, and by how many lines they moved.
+    moved: Option<(usize, isize)>,
+}
+
+impl Default for Dirty {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl Dirty {
+    /// Every line, for when the whole buffer is replaced.
+    pub fn all() -> Self {
+        Self {
+            lines: Some(0..usize::MAX),
+            col: 0,
+            revision: 0,
+            moved: None,
+        }
+    }
+
+    /// Mark every line, after replacing the whole buffer.
+    pub fn mark_replaced(&mut self) {
+        self.mark(0..usize::MAX, 0);
+        self.revision += 1;
+    }
+
+    /// Add the lines that an edit about to be applied changes.
+    ///
+    /// Edits that add or remove lines move every line after them, so all
+    /// of those lines are changed as well until edits after them move the
+    /// lines back, such as when a line is moved up or down.
+    pub fn mark_edit(&mut self, buf: &Rope, edit: &Edit) {
+        let (idx, end, text) = match edit {
+            Edit::Insert { idx, text } => (*idx, *idx, text),
+            Edit::Remove { idx, text } => (*idx, idx + text.chars().count(), text),
+        };
+        let ln = buf.char_to_line(idx);
+        let col = idx - buf.line_to_char(ln);
+        let end_ln = buf.char_to_line(end);
+        let (moved_by, changed) = match edit {
+            Edit::Insert { .. } => {
+                let added = text.matches('\n').count();
+                // Whole lines put in before a line leave it as it was.
+                let whole = col == 0 && text.ends_with('\n');
+                (added as isize, ln..ln + added + usize::from(!whole))
+            }
+            Edit::Remove { .. } => {
+                let whole = col == 0 && buf.line_to_char(end_ln) == end;
+                (ln as isize - end_ln as isize, ln..ln + usize::from(!whole))
+            }
+        };
+        match self.moved.take() {
+            None if moved_by == 0 => self.mark(changed, col),
+            moved => {
+                // Every line from the first one that moved has changed, up
+                // to this edit or to where that line is now.
+                let (from, by) = moved.unwrap_or((ln, 0));
+                let from_now = match from > end_ln {
+                    true => (from as isize + moved_by) as usize,
+                    false => from,
+                };
+                self.mark(from.min(ln)..changed.end.max(from_now), 0);
+                let by = by + moved_by;
+                self.moved = Some((from.min(ln), by)).filter(|_| by != 0);
+            }
+        }
+        self.revision += 1;
+    }
+
+    /// Add lines that changed from a column of the first one.
+    pub fn mark(&mut self, lines: Range<usize>, col: usize) {
+        let (lines, col) = match self.lines.take() {
+            Some(dirty) => {
+                let col = match dirty.start.cmp(&lines.start) {
+                    Ordering::Less => self.col,
+                    Ordering::Equal => self.col.min(col),
+                    Ordering::Greater => col,
+                };
+                (dirty.start.min(lines.start)..dirty.end.max(lines.end), col)
+            }
+            None => (lines, col),
+        };
+        self.lines = Some(lines);
+        self.col = col;
+    }
+}
+
+impl Editor {
+    /// Get the range of lines that changed since the editor was last drawn
+    /// by [`Editor::read`], which can extend past the last line.
+    ///
+    /// Renderers can use it to only redraw the lines that changed.
+    pub fn dirty_lines(&self) -> Option<Range<usize>> {
+        match self.dirty.moved {
+            Some((from, _)) => self
+                .dirty
+                .lines
+                .clone()
+                .map(|lines| lines.start.min(from)..usize::MAX),
+            None => self.dirty.lines.clone(),
+        }
+    }
+
+    /// Get the column of the first dirty line from which it changed.
+    pub fn dirty_col(&self) -> usize {
+        self.dirty.col
+    }
+
+    /// Forget which lines changed, after drawing all of them.
+    pub fn clear_dirty(&mut self) {
+        self.dirty.lines = None;
+        self.dirty.col = 0;
+        self.dirty.moved = None;
+    }
+
+    /// Get the number of changes made to the buffer, which is different
+    /// after each change even if the lines it changed were drawn.
+    pub(crate) fn revision(&self) -> usize {
+        self.dirty.revision
+    }
+
+    /// Mark lines as changed.
+    pub(crate) fn mark_dirty(&mut self, lines: Range<usize>) {
+        self.dirty.mark(lines, 0);
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::Editor;
+use crate::{renderer::Renderer, Result};
+
+/// Command that edits a file, from `$VISUAL` or `$EDITOR` like
+/// readline, which can have arguments before the path of the file.
+fn editor_command() -> Vec<String> {
+    let command = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|command| !command.trim().is_empty());
+    let default = if cfg!(windows) { "notepad" } else { "vi" };
+    command
+        .as_deref()
+        .unwrap_or(default)
+        .split_whitespace()
+        .map(String::from)
+        .collect()
+}
+
+/// Options to write a file that only the user can read, if it is created.
+pub(super) fn private_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    options.write(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+}
+
+/// Create a file that only the user can read, failing instead of opening
+/// or following whatever is already at the path.
+fn create_private(path: &Path) -> io::Result<File> {
+    private_options().create_new(true).open(path)
+}
+
+impl Editor {
+    /// Edit the contents in the external editor of `$VISUAL` or `$EDITOR`,
+    /// once the events that arrived along with
+    /// [`Action::EditExternally`](super::actions::Action::EditExternally)
+    /// are handled. The contents are replaced as a single edit once the
+    /// editor exits, unless it could not be run or failed.
+    ///
+    /// Without a renderer to hand the terminal over, such as when handling
+    /// events for an application, this does nothing. Neither does it with
+    /// a [mask](Editor::mask), which keeps the contents out of files.
+    pub fn edit_externally(&mut self) {
+        if self.mask.is_none() {
+            self.external_edit = true;
+        }
+    }
+
+    /// Run the external editor on a temporary file of the contents,
+    /// while the renderer hands over the terminal.
+    pub(crate) fn run_external_editor(&mut self, renderer: &mut impl Renderer) -> Result<()> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.subsec_nanos());
+        let path = env::temp_dir().join(format!("minime-{}-{}.txt", std::process::id(), nanos));
+        let mut file = create_private(&path)?;
+        if let Err(e) = file.write_all(self.contents().as_bytes()) {
+            let _ = fs::remove_file(&path);
+            return Err(e.into());
+        }
+        drop(file);
+
+        let command = editor_command();
+        let mut succeeded = false;
+        renderer.hand_over(&mut || {
+            succeeded = Command::new(&command[0])
+                .args(&command[1..])
+                .arg(&path)
+                .status()
+                .is_ok_and(|status| status.success());
+        })?;
+
+        let edited = fs::read_to_string(&path);
+        let _ = fs::remove_file(&path);
+        if let (true, Ok(text)) = (succeeded, edited) {
+            // Editors end the file with a new-line that was not in the buffer.
+            let text = text.strip_suffix('\n').unwrap_or(&text);
+            let text = text.strip_suffix('\r').unwrap_or(text);
+            if text != self.contents() {
+                self.replace_contents(text);
+            }
+        }
+        self.dirty.mark_replaced();
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use std::mem;
+
+use super::{actions::Action, Editor};
+
+/// What an input filter does with a typed character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filtered {
+    /// Pass on a character, which can differ from the one that was typed,
+    /// to the next filter and then into the buffer.
+    Pass(char),
+    /// Swallow the character, such as a dead key that the filter holds on
+    /// to until it is composed with the next one.
+    Consume,
+    /// Delete this many characters before the cursor on its line, and
+    /// insert the text in place of them and the character.
+    Replace {
+        /// Characters before the cursor to delete.
+        before: usize,
+        /// Text to insert in place of them and the typed character.
+        text: String,
+    },
+}
+
+/// Transforms typed characters before they are inserted, such as to
+/// compose them or replace what was typed before them.
+///
+/// Filters are run in the order that they were added to the editor, each
+/// on the character that the one before it passed on. They only see typed
+/// characters, and are reset by anything else, such as pastes, clicks and
+/// contents recalled from the history. Their replacements are made at the
+/// main cursor.
+pub trait InputFilter {
+    /// Filter a typed character, given the editor to look at the text
+    /// around the cursor.
+    fn filter(&mut self, c: char, editor: &Editor) -> Filtered;
+
+    /// Forget the characters that were held on to, which is called when
+    /// anything other than typing happens. Defaults to doing nothing.
+    fn reset(&mut self) {}
+}
+
+impl<F: FnMut(char, &Editor) -> Filtered> InputFilter for F {
+    fn filter(&mut self, c: char, editor: &Editor) -> Filtered {
+        self(c, editor)
+    }
+}
+
+/// Replaces text as soon as its last character is typed, such as `->`
+/// with `→`.
+#[derive(Debug, Clone, Default)]
+pub struct Replacements {
+    replacements: Vec<(String, String)>,
+}
+
+impl Replacements {
+    /// Create a filter without any replacements.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a replacement, which takes precedence over the shorter ones
+    /// that it ends with.
+    pub fn add(mut self, from: &str, to: &str) -> Self {
+        self.replacements.retain(|(f, _)| f != from);
+        if !from.is_empty() {
+            self.replacements.push((from.to_string(), to.to_string()));
+        }
+        self.replacements
+            .sort_by_key(|(from, _)| std::cmp::Reverse(from.chars().count()));
+        self
+    }
+}
+
+impl InputFilter for Replacements {
+    fn filter(&mut self, c: char, editor: &Editor) -> Filtered {
+        if editor.selection.anchor.is_some() {
+            return Filtered::Pass(c);
+        }
+        let col = editor.selection.focus.col;
+        let mut typed: String = editor.curr_ln_chars().take(col).collect();
+        typed.push(c);
+        match self
+            .replacements
+            .iter()
+            .find(|(from, _)| typed.ends_with(from.as_str()))
+        {
+            Some((from, to)) => Filtered::Replace {
+                before: from.chars().count() - 1,
+                text: to.clone(),
+            },
+            None => Filtered::Pass(c),
+        }
+    }
+}
+
+/// Composes a dead key with the character typed after it, such as `'`
+/// and `e` into `é`.
+///
+/// The dead key is held on to until the next character, and is inserted
+/// along with it when they do not compose.
+#[derive(Debug, Clone, Default)]
+pub struct Compose {
+    compositions: Vec<(char, char, char)>,
+    pending: Option<char>,
+}
+
+impl Compose {
+    /// Create a filter without any dead keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compose a dead key and a character into another character,
+    /// making the first one a dead key.
+    pub fn add(mut self, dead: char, c: char, composed: char) -> Self {
+        self.compositions
+            .retain(|&(d, other, _)| (d, other) != (dead, c));
+        self.compositions.push((dead, c, composed));
+        self
+    }
+
+    fn is_dead(&self, c: char) -> bool {
+        self.compositions.iter().any(|&(dead, _, _)| dead == c)
+    }
+}
+
+impl InputFilter for Compose {
+    fn filter(&mut self, c: char, _: &Editor) -> Filtered {
+        match self.pending.take() {
+            Some(dead) => match self
+                .compositions
+                .iter()
+                .find(|&&(d, other, _)| (d, other) == (dead, c))
+            {
+                Some(&(_, _, composed)) => Filtered::Pass(composed),
+                None => Filtered::Replace {
+                    before: 0,
+                    text: format!("{}{}", dead, c),
+                },
+            },
+            None if self.is_dead(c) => {
+                self.pending = Some(c);
+                Filtered::Consume
+            }
+            None => Filtered::Pass(c),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.pending = None;
+    }
+}
+
+impl Editor {
+    /// Transform typed characters with a filter before they are inserted,
+    /// after the filters that were added before it.
+    pub fn input_filter(mut self, filter: impl InputFilter + 'static) -> Self {
+        self.input_filters.push(Box::new(filter));
+        self
+    }
+
+    /// Run a typed character through the input filters, returning the
+    /// action that is left to apply, if any. Anything else resets them.
+    pub(crate) fn filter_input(&mut self, action: Action) -> Option<Action> {
+        if self.input_filters.is_empty() {
+            return Some(action);
+        }
+        let c = match action {
+            Action::InsertChar(c) => c,
+            _ => {
+                self.reset_input_filters();
+                return Some(action);
+            }
+        };
+        let mut filters = mem::take(&mut self.input_filters);
+        let mut filtered = Filtered::Pass(c);
+        for filter in filters.iter_mut() {
+            filtered = match filtered {
+                Filtered::Pass(c) => filter.filter(c, self),
+                _ => break,
+            };
+        }
+        self.input_filters = filters;
+        match filtered {
+            Filtered::Pass(c) => Some(Action::InsertChar(c)),
+            Filtered::Consume => None,
+            Filtered::Replace { before, text } => {
+                self.replace_before_cursor(before, &text);
+                None
+            }
+        }
+    }
+
+    /// Replace the characters before the cursor on its line with text,
+    /// as a single edit.
+    fn replace_before_cursor(&mut self, before: usize, text: &str) {
+        self.clamp();
+        self.undo.begin(self.selection);
+        if let Some(anchor) = self.selection.anchor {
+            self.delete_selection(self.selection.focus, anchor);
+        }
+        let col = self.selection.focus.col;
+        self.delete_ln_range(col - before.min(col), col);
+        self.insert_str(text);
+        self.end_edit();
+    }
+
+    /// Forget what the input filters held on to.
+    pub(crate) fn reset_input_filters(&mut self) {
+        self.input_filters
+            .iter_mut()
+            .for_each(|filter| filter.reset());
+    }
+}
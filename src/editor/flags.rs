@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+use ropey::Rope;
+
+use super::{read_only::moved_line, undo::Edit, Editor};
+
+/// Metadata attached to a line, such as for a margin to show.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LineFlag {
+    /// The line has an error, which the gutter shows in red.
+    Error,
+    /// The line has a warning, which the gutter shows in yellow.
+    Warning,
+    /// The line has something to point out.
+    Info,
+    /// A breakpoint is set on the line.
+    Breakpoint,
+    /// Any other flag, known by its name.
+    Custom(String),
+}
+
+/// Flags of the lines of the buffer, which move along
+/// with the lines that are inserted and removed around them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LineFlags {
+    lines: BTreeMap<usize, Vec<LineFlag>>,
+}
+
+impl LineFlags {
+    /// Move the flags for an edit that is about to be applied.
+    pub fn shift(&mut self, buf: &Rope, edit: &Edit) {
+        if self.lines.is_empty() {
+            return;
+        }
+        let moved = moved_line(buf, edit);
+        self.lines = std::mem::take(&mut self.lines)
+            .into_iter()
+            .filter_map(|(ln, flags)| Some((moved(ln)?, flags)))
+            .collect();
+    }
+}
+
+impl Editor {
+    /// Attach a flag to a line, unless it already has it.
+    ///
+    /// The flag moves along with the line as lines are inserted
+    /// and removed before it, and goes away with the line.
+    pub fn set_line_flag(&mut self, line_idx: usize, flag: LineFlag) {
+        let flags = self.line_flags.lines.entry(line_idx).or_default();
+        if !flags.contains(&flag) {
+            flags.push(flag);
+            self.mark_dirty(line_idx..line_idx + 1);
+        }
+    }
+
+    /// Remove a flag from a line.
+    pub fn remove_line_flag(&mut self, line_idx: usize, flag: &LineFlag) {
+        if let Some(flags) = self.line_flags.lines.get_mut(&line_idx) {
+            flags.retain(|other| other != flag);
+            if flags.is_empty() {
+                self.line_flags.lines.remove(&line_idx);
+            }
+            self.mark_dirty(line_idx..line_idx + 1);
+        }
+    }
+
+    /// Attach a flag to a line, or remove it if the line already has it.
+    /// Returns whether the line has it afterwards.
+    pub fn toggle_line_flag(&mut self, line_idx: usize, flag: LineFlag) -> bool {
+        if self.line_flags(line_idx).contains(&flag) {
+            self.remove_line_flag(line_idx, &flag);
+            false
+        } else {
+            self.set_line_flag(line_idx, flag);
+            true
+        }
+    }
+
+    /// Get the flags attached to a line.
+    pub fn line_flags(&self, line_idx: usize) -> &[LineFlag] {
+        self.line_flags
+            .lines
+            .get(&line_idx)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Remove the flags of every line.
+    pub fn clear_line_flags(&mut self) {
+        self.line_flags.lines.clear();
+        self.mark_dirty(0..usize::MAX);
+    }
+}
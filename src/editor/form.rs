@@ -0,0 +1,164 @@
+use std::{cell::Cell, collections::HashMap};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use super::{keybindings::Keybinding, validation::Validation, Editor, ReadResult};
+use crate::{renderer::Renderer, Result};
+
+/// A sequence of named fields, each read by its own editor, which
+/// are submitted together.
+///
+/// Tab and Shift-Tab move to the next and the previous field, and
+/// submitting a field moves to the next one, until the last field is
+/// submitted. The fields that were left without submitting them are
+/// validated then, and the first one that is not valid is read again.
+#[derive(Default)]
+pub struct Form {
+    fields: Vec<(String, Editor)>,
+}
+
+/// How reading a form ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormResult {
+    /// The form was submitted with the contents of every field by name.
+    Submitted(HashMap<String, String>),
+    /// The read of a field was cancelled.
+    Cancelled,
+    /// A field was ended on an empty buffer.
+    Eof,
+}
+
+/// Where to go after the read of a field.
+#[derive(Debug, Clone, Copy)]
+enum Move {
+    Next,
+    Previous,
+}
+
+/// Keybinding that ends the read of a field with Tab and Shift-Tab,
+/// and hands every other event to the keybinding of the form.
+struct FieldKeybinding<'k, K> {
+    keybinding: &'k K,
+    moved: &'k Cell<Option<Move>>,
+}
+
+impl<K: Keybinding> Keybinding for FieldKeybinding<'_, K> {
+    fn process(&self, editor: &mut Editor, event: Event) -> Result<bool> {
+        let moved = match event {
+            _ if editor.confirmation().is_some() => None,
+            Event::Key(KeyEvent {
+                code: KeyCode::Tab,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => Some(Move::Next),
+            Event::Key(KeyEvent {
+                code: KeyCode::BackTab,
+                ..
+            }) => Some(Move::Previous),
+            _ => None,
+        };
+        match moved {
+            Some(moved) => {
+                self.moved.set(Some(moved));
+                Ok(editor.end_read(Ok(ReadResult::Submitted(editor.contents()))))
+            }
+            None => self.keybinding.process(editor, event),
+        }
+    }
+}
+
+impl Form {
+    /// Create a form without any fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a field after the others, read by an editor with its own
+    /// settings, such as a validator.
+    pub fn field(mut self, name: impl Into<String>, editor: Editor) -> Self {
+        self.fields.push((name.into(), editor));
+        self
+    }
+
+    /// Get the editor of a field.
+    pub fn editor(&self, name: &str) -> Option<&Editor> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, editor)| editor)
+    }
+
+    /// Get the editor of a field to change it.
+    pub fn editor_mut(&mut self, name: &str) -> Option<&mut Editor> {
+        self.fields
+            .iter_mut()
+            .find(|(field, _)| field == name)
+            .map(|(_, editor)| editor)
+    }
+
+    /// Read the fields, starting at the first one, with a renderer made
+    /// for each field from its name, such as to give it its own label
+    /// or gutter.
+    ///
+    /// The contents are left in the editors however the read ends.
+    pub fn read<R: Renderer>(
+        &mut self,
+        keybinding: impl Keybinding,
+        mut renderer: impl FnMut(&str) -> R,
+    ) -> Result<FormResult> {
+        if self.fields.is_empty() {
+            return Ok(FormResult::Submitted(HashMap::new()));
+        }
+        let last = self.fields.len() - 1;
+        let mut current = 0;
+        loop {
+            let (name, editor) = &mut self.fields[current];
+            let moved = Cell::new(None);
+            let field_keybinding = FieldKeybinding {
+                keybinding: &keybinding,
+                moved: &moved,
+            };
+            let result = editor.read(field_keybinding, renderer(name))?;
+            current = match (moved.get(), result) {
+                (Some(Move::Next), _) => (current + 1) % self.fields.len(),
+                (Some(Move::Previous), _) => current.checked_sub(1).unwrap_or(last),
+                (None, ReadResult::Cancelled) => return Ok(FormResult::Cancelled),
+                (None, ReadResult::Eof) => return Ok(FormResult::Eof),
+                (None, ReadResult::Submitted(_)) if current < last => current + 1,
+                (None, ReadResult::Submitted(_)) => match self.first_invalid(current) {
+                    Some(invalid) => invalid,
+                    None => return Ok(FormResult::Submitted(self.contents())),
+                },
+            };
+        }
+    }
+
+    /// Find the first field other than `submitted` whose contents
+    /// cannot be submitted, showing why in the editor.
+    fn first_invalid(&mut self, submitted: usize) -> Option<usize> {
+        self.fields
+            .iter_mut()
+            .enumerate()
+            .filter(|&(i, _)| i != submitted)
+            .find_map(|(i, (_, editor))| match editor.validate() {
+                Validation::Complete => None,
+                Validation::Incomplete => Some(i),
+                Validation::Invalid(message) => {
+                    editor.validation_error = Some(message);
+                    Some(i)
+                }
+            })
+    }
+
+    /// Get the contents of every field, cleaned up like each
+    /// field cleans up its contents when it is submitted.
+    fn contents(&self) -> HashMap<String, String> {
+        self.fields
+            .iter()
+            .map(|(name, editor)| {
+                let contents = editor.submit_transform.apply(&editor.contents());
+                (name.clone(), contents)
+            })
+            .collect()
+    }
+}
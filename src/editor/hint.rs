@@ -0,0 +1,68 @@
+use super::Editor;
+
+/// Suggests text to follow the cursor, which is shown dimmed
+/// until it is accepted.
+pub trait Hinter {
+    /// Get the text that would follow the cursor.
+    fn hint(&self, editor: &Editor) -> Option<String>;
+}
+
+impl<F: Fn(&Editor) -> Option<String>> Hinter for F {
+    fn hint(&self, editor: &Editor) -> Option<String> {
+        self(editor)
+    }
+}
+
+/// Hints the rest of the newest history entry that starts with the
+/// contents before the cursor, when the cursor is at the end of the buffer.
+///
+/// Only the rest of the first line of the entry is shown, but accepting
+/// the hint inserts all of it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HistoryHinter;
+
+impl Hinter for HistoryHinter {
+    fn hint(&self, editor: &Editor) -> Option<String> {
+        let history = editor.history.as_ref()?;
+        let at_end = editor.selection.focus.ln + 1 == editor.line_count()
+            && editor.selection.focus.col >= editor.curr_ln_len();
+        let prefix = editor.contents();
+        if !at_end || prefix.is_empty() {
+            return None;
+        }
+        let entry = history.newest_with_prefix(&prefix)?;
+        Some(entry[prefix.len()..].to_string())
+    }
+}
+
+impl Editor {
+    /// Get the hint for the cursor, which is only given
+    /// at the end of a line without a selection, and never
+    /// while the input is masked.
+    pub fn hint(&self) -> Option<String> {
+        let hinter = self.hinter.as_ref()?;
+        if self.mask.is_some()
+            || self.selection.anchor.is_some()
+            || self.selection.focus.col < self.curr_ln_len()
+            || self.completion.is_some()
+            || self.history_search.is_some()
+            || self.buffer_search.is_some()
+        {
+            return None;
+        }
+        hinter.hint(self).filter(|hint| !hint.is_empty())
+    }
+
+    /// Insert the hint at the cursor.
+    ///
+    /// Returns `false` if there was no hint.
+    pub fn accept_hint(&mut self) -> bool {
+        match self.hint() {
+            Some(hint) => {
+                self.insert_str(&hint);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    ops::Bound,
+    path::Path,
+};
+
+use ropey::Rope;
+
+use super::{actions::Action, selection::Selection, Editor};
+use crate::Result;
+
+/// Previously submitted inputs, from oldest to newest.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    entries: VecDeque<String>,
+    capacity: Option<usize>,
+    dedup: bool,
+    /// Sorted distinct entries, to look up the entries with a prefix.
+    index: BTreeMap<String, Indexed>,
+    /// Number of entries that were ever pushed.
+    pushed: u64,
+}
+
+/// Where the copies of an entry are in the history.
+#[derive(Debug, Clone, Copy)]
+struct Indexed {
+    /// Number of the push of the newest copy.
+    newest: u64,
+    copies: usize,
+}
+
+impl PartialEq for History {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+            && self.capacity == other.capacity
+            && self.dedup == other.dedup
+    }
+}
+
+impl Eq for History {}
+
+impl History {
+    /// Create a history that keeps at most `capacity` entries,
+    /// forgetting the oldest ones first.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Remove earlier copies of an entry when it is submitted again.
+    pub fn dedup(self, dedup: bool) -> Self {
+        Self { dedup, ..self }
+    }
+
+    /// Add an entry as the newest one. Empty entries are ignored.
+    pub fn push(&mut self, entry: impl Into<String>) {
+        let entry = entry.into();
+        if entry.is_empty() {
+            return;
+        }
+        if self.dedup {
+            self.entries.retain(|e| *e != entry);
+        }
+        let copies = match (self.dedup, self.index.get(&entry)) {
+            (false, Some(indexed)) => indexed.copies + 1,
+            _ => 1,
+        };
+        let newest = self.pushed;
+        self.pushed += 1;
+        self.index.insert(entry.clone(), Indexed { newest, copies });
+        self.entries.push_back(entry);
+        if let Some(capacity) = self.capacity {
+            while self.entries.len() > capacity {
+                if let Some(entry) = self.entries.pop_front() {
+                    self.unindex(&entry);
+                }
+            }
+        }
+    }
+
+    /// Forget the oldest copy of an entry in the index,
+    /// which is never the newest copy unless it is the only one.
+    fn unindex(&mut self, entry: &str) {
+        if let Some(indexed) = self.index.get_mut(entry) {
+            indexed.copies -= 1;
+            if indexed.copies == 0 {
+                self.index.remove(entry);
+            }
+        }
+    }
+
+    /// Get the newest entry that starts with `prefix` and is longer
+    /// than it.
+    ///
+    /// The entries are looked up in a sorted index, so only the entries
+    /// with the prefix are visited, however long the history is.
+    pub fn newest_with_prefix(&self, prefix: &str) -> Option<&str> {
+        self.index
+            .range::<str, _>((Bound::Excluded(prefix), Bound::Unbounded))
+            .take_while(|(entry, _)| entry.starts_with(prefix))
+            .max_by_key(|(_, indexed)| indexed.newest)
+            .map(|(entry, _)| entry.as_str())
+    }
+
+    /// Get an entry by its index, where `0` is the oldest entry.
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Iterate over the entries from oldest to newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &str> + '_ {
+        self.entries.iter().map(String::as_str)
+    }
+
+    /// Get the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+
+    /// Push the entries of a history file, treating a missing file as empty.
+    ///
+    /// Each line of the file is one entry, with newlines and backslashes
+    /// in the entry escaped as `\n` and `\\`.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        for line in BufReader::new(file).lines() {
+            self.push(unescape(&line?));
+        }
+        Ok(())
+    }
+
+    /// Write all entries to a history file, replacing its contents.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        for entry in &self.entries {
+            writeln!(writer, "{}", escape(entry))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Append a single entry to a history file.
+    pub(crate) fn append(path: impl AsRef<Path>, entry: &str) -> Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", escape(entry))?;
+        Ok(())
+    }
+}
+
+fn escape(entry: &str) -> String {
+    entry.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(line: &str) -> String {
+    let mut entry = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                entry.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                entry.push('\\');
+                chars.next();
+            }
+            _ => entry.push(c),
+        }
+    }
+    entry
+}
+
+/// Position of the editor while browsing the history.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HistoryCursor {
+    /// Index of the entry in the buffer, or `None` if editing the draft.
+    pub index: Option<usize>,
+    /// Contents of the buffer before browsing started.
+    pub draft: String,
+}
+
+/// State of a reverse incremental search through the history.
+#[derive(Debug, Clone)]
+pub struct HistorySearch {
+    query: String,
+    matched: Option<usize>,
+    failed: bool,
+    draft: String,
+    selection: Selection,
+}
+
+impl HistorySearch {
+    /// Get the text being searched for.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Whether no history entry contains the query.
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+}
+
+impl Editor {
+    /// Start a reverse search through the history, or jump to the
+    /// next older match if already searching.
+    pub fn search_history(&mut self) {
+        match &mut self.history_search {
+            Some(search) => match search.matched {
+                Some(0) => search.failed = true,
+                Some(i) => self.update_search(Some(i - 1)),
+                None => self.update_search(None),
+            },
+            None if self.history.is_some() => {
+                self.history_search = Some(HistorySearch {
+                    query: String::new(),
+                    matched: None,
+                    failed: false,
+                    draft: self.buf.to_string(),
+                    selection: self.selection,
+                });
+            }
+            None => {}
+        }
+    }
+
+    /// Get the history search in progress.
+    pub fn history_search(&self) -> Option<&HistorySearch> {
+        self.history_search.as_ref()
+    }
+
+    /// Put the matched entry into the buffer and stop searching.
+    pub fn accept_search(&mut self) {
+        if let Some(search) = self.history_search.take() {
+            let entry = self.buf.to_string();
+            let selection = self.selection;
+            // The edit history only knows about the draft.
+            self.buf = Rope::from_str(&search.draft);
+            self.dirty.mark_replaced();
+            self.selection = search.selection;
+            if search.matched.is_some() {
+                self.replace_contents(&entry);
+                self.selection = selection;
+            }
+        }
+    }
+
+    /// Stop searching, restoring the buffer from before the search.
+    pub fn abort_search(&mut self) {
+        if let Some(search) = self.history_search.take() {
+            self.buf = Rope::from_str(&search.draft);
+            self.dirty.mark_replaced();
+            self.selection = search.selection;
+        }
+    }
+
+    /// Apply an action while searching, returning `None`
+    /// if it should be applied to the editor as usual.
+    pub(crate) fn apply_search(&mut self, action: Action) -> Option<bool> {
+        let search = self.history_search.as_mut()?;
+        match action {
+            Action::InsertChar(c) => {
+                search.query.push(c);
+                let from = search.matched;
+                self.update_search(from);
+            }
+            Action::DeleteBackward => {
+                search.query.pop();
+                self.update_search(None);
+            }
+            Action::SearchHistory => self.search_history(),
+            Action::Submit => self.abort_search(),
+            Action::NewlineOrSubmit | Action::InsertNewline => self.accept_search(),
+            _ => {
+                self.accept_search();
+                return None;
+            }
+        }
+        Some(true)
+    }
+
+    /// Find the newest entry at or before `from` that contains the query,
+    /// showing it in the buffer.
+    fn update_search(&mut self, from: Option<usize>) {
+        self.mark_dirty(0..usize::MAX);
+        let (search, history) = match (&mut self.history_search, &self.history) {
+            (Some(search), Some(history)) => (search, history),
+            _ => return,
+        };
+        let from = from.unwrap_or(history.len().saturating_sub(1));
+        let found = history
+            .entries
+            .iter()
+            .enumerate()
+            .take(from + 1)
+            .rev()
+            .find_map(|(i, entry)| entry.find(&search.query).map(|pos| (i, entry, pos)));
+
+        match found {
+            Some((i, entry, pos)) => {
+                search.matched = Some(i);
+                search.failed = false;
+                let idx = entry[..pos].chars().count();
+                self.buf = Rope::from_str(entry);
+                self.dirty.mark_replaced();
+                self.selection = Selection {
+                    focus: self.char_to_cursor(idx),
+                    anchor: None,
+                    marking: false,
+                };
+            }
+            None if search.query.is_empty() => {
+                search.matched = None;
+                search.failed = false;
+                self.buf = Rope::from_str(&search.draft);
+                self.dirty.mark_replaced();
+                self.selection = search.selection;
+            }
+            // Keep showing the last match, like readline does.
+            None => search.failed = true,
+        }
+    }
+}
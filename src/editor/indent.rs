@@ -0,0 +1,97 @@
+use super::Editor;
+
+/// Decides the indentation of a new line.
+pub trait Indenter {
+    /// Get the leading whitespace of the line that follows `prev_line`,
+    /// which is the part of the line before the cursor.
+    fn indent(&self, prev_line: &str, editor: &Editor) -> String;
+}
+
+impl<F: Fn(&str, &Editor) -> String> Indenter for F {
+    fn indent(&self, prev_line: &str, editor: &Editor) -> String {
+        self(prev_line, editor)
+    }
+}
+
+/// Indenter that gives every new line the leading whitespace
+/// of the line before it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeepIndent;
+
+impl Indenter for KeepIndent {
+    fn indent(&self, prev_line: &str, _: &Editor) -> String {
+        prev_line
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect()
+    }
+}
+
+/// What the Tab key does once there is no snippet, hint or
+/// completion for it to take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabBehavior {
+    /// Insert spaces up to the next multiple of the width, which backspace
+    /// deletes all at once in the leading whitespace of a line.
+    Spaces(usize),
+    /// Insert a tab character.
+    Hard,
+    /// Only complete, leaving indentation to the `Indent` action.
+    Completion,
+}
+
+impl Default for TabBehavior {
+    fn default() -> Self {
+        TabBehavior::Spaces(4)
+    }
+}
+
+impl Editor {
+    /// Number of columns that a level of indentation takes up.
+    pub(crate) fn indent_width(&self) -> usize {
+        match self.tab_behavior {
+            TabBehavior::Spaces(width) => width.max(1),
+            TabBehavior::Hard | TabBehavior::Completion => self.tab_width,
+        }
+    }
+
+    /// Delete the spaces back to the previous tab stop when the cursor is
+    /// in the leading spaces of a line, returning whether it did.
+    pub(crate) fn delete_soft_tab(&mut self) -> bool {
+        self.clamp();
+        let col = self.selection.focus.col;
+        let width = match self.tab_behavior {
+            TabBehavior::Spaces(width) => width.max(1),
+            _ => return false,
+        };
+        if self.selection.anchor.is_some() || col == 0 {
+            return false;
+        }
+        if !self.curr_ln_chars().take(col).all(|c| c == ' ') {
+            return false;
+        }
+        let start = col - ((col - 1) % width + 1);
+        self.undo.begin(self.selection);
+        self.delete_ln_range(start, col);
+        self.end_edit();
+        true
+    }
+
+    /// Insert a new line at the cursor, followed by
+    /// the indentation that the indenter gives it.
+    pub fn insert_newline(&mut self) {
+        self.clamp();
+        let indent = match &self.indenter {
+            Some(indenter) => {
+                let col = self.selection.focus.col;
+                let prev_line: String = self.curr_ln_chars().take(col).collect();
+                indenter.indent(&prev_line, self)
+            }
+            None => String::new(),
+        };
+        self.undo.begin(self.selection);
+        self.type_char('\n');
+        self.insert_str(&indent);
+        self.end_edit();
+    }
+}
@@ -1,127 +1,273 @@
+use std::collections::HashMap;
+
+use super::actions::Action;
 use crate::{editor::Editor, Result};
 
-use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 
 /// Generic keybinding trait.
+///
+/// Since 0.4.0, keybindings are given the events that the renderer reads
+/// with [`Keybinding::process`], which takes the place of `read`.
 pub trait Keybinding {
-    /// Read a key from the environment and act upon the editor.
-    fn read(&self, editor: &mut Editor) -> Result<bool>;
+    /// Act upon the editor with an event read from the terminal.
+    ///
+    /// Returns `false` if the event ends the read.
+    fn process(&self, editor: &mut Editor, event: Event) -> Result<bool>;
+
+    /// Read an event from the terminal and act upon the editor with it.
+    #[deprecated(
+        since = "0.4.0",
+        note = "use `process` with an event from the renderer"
+    )]
+    fn read(&self, editor: &mut Editor) -> Result<bool> {
+        self.process(editor, crossterm::event::read()?)
+    }
 }
 
 /// Default keybindings for the editor.
 pub struct NormalKeybinding;
 
 impl Keybinding for NormalKeybinding {
-    fn read(&self, editor: &mut Editor) -> Result<bool> {
-        let key_event = read()?;
-        match key_event {
-            Event::Key(k) => Self::process_key_event(editor, k),
+    fn process(&self, editor: &mut Editor, event: Event) -> Result<bool> {
+        match event {
+            Event::Key(k) if editor.vi_mode().is_some() => Ok(editor.process_vi_key(k)),
+            Event::Key(k) => Ok(Self::action(k).is_none_or(|action| editor.apply(action))),
             _ => Ok(true),
         }
     }
 }
 
 impl NormalKeybinding {
-    fn process_key_event(editor: &mut Editor, event: KeyEvent) -> Result<bool> {
+    /// Map a key event to the action that it is bound to by default.
+    pub fn action(event: KeyEvent) -> Option<Action> {
         let code = event.code;
-        let ln_count = editor.line_count();
-        // let mut cursor = editor.cursor();
         let shifted = event.modifiers.contains(KeyModifiers::SHIFT);
         let alt = event.modifiers.contains(KeyModifiers::ALT);
         let control = event.modifiers.contains(KeyModifiers::CONTROL);
 
-        match code {
-            KeyCode::Down => editor.move_down(shifted),
-            KeyCode::Up => editor.move_up(shifted),
-            KeyCode::Left => editor.move_left(shifted),
-            KeyCode::Right => editor.move_right(shifted),
-
-            KeyCode::PageDown => editor.move_to_bottom(),
-            KeyCode::PageUp => editor.move_to_top(),
-            KeyCode::Home => {
-                let leading_spaces = editor
-                    .curr_ln_chars()
-                    .take_while(|c| c.is_whitespace())
-                    .count();
-                if editor.selection.focus.col == leading_spaces {
-                    editor.move_to_col(0, shifted);
-                } else {
-                    editor.move_to_col(leading_spaces, shifted);
-                }
+        let action = match code {
+            // Terminals send Ctrl-Shift and a letter as Ctrl and the letter,
+            // so these take Alt-Shift instead.
+            KeyCode::Down if alt && shifted => Action::DuplicateLines,
+            KeyCode::Char('K') if alt => Action::DeleteLines,
+            KeyCode::Down if alt => Action::MoveLinesDown,
+            KeyCode::Up if alt => Action::MoveLinesUp,
+            KeyCode::Down => Action::MoveDown(shifted),
+            KeyCode::Up => Action::MoveUp(shifted),
+            KeyCode::Left if control => Action::MoveWordLeft(shifted),
+            KeyCode::Right if control => Action::MoveWordRight(shifted),
+            KeyCode::Left => Action::MoveLeft(shifted),
+            KeyCode::Right => Action::MoveRight(shifted),
+            KeyCode::Char('b') if alt => Action::MoveWordLeft(false),
+            KeyCode::Char('f') if alt => Action::MoveWordRight(false),
+
+            KeyCode::PageDown => Action::PageDown(shifted),
+            KeyCode::PageUp => Action::PageUp(shifted),
+            KeyCode::Home if control => Action::MoveToTop,
+            KeyCode::End if control => Action::MoveToBottom,
+            KeyCode::Home => Action::MoveHome(shifted),
+            // Terminals send Ctrl-] as Ctrl-5.
+            KeyCode::Char(']') | KeyCode::Char('5') if control => {
+                Action::MoveToMatchingBracket(shifted)
             }
-            KeyCode::End => editor.move_to_line_end(shifted),
+            KeyCode::End => Action::MoveEnd(shifted),
 
-            KeyCode::Backspace => editor.backspace(),
-            KeyCode::Char('h') if control => editor.backspace(),
-            KeyCode::Delete => editor.delete(),
+            KeyCode::Backspace => Action::DeleteBackward,
+            KeyCode::Char('h') if control => Action::DeleteBackward,
+            KeyCode::Delete => Action::DeleteForward,
+            KeyCode::Char('w') if control => Action::DeleteWordBackward,
+            KeyCode::Char('d') if control && alt => Action::AddNextMatch,
+            KeyCode::Char('d') if alt => Action::DeleteWordForward,
+            KeyCode::Char('j') if alt => Action::JoinLines,
+            KeyCode::Char('o') if control => Action::SplitLine,
+            // Terminals send Ctrl-/ as Ctrl-_.
+            KeyCode::Char('/') | KeyCode::Char('_') if control => Action::ToggleComment,
 
-            KeyCode::F(12) => {
-                editor.altscreen = !editor.altscreen;
-            }
+            KeyCode::Char('z') if control => Action::Undo,
+            KeyCode::Char('y') if control => Action::Redo,
+            KeyCode::Char('r') if control => Action::SearchHistory,
+            KeyCode::Char('s') | KeyCode::Char('f') if control => Action::SearchBuffer,
 
-            #[cfg(feature = "unstable")]
-            KeyCode::Char('c') if control => {
-                if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                    if let Some(txt) = editor.curr_sel() {
-                        clipboard.set_text(txt.to_string()).unwrap();
-                    } else {
-                        clipboard.set_text(editor.curr_ln().to_string()).unwrap();
-                    }
-                }
-            }
-            #[cfg(feature = "unstable")]
-            KeyCode::Char('x') if control => {
-                if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                    if let Some(txt) = editor.curr_sel() {
-                        clipboard.set_text(txt.to_string()).unwrap();
-                        editor.delete();
-                    } else {
-                        clipboard
-                            .set_text(editor.remove_line(editor.selection.focus.ln))
-                            .unwrap();
-                    }
-                }
-            }
-            #[cfg(feature = "unstable")]
-            KeyCode::Char('v') if control => {
-                if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                    if let Ok(txt) = clipboard.get_text() {
-                        editor.insert_str(&txt);
-                    }
-                }
-            }
+            KeyCode::F(12) => Action::ToggleAltscreen,
 
-            KeyCode::Tab => {
-                editor.clamp();
-                let soft = 4 - editor.selection.focus.col % 4;
-                for _ in 0..soft {
-                    editor.insert_char(0, ' ');
-                }
-                editor.selection.focus.col += soft;
-            }
-            KeyCode::BackTab => {
-                editor.clamp();
+            KeyCode::Char(' ') if control => Action::ToggleMark,
+            KeyCode::Char('u') if control => Action::UniversalArgument,
+            KeyCode::Char(c @ '0'..='9') if alt => Action::DigitArgument(c as u8 - b'0'),
+            #[cfg(feature = "clipboard")]
+            KeyCode::Char('c') | KeyCode::Char('C') if control && shifted => Action::ClipboardCopy,
+            #[cfg(feature = "clipboard")]
+            KeyCode::Char('v') | KeyCode::Char('V') if control && shifted => Action::ClipboardPaste,
+            KeyCode::Char('c') if control => Action::Interrupt,
+            KeyCode::Char('d') if control => Action::EndOfInput,
+            KeyCode::Char('x') if control => Action::Cut,
+            KeyCode::Char('v') if control => Action::Paste,
 
-                let leading_spaces = editor
-                    .curr_ln_chars()
-                    .take(4)
-                    .take_while(|c| c.is_whitespace())
-                    .count();
+            KeyCode::Tab => Action::Complete,
+            KeyCode::BackTab => Action::CompletePrevious,
+            KeyCode::Esc => Action::Submit,
+            KeyCode::Enter if alt => Action::InsertNewline,
+            KeyCode::Enter => Action::NewlineOrSubmit,
+            KeyCode::Char(c) => Action::InsertChar(c),
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+/// Emacs-style keybindings, falling back to [`NormalKeybinding`].
+///
+/// Kills and yanks go through the same kill ring as cutting and
+/// pasting the selection. `Ctrl-X` starts a sequence of two keys, which
+/// are mapped by [`EmacsKeybinding::chord_action`]. Use
+/// [`EmacsKeybinding::action`] as the fallback of a [`Keymap`] to rebind
+/// keys on top of it, which leaves out the sequences.
+pub struct EmacsKeybinding;
 
-                editor.delete_ln_range(0, leading_spaces);
+impl Keybinding for EmacsKeybinding {
+    fn process(&self, editor: &mut Editor, event: Event) -> Result<bool> {
+        match event {
+            Event::Key(k) if editor.vi_mode().is_some() => Ok(editor.process_vi_key(k)),
+            Event::Key(k) if editor.chord.take().is_some() => {
+                Ok(Self::chord_action(k).is_none_or(|action| editor.apply(action)))
             }
-            KeyCode::Esc => return Ok(false),
-            KeyCode::Enter => {
-                if !alt && editor.curr_ln_len() == 0 && editor.selection.focus.ln + 1 == ln_count {
-                    return Ok(false);
-                } else {
-                    editor.type_char('\n');
-                }
+            Event::Key(k) if k == KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL) => {
+                editor.chord = Some(k);
+                Ok(true)
             }
-            KeyCode::Char(c) => editor.type_char(c),
-            _ => { /* ignored */ }
+            Event::Key(k) => Ok(Self::action(k).is_none_or(|action| editor.apply(action))),
+            _ => Ok(true),
+        }
+    }
+}
+
+impl EmacsKeybinding {
+    /// Map a key event to the action that it is bound to.
+    pub fn action(event: KeyEvent) -> Option<Action> {
+        let alt = event.modifiers.contains(KeyModifiers::ALT);
+        let control = event.modifiers.contains(KeyModifiers::CONTROL);
+
+        let action = match event.code {
+            KeyCode::Char('a') if control => Action::MoveLineStart(false),
+            KeyCode::Char('e') if control => Action::MoveEnd(false),
+            KeyCode::Char('b') if control => Action::MoveLeft(false),
+            KeyCode::Char('f') if control => Action::MoveRight(false),
+            KeyCode::Char('p') if control => Action::MoveUp(false),
+            KeyCode::Char('n') if control => Action::MoveDown(false),
+            KeyCode::Char('<') if alt => Action::MoveToTop,
+            KeyCode::Char('>') if alt => Action::MoveToBottom,
+
+            KeyCode::Char('k') if control => Action::KillToLineEnd,
+            KeyCode::Char('w') if control => Action::Cut,
+            KeyCode::Char('w') if alt => Action::Copy,
+            KeyCode::Char('y') if control => Action::Paste,
+            KeyCode::Char('y') if alt => Action::YankPop,
+            KeyCode::Char('d') if alt => Action::KillWordForward,
+            KeyCode::Backspace if alt => Action::KillWordBackward,
+            KeyCode::Char('t') if control => Action::TransposeChars,
+            KeyCode::Char('u') if alt => Action::UpcaseWord,
+            KeyCode::Char('l') if alt => Action::DowncaseWord,
+            KeyCode::Char('c') if alt => Action::CapitalizeWord,
+            KeyCode::Char('_') | KeyCode::Char('/') if control => Action::Undo,
+            KeyCode::Char('z') if control => Action::Suspend,
+            _ => return NormalKeybinding::action(event),
+        };
+        Some(action)
+    }
+
+    /// Map the key that follows `Ctrl-X` to the action that the
+    /// sequence is bound to. Other keys end the sequence.
+    pub fn chord_action(event: KeyEvent) -> Option<Action> {
+        let control = event.modifiers.contains(KeyModifiers::CONTROL);
+
+        let action = match event.code {
+            KeyCode::Char('e') if control => Action::EditExternally,
+            KeyCode::Char('u') if control => Action::Undo,
+            KeyCode::Backspace => Action::KillToLineStart,
+            KeyCode::Char('(') => Action::StartMacro,
+            KeyCode::Char(')') => Action::EndMacro,
+            KeyCode::Char('e') => Action::ReplayMacro,
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+/// What a key is bound to inside of a [`Keymap`].
+pub enum Binding {
+    /// Dispatch an action to the editor.
+    Action(Action),
+    /// Run a closure on the editor. Returning `false` ends the read.
+    Custom(Box<dyn Fn(&mut Editor) -> bool>),
+    /// Ignore the key.
+    Unbound,
+}
+
+/// Keybindings that can be overridden or extended.
+///
+/// Keys that are not bound explicitly fall back to a default mapping,
+/// which is [`NormalKeybinding`] unless swapped out with [`Keymap::fallback`].
+pub struct Keymap {
+    bindings: HashMap<KeyEvent, Binding>,
+    fallback: fn(KeyEvent) -> Option<Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::new(),
+            fallback: NormalKeybinding::action,
+        }
+    }
+}
+
+impl Keymap {
+    /// Bind a key to an action.
+    pub fn bind(mut self, key: impl Into<KeyEvent>, action: Action) -> Self {
+        self.bindings.insert(key.into(), Binding::Action(action));
+        self
+    }
+
+    /// Bind a key to a closure.
+    pub fn bind_fn(
+        mut self,
+        key: impl Into<KeyEvent>,
+        f: impl Fn(&mut Editor) -> bool + 'static,
+    ) -> Self {
+        self.bindings
+            .insert(key.into(), Binding::Custom(Box::new(f)));
+        self
+    }
+
+    /// Make a key do nothing, even if bound by the fallback.
+    pub fn unbind(mut self, key: impl Into<KeyEvent>) -> Self {
+        self.bindings.insert(key.into(), Binding::Unbound);
+        self
+    }
+
+    /// Swap out the mapping used for keys that are not bound explicitly.
+    pub fn fallback(self, fallback: fn(KeyEvent) -> Option<Action>) -> Self {
+        Self { fallback, ..self }
+    }
+
+    /// Act upon the editor with the binding of a key event.
+    pub fn process_key_event(&self, editor: &mut Editor, event: KeyEvent) -> bool {
+        match self.bindings.get(&event) {
+            Some(Binding::Action(action)) => editor.apply(*action),
+            Some(Binding::Custom(f)) => f(editor),
+            Some(Binding::Unbound) => true,
+            None if editor.vi_mode().is_some() => editor.process_vi_key(event),
+            None => (self.fallback)(event).is_none_or(|action| editor.apply(action)),
+        }
+    }
+}
+
+impl Keybinding for Keymap {
+    fn process(&self, editor: &mut Editor, event: Event) -> Result<bool> {
+        match event {
+            Event::Key(k) => Ok(self.process_key_event(editor, k)),
+            _ => Ok(true),
         }
-        Ok(true)
     }
 }
 
@@ -129,9 +275,8 @@ impl NormalKeybinding {
 pub struct DebugKeybinding;
 
 impl Keybinding for DebugKeybinding {
-    fn read(&self, editor: &mut Editor) -> Result<bool> {
-        let key_event = read()?;
-        match key_event {
+    fn process(&self, editor: &mut Editor, event: Event) -> Result<bool> {
+        match event {
             Event::Key(k) => Self::process_key_event(editor, k),
             _ => Ok(true),
         }
@@ -0,0 +1,216 @@
+use std::collections::VecDeque;
+
+use super::{
+    actions::Action,
+    selection::Cursor,
+    words::{next_word, prev_word},
+    Editor,
+};
+
+/// Text that was cut or copied, from oldest to newest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillRing {
+    entries: VecDeque<String>,
+    capacity: usize,
+    /// Whether the last action killed text, which the next kill joins.
+    killing: bool,
+    /// Range of characters that the last yank inserted,
+    /// and how many entries its entry is older than the newest.
+    yanked: Option<(usize, usize, usize)>,
+}
+
+impl Default for KillRing {
+    fn default() -> Self {
+        Self::new(32)
+    }
+}
+
+impl KillRing {
+    /// Create a kill ring that keeps at most `capacity` entries,
+    /// forgetting the oldest ones first.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+            killing: false,
+            yanked: None,
+        }
+    }
+
+    /// Add an entry as the newest one. Empty entries are ignored.
+    pub fn push(&mut self, entry: impl Into<String>) {
+        let entry = entry.into();
+        if entry.is_empty() {
+            return;
+        }
+        self.entries.push_back(entry);
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Add killed text as the newest entry, or join it with the newest
+    /// entry if the last action killed text too. Text killed backwards
+    /// is put in front of that entry with `prepend`.
+    pub fn kill(&mut self, text: &str, prepend: bool) {
+        match self.entries.back_mut() {
+            Some(newest) if self.killing => match prepend {
+                true => newest.insert_str(0, text),
+                false => newest.push_str(text),
+            },
+            _ => self.push(text),
+        }
+        self.killing = true;
+    }
+
+    /// Get the newest entry.
+    pub fn yank(&self) -> Option<&str> {
+        self.entries.back().map(String::as_str)
+    }
+
+    /// Get the entry that is `back` entries older than the newest one.
+    pub fn get(&self, back: usize) -> Option<&str> {
+        let idx = self.entries.len().checked_sub(back + 1)?;
+        self.entries.get(idx).map(String::as_str)
+    }
+
+    /// Get the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Stop joining kills and replacing yanks after an action
+    /// that does not continue them.
+    pub(crate) fn settle(&mut self, action: Action) {
+        if !matches!(
+            action,
+            Action::KillToLineEnd
+                | Action::KillToLineStart
+                | Action::KillWordForward
+                | Action::KillWordBackward
+        ) {
+            self.killing = false;
+        }
+        if !matches!(action, Action::Paste | Action::YankPop) {
+            self.yanked = None;
+        }
+    }
+}
+
+impl Editor {
+    /// Start or stop extending the selection with plain movement.
+    pub fn toggle_mark(&mut self) {
+        if self.selection.marking {
+            self.selection.marking = false;
+            self.selection.anchor = None;
+        } else {
+            self.clamp();
+            self.selection.marking = true;
+            self.selection.anchor = Some(self.selection.focus);
+        }
+    }
+
+    /// Copy the selection, or the current line, to the kill ring.
+    pub fn copy(&mut self) {
+        let text = match self.curr_sel() {
+            Some(text) => text.to_string(),
+            None => format!("{}\n", self.curr_ln()),
+        };
+        self.kill_ring.push(text);
+        self.selection.marking = false;
+        self.selection.anchor = None;
+    }
+
+    /// Cut the selection, or the current line, to the kill ring.
+    pub fn cut(&mut self) {
+        let text = match self.selection.range() {
+            Some((start, end)) => self.delete_range(start, end),
+            None => self.remove_line(self.selection.focus.ln),
+        };
+        self.kill_ring.push(text);
+    }
+
+    /// Cut from the cursor to the end of the line to the kill ring,
+    /// or the new-line if the cursor is already there.
+    pub fn kill_to_line_end(&mut self) {
+        self.clamp();
+        let focus = self.selection.focus;
+        let end = if focus.col < self.curr_ln_len() {
+            Cursor {
+                col: self.curr_ln_len(),
+                ..focus
+            }
+        } else if focus.ln + 1 < self.line_count() {
+            Cursor {
+                ln: focus.ln + 1,
+                col: 0,
+            }
+        } else {
+            return;
+        };
+        let text = self.delete_range(focus, end);
+        self.kill_ring.kill(&text, false);
+    }
+
+    /// Cut from the start of the line to the cursor to the kill ring.
+    pub fn kill_to_line_start(&mut self) {
+        self.clamp();
+        let focus = self.selection.focus;
+        let text = self.delete_range(Cursor { col: 0, ..focus }, focus);
+        self.kill_ring.kill(&text, true);
+    }
+
+    /// Cut up to the end of the next word to the kill ring.
+    pub fn kill_word_forward(&mut self) {
+        self.clamp();
+        let focus = self.selection.focus;
+        let col = next_word(&self.curr_ln(), focus.col, self.word_chars);
+        let text = self.delete_range(focus, Cursor { col, ..focus });
+        self.kill_ring.kill(&text, false);
+    }
+
+    /// Cut back to the start of the previous word to the kill ring.
+    pub fn kill_word_backward(&mut self) {
+        self.clamp();
+        let focus = self.selection.focus;
+        let col = prev_word(&self.curr_ln(), focus.col, self.word_chars);
+        let text = self.delete_range(Cursor { col, ..focus }, focus);
+        self.kill_ring.kill(&text, true);
+    }
+
+    /// Insert the newest entry of the kill ring at the cursor.
+    pub fn paste(&mut self) {
+        if let Some(text) = self.kill_ring.yank().map(str::to_string) {
+            self.selection.marking = false;
+            self.insert_str(&text);
+            let end = self.rope_idx(self.selection.focus, 0);
+            self.kill_ring.yanked = Some((end - text.chars().count(), end, 0));
+        }
+    }
+
+    /// Replace the text inserted by the last yank with the entry before
+    /// its own in the kill ring, wrapping around to the newest one.
+    /// Does nothing unless the last action was a yank.
+    pub fn yank_pop(&mut self) {
+        let (start, end, back) = match self.kill_ring.yanked {
+            Some(yanked) => yanked,
+            None => return,
+        };
+        let back = (back + 1) % self.kill_ring.len();
+        let text = match self.kill_ring.get(back) {
+            Some(text) => text.to_string(),
+            None => return,
+        };
+        self.undo.begin(self.selection);
+        self.delete_range(self.char_to_cursor(start), self.char_to_cursor(end));
+        self.insert_str(&text);
+        if self.end_edit() {
+            self.kill_ring.yanked = Some((start, start + text.chars().count(), back));
+        }
+    }
+}
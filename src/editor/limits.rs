@@ -0,0 +1,85 @@
+use std::borrow::Cow;
+
+use ropey::Rope;
+
+use super::undo::Edit;
+use crate::util::trimmed;
+
+/// Limits on the size of the buffer, which edits cannot go beyond.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Limits {
+    pub max_lines: Option<usize>,
+    pub max_line_length: Option<usize>,
+    /// Message shown when an edit goes beyond a limit.
+    pub message: Option<String>,
+    /// Whether the terminal bell rings when an edit goes beyond a limit.
+    pub bell: bool,
+}
+
+impl Limits {
+    /// Cut text down to the limits, dropping the lines past the last one
+    /// and the characters past the end of each line.
+    pub fn clamp<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let max_lines = self.max_lines.unwrap_or(usize::MAX);
+        let max_line_length = self.max_line_length.unwrap_or(usize::MAX);
+        let within = text.split('\n').count() <= max_lines
+            && text
+                .split('\n')
+                .all(|line| line.chars().count() <= max_line_length);
+        if within {
+            return Cow::Borrowed(text);
+        }
+        let lines: Vec<String> = text
+            .split('\n')
+            .take(max_lines.max(1))
+            .map(|line| line.chars().take(max_line_length).collect())
+            .collect();
+        Cow::Owned(lines.join("\n"))
+    }
+
+    /// Whether an edit keeps the buffer within the limits. Lines that
+    /// are already too long can still be edited without growing.
+    pub fn allows(&self, buf: &Rope, edit: &Edit) -> bool {
+        if self.max_lines.is_none() && self.max_line_length.is_none() {
+            return true;
+        }
+        let line_col = |idx: usize| {
+            let ln = buf.char_to_line(idx);
+            (ln, idx - buf.line_to_char(ln))
+        };
+        let len = |ln: usize| trimmed(buf.line(ln)).len_chars();
+
+        let (lines, old_len, new_len) = match edit {
+            Edit::Insert { idx, text } => {
+                let (ln, col) = line_col(*idx);
+                let parts: Vec<usize> = text.split('\n').map(|part| part.chars().count()).collect();
+                let rest = len(ln).saturating_sub(col);
+                let new_len = match parts.as_slice() {
+                    [part] => col + part + rest,
+                    [first, middle @ .., last] => middle
+                        .iter()
+                        .copied()
+                        .chain([col + first, last + rest])
+                        .max()
+                        .unwrap_or(0),
+                    [] => 0,
+                };
+                (buf.len_lines() + parts.len() - 1, len(ln), new_len)
+            }
+            Edit::Remove { idx, text } => {
+                let (start, start_col) = line_col(*idx);
+                let (end, end_col) = line_col(idx + text.chars().count());
+                let new_len = start_col + len(end) - end_col;
+                (0, len(start).max(len(end)), new_len)
+            }
+        };
+
+        let too_many = self
+            .max_lines
+            .is_some_and(|max| lines > max && lines > buf.len_lines());
+        let too_long = self
+            .max_line_length
+            .is_some_and(|max| new_len > max && new_len > old_len);
+        !too_many && !too_long
+    }
+}
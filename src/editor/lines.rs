@@ -0,0 +1,196 @@
+use std::ops::RangeInclusive;
+
+use super::{selection::Cursor, Editor};
+use crate::util::trimmed;
+
+impl Editor {
+    /// Lines that the selection is on, or the cursor line.
+    fn selected_lines(&self) -> RangeInclusive<usize> {
+        match self.selection.range() {
+            // A selection that ends at the start of a line leaves it out.
+            Some((start, end)) if end.col == 0 && end.ln > start.ln => start.ln..=end.ln - 1,
+            Some((start, end)) => start.ln..=end.ln,
+            None => self.selection.focus.ln..=self.selection.focus.ln,
+        }
+    }
+
+    /// Move the selection and the cursor by a number of lines.
+    fn shift_selection(&mut self, lines: isize) {
+        let shift = |ln: usize| (ln as isize + lines) as usize;
+        self.selection.focus.ln = shift(self.selection.focus.ln);
+        if let Some(anchor) = &mut self.selection.anchor {
+            anchor.ln = shift(anchor.ln);
+        }
+    }
+
+    /// Character index of the end of a line, before its new-line.
+    fn line_end_idx(&self, ln: usize) -> usize {
+        self.buf.line_to_char(ln) + trimmed(self.buf.line(ln)).len_chars()
+    }
+
+    /// Swap the lines of the selection, or the cursor line, with
+    /// the line above them, moving the selection along.
+    pub fn move_lines_up(&mut self) {
+        let lines = self.selected_lines();
+        let (first, last) = (*lines.start(), *lines.end());
+        if first == 0 || (first - 1..=last).any(|ln| self.is_read_only(ln)) {
+            return;
+        }
+        let above = self.line(first - 1).into_owned();
+        let start = self.buf.line_to_char(first - 1);
+        let line_count = self.line_count();
+        self.undo.begin(self.selection);
+        self.remove_text(start, self.buf.line_to_char(first));
+        if last + 1 < line_count {
+            // The lines moved up, so the line after them is now at `last`.
+            let idx = self.buf.line_to_char(last);
+            self.insert_text(idx, &format!("{}\n", above));
+        } else {
+            // The last line has no new-line to put the line after.
+            let idx = self.buf.len_chars();
+            self.insert_text(idx, &format!("\n{}", above));
+        }
+        if self.end_edit() {
+            self.shift_selection(-1);
+        }
+    }
+
+    /// Swap the lines of the selection, or the cursor line, with
+    /// the line below them, moving the selection along.
+    pub fn move_lines_down(&mut self) {
+        let lines = self.selected_lines();
+        let (first, last) = (*lines.start(), *lines.end());
+        if last + 1 >= self.line_count() || (first..=last + 1).any(|ln| self.is_read_only(ln)) {
+            return;
+        }
+        let below = self.line(last + 1).into_owned();
+        self.undo.begin(self.selection);
+        if last + 2 < self.line_count() {
+            let start = self.buf.line_to_char(last + 1);
+            self.remove_text(start, self.buf.line_to_char(last + 2));
+        } else {
+            // The last line takes the new-line before it along.
+            let start = self.line_end_idx(last);
+            self.remove_text(start, self.buf.len_chars());
+        }
+        let idx = self.buf.line_to_char(first);
+        self.insert_text(idx, &format!("{}\n", below));
+        if self.end_edit() {
+            self.shift_selection(1);
+        }
+    }
+
+    /// Insert a copy of the lines of the selection, or of the cursor
+    /// line, below them, moving the selection onto the copy.
+    pub fn duplicate_lines(&mut self) {
+        let lines = self.selected_lines();
+        let (first, last) = (*lines.start(), *lines.end());
+        let start = self.buf.line_to_char(first);
+        let text = self.buf.slice(start..self.line_end_idx(last)).to_string();
+        self.undo.begin(self.selection);
+        self.insert_text(start, &format!("{}\n", text));
+        if self.end_edit() {
+            self.shift_selection((last - first + 1) as isize);
+        }
+    }
+
+    /// Delete the lines of the selection, or the cursor line,
+    /// along with their new-lines.
+    pub fn delete_lines(&mut self) {
+        let lines = self.selected_lines();
+        let (first, last) = (*lines.start(), *lines.end());
+        let (start, end) = if last + 1 < self.line_count() {
+            (
+                self.buf.line_to_char(first),
+                self.buf.line_to_char(last + 1),
+            )
+        } else if first > 0 {
+            // The last line takes the new-line before it along.
+            (self.line_end_idx(first - 1), self.buf.len_chars())
+        } else {
+            (0, self.buf.len_chars())
+        };
+        self.undo.begin(self.selection);
+        self.remove_text(start, end);
+        if self.end_edit() {
+            self.selection.anchor = None;
+            self.selection.focus.ln = first.min(self.line_count() - 1);
+            self.clamp();
+        }
+    }
+
+    /// Join the cursor line with the line after it, collapsing the
+    /// whitespace between them to a single space, and put the cursor
+    /// where they were joined.
+    pub fn join_lines(&mut self) {
+        let ln = self.selection.focus.ln;
+        if let Some(delta) = self.buffer_mut().join_lines(ln) {
+            self.selection.anchor = None;
+            self.selection.focus = delta.start;
+        }
+    }
+
+    /// Break the cursor line in two at the cursor, like
+    /// [`Buffer::split_line`](super::buffer::Buffer::split_line), keeping
+    /// the cursor at the end of the first line.
+    pub fn split_line(&mut self) {
+        self.clamp();
+        let Cursor { ln, col } = self.selection.focus;
+        if let Some(delta) = self.buffer_mut().split_line(ln, col) {
+            self.selection.anchor = None;
+            self.selection.focus = delta.start;
+        }
+    }
+
+    /// Comment out the selected lines, or the cursor line, with the
+    /// comment prefix, or uncomment them if they all have it already.
+    /// Blank lines are left alone.
+    pub fn toggle_comment(&mut self) {
+        let prefix = self.comment_prefix.clone();
+        let bare = prefix.trim_end();
+        if bare.is_empty() {
+            return;
+        }
+        let lines: Vec<(usize, String)> = self
+            .selected_lines()
+            .map(|ln| (ln, self.line(ln).into_owned()))
+            .filter(|(_, line)| !line.trim().is_empty())
+            .collect();
+        let indent_of = |line: &str| line.chars().take_while(|c| c.is_whitespace()).count();
+        let commented = !lines.is_empty()
+            && lines
+                .iter()
+                .all(|(_, line)| line.trim_start().starts_with(bare));
+
+        self.undo.begin(self.selection);
+        if commented {
+            for (ln, line) in &lines {
+                let rest = line.trim_start();
+                let len = match rest.starts_with(prefix.as_str()) {
+                    true => prefix.chars().count(),
+                    false => bare.chars().count(),
+                };
+                let start = Cursor {
+                    ln: *ln,
+                    col: indent_of(line),
+                };
+                let end = Cursor {
+                    col: start.col + len,
+                    ..start
+                };
+                self.buffer_mut().delete_range(start, end);
+            }
+        } else {
+            // Every line is commented at the indentation of the least indented.
+            let col = lines
+                .iter()
+                .map(|(_, line)| indent_of(line))
+                .min()
+                .unwrap_or(0);
+            for (ln, _) in &lines {
+                self.buffer_mut().insert_str(*ln, col, &prefix);
+            }
+        }
+        self.end_edit();
+    }
+}
@@ -0,0 +1,88 @@
+use super::{
+    actions::Action,
+    selection::{Cursor, Selection},
+    Editor,
+};
+
+/// Something that the keyboard macro does again when it is replayed.
+///
+/// Mouse events are recorded as what they did to the selection, since
+/// where they point at depends on how the editor was drawn.
+#[derive(Debug, Clone)]
+pub(crate) enum Recorded {
+    /// An action that was applied.
+    Action(Action),
+    /// Text that was pasted.
+    Paste(String),
+    /// Selection that a click or a drag of the mouse made.
+    Select(Selection),
+    /// Cursor that a click of the mouse added.
+    AddCursor(Cursor),
+}
+
+impl Editor {
+    /// Start recording the actions that are applied to the editor as the
+    /// keyboard macro, starting over if already recording. Pastes and
+    /// what the mouse does are recorded along with them.
+    pub fn start_macro(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    /// Stop recording, keeping the actions as the macro to replay.
+    pub fn end_macro(&mut self) {
+        if let Some(recorded) = self.recording.take() {
+            self.recorded = recorded;
+        }
+    }
+
+    /// Whether actions are being recorded as the keyboard macro.
+    pub fn recording_macro(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Apply the actions of the keyboard macro again.
+    ///
+    /// Returns `false` if one of them ends the read, skipping the rest.
+    pub fn replay_macro(&mut self) -> bool {
+        let recorded = self.recorded.clone();
+        for recorded in recorded {
+            match recorded {
+                Recorded::Action(action) => {
+                    if !self.apply(action) {
+                        return false;
+                    }
+                }
+                Recorded::Paste(text) => self.paste_input(text),
+                Recorded::Select(selection) => {
+                    self.leave_for_mouse();
+                    // The buffer can be shorter than when it was recorded.
+                    self.selection = Selection {
+                        focus: self.clamp_cursor(selection.focus),
+                        anchor: selection.anchor.map(|anchor| self.clamp_cursor(anchor)),
+                        ..selection
+                    };
+                    self.selection.fix_anchor();
+                    self.record(Recorded::Select(self.selection));
+                }
+                Recorded::AddCursor(cursor) => {
+                    self.add_cursor(cursor);
+                    self.record(Recorded::AddCursor(cursor));
+                }
+            }
+        }
+        true
+    }
+
+    /// Record what was done while recording, leaving out the actions
+    /// of the macro itself. Replayed actions are recorded one by one.
+    pub(crate) fn record(&mut self, recorded: Recorded) {
+        if let Some(recording) = &mut self.recording {
+            if !matches!(
+                recorded,
+                Recorded::Action(Action::StartMacro | Action::EndMacro | Action::ReplayMacro)
+            ) {
+                recording.push(recorded);
+            }
+        }
+    }
+}
@@ -1,18 +1,236 @@
+/// A module that contains the actions that keybindings dispatch.
+pub mod actions;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod argument;
+/// A module that contains saving the input to recover it after a crash.
+pub mod autosave;
+/// A module that contains pairing and matching of brackets.
+pub mod brackets;
+/// A module that contains editing the buffer at any position.
+pub mod buffer;
+/// A module that contains cancelling the read from other threads.
+pub mod cancel;
+/// A module that contains tab completion for the editor.
+pub mod completion;
+/// A module that contains confirming the input before it is submitted.
+pub mod confirm;
+/// A module that contains problems found in the buffer.
+pub mod diagnostics;
+mod dirty;
+mod external;
+/// A module that contains transforming typed characters before they are inserted.
+pub mod filter;
+/// A module that contains metadata attached to lines.
+pub mod flags;
+/// A module that contains reading several fields as one form.
+pub mod form;
+/// A module that contains suggestions that follow the cursor.
+pub mod hint;
+/// A module that contains the history of submitted inputs.
+pub mod history;
+/// A module that contains the indentation of new lines.
+pub mod indent;
 /// A module that contains keybindings for the editor.
 pub mod keybindings;
+/// A module that contains operations on whole lines.
+pub mod lines;
+/// A module that contains the text that was cut or copied.
+pub mod kill_ring;
+mod limits;
+/// A module that contains recording and replaying keyboard macros.
+pub mod macros;
+mod mouse;
+/// A module that contains editing at several cursors at once.
+pub mod multi;
+mod paste;
+/// A module that contains reading line by line where the input cannot be
+/// edited on the terminal.
+pub mod plain;
+/// A module that contains places in a line in bytes, characters,
+/// graphemes and terminal columns.
+pub mod position;
+/// A module that contains printing above the editor from other threads.
+pub mod printer;
+mod read_only;
+/// A module that contains incremental search through the buffer.
+pub mod search;
 pub mod selection;
+/// A module that contains abbreviations that expand into templates.
+pub mod snippets;
+/// A module that contains snapshots of the editor to resume editing later.
+pub mod state;
+/// A module that contains cleaning up the contents when they are submitted.
+pub mod transform;
+/// A module that contains the edit history of the editor.
+pub mod undo;
+/// A module that contains validation of the input before it is submitted.
+pub mod validation;
+/// A module that contains the vi editing mode.
+pub mod vi;
+/// A module that contains word-wise movement and deletion.
+pub mod words;
 
-use std::{borrow::Cow, io::Read};
+use std::{
+    borrow::Cow,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+    time::{Duration, Instant},
+};
 
-use self::{keybindings::Keybinding, selection::{Cursor, Selection}};
-use crate::{Result, renderer::{Renderer}, util::trimmed};
+use self::{
+    actions::{EndOfInput, Interrupt, SubmitPolicy},
+    argument::Argument,
+    autosave::Autosave,
+    completion::{Completer, CompletionState},
+    confirm::Confirm,
+    diagnostics::Diagnostics,
+    dirty::Dirty,
+    filter::InputFilter,
+    flags::LineFlags,
+    hint::Hinter,
+    history::{History, HistoryCursor, HistorySearch},
+    indent::{Indenter, TabBehavior},
+    keybindings::Keybinding,
+    kill_ring::KillRing,
+    limits::Limits,
+    macros::Recorded,
+    printer::PrinterQueue,
+    read_only::ReadOnlyLines,
+    search::BufferSearch,
+    selection::{Cursor, Selection},
+    snippets::Snippets,
+    transform::SubmitTransform,
+    undo::{Edit, UndoStack},
+    validation::Validator,
+    vi::{EditMode, ViState},
+};
+use crate::{
+    backend::Input,
+    renderer::Renderer,
+    util::{col_at_width, next_grapheme, prev_grapheme, trimmed},
+    Error, Result,
+};
 
+use crossterm::event::{Event, KeyEvent};
 use ropey::Rope;
 
+/// How a read of the input ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadResult {
+    /// The input was submitted with these contents.
+    Submitted(String),
+    /// The read was cancelled, with `Ctrl-C` by default.
+    Cancelled,
+    /// The input was ended on an empty buffer, with `Ctrl-D` by default.
+    Eof,
+}
+
+/// What handling a batch of events did to the read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditOutcome {
+    /// The editor keeps reading.
+    Continue,
+    /// The read ended like [`Editor::read`] would have.
+    Ended(ReadResult),
+}
+
+/// Event that the editor can be driven with in place of the events of
+/// the terminal, with [`Editor::read_events`].
+pub trait InputEvent {
+    /// Turn into the event that the editor handles.
+    fn into_event(self) -> Event;
+}
+
+impl InputEvent for Event {
+    fn into_event(self) -> Event {
+        self
+    }
+}
+
+impl InputEvent for KeyEvent {
+    fn into_event(self) -> Event {
+        Event::Key(self)
+    }
+}
+
+/// Callback that is given the editor when something happens to it.
+type Hook = dyn Fn(&Editor);
+
+/// Callback that is given the editor and a line of the buffer.
+type LineHook = dyn Fn(&mut Editor, usize);
+
+/// Callback for when no events came in for a while,
+/// which returns `false` to cancel the read.
+type IdleHook = dyn Fn(&mut Editor) -> bool;
+
+/// How often the editor checks for messages of the external printers
+/// and for a cancellation.
+const PRINT_INTERVAL: Duration = Duration::from_millis(50);
+
 pub struct Editor {
     pub selection: Selection,
+    cursors: Vec<Selection>,
     pub(crate) buf: Rope,
     pub altscreen: bool,
+    suspended: bool,
+    external_edit: bool,
+    /// Whether the terminal bell rings before the next draw.
+    bell: bool,
+    /// Key that started a sequence of keys, such as `Ctrl-X`.
+    pub(crate) chord: Option<KeyEvent>,
+    pub history: Option<History>,
+    pub kill_ring: KillRing,
+    history_file: Option<PathBuf>,
+    history_cursor: HistoryCursor,
+    history_search: Option<HistorySearch>,
+    buffer_search: Option<BufferSearch>,
+    pub(crate) undo: UndoStack,
+    read_only: ReadOnlyLines,
+    line_flags: LineFlags,
+    diagnostics: Diagnostics,
+    limits: Limits,
+    dirty: Dirty,
+    edit_log: Option<Vec<Edit>>,
+    completer: Option<Box<dyn Completer>>,
+    completion: Option<CompletionState>,
+    page_size: Option<usize>,
+    scroll: usize,
+    word_chars: fn(char) -> bool,
+    validator: Option<Box<dyn Validator>>,
+    validation_error: Option<String>,
+    confirmer: Option<Box<dyn Confirm>>,
+    submit_transform: SubmitTransform,
+    autosave: Option<Autosave>,
+    confirmation: Option<String>,
+    hinter: Option<Box<dyn Hinter>>,
+    indenter: Option<Box<dyn Indenter>>,
+    input_filters: Vec<Box<dyn InputFilter>>,
+    tab_behavior: TabBehavior,
+    pub(crate) tab_width: usize,
+    comment_prefix: String,
+    pairs: Vec<(char, char)>,
+    auto_pair: bool,
+    snippets: Snippets,
+    printer: Option<PrinterQueue>,
+    cancelled: Option<Arc<AtomicBool>>,
+    ended: Option<Result<ReadResult>>,
+    interrupt: Interrupt,
+    end_of_input: EndOfInput,
+    submit_policy: SubmitPolicy,
+    sentinel: Option<String>,
+    pub(crate) mask: Option<char>,
+    edit_mode: EditMode,
+    vi: ViState,
+    argument: Option<Argument>,
+    recording: Option<Vec<Recorded>>,
+    recorded: Vec<Recorded>,
+    on_change: Option<Box<Hook>>,
+    on_submit: Option<Box<Hook>>,
+    on_cancel: Option<Box<Hook>>,
+    on_idle: Option<(Duration, Box<IdleHook>)>,
+    on_gutter_click: Option<Box<LineHook>>,
 }
 
 impl Default for Editor {
@@ -20,39 +238,693 @@ impl Default for Editor {
         Self {
             buf: Rope::new(),
             selection: Selection::default(),
+            cursors: Vec::new(),
             altscreen: false,
+            suspended: false,
+            external_edit: false,
+            bell: false,
+            chord: None,
+            history: None,
+            kill_ring: KillRing::default(),
+            history_file: None,
+            history_cursor: HistoryCursor::default(),
+            history_search: None,
+            buffer_search: None,
+            undo: UndoStack::default(),
+            read_only: ReadOnlyLines::default(),
+            line_flags: LineFlags::default(),
+            diagnostics: Diagnostics::default(),
+            limits: Limits::default(),
+            dirty: Dirty::all(),
+            edit_log: None,
+            completer: None,
+            completion: None,
+            page_size: None,
+            scroll: 0,
+            word_chars: words::is_word_char,
+            validator: None,
+            validation_error: None,
+            confirmer: None,
+            submit_transform: SubmitTransform::default(),
+            autosave: None,
+            confirmation: None,
+            hinter: None,
+            indenter: None,
+            input_filters: Vec::new(),
+            tab_behavior: TabBehavior::default(),
+            tab_width: 4,
+            comment_prefix: String::from("// "),
+            pairs: brackets::DEFAULT_PAIRS.to_vec(),
+            auto_pair: false,
+            snippets: Snippets::default(),
+            printer: None,
+            cancelled: None,
+            ended: None,
+            interrupt: Interrupt::Cancel,
+            end_of_input: EndOfInput::DeleteForward,
+            submit_policy: SubmitPolicy::EmptyLastLine,
+            sentinel: None,
+            mask: None,
+            edit_mode: EditMode::Standard,
+            vi: ViState::default(),
+            argument: None,
+            recording: None,
+            recorded: Vec::new(),
+            on_change: None,
+            on_submit: None,
+            on_cancel: None,
+            on_idle: None,
+            on_gutter_click: None,
         }
     }
 }
 
 impl Editor {
+    /// Provide completions when pressing tab.
+    pub fn completer(self, completer: impl Completer + 'static) -> Self {
+        Self {
+            completer: Some(Box::new(completer)),
+            ..self
+        }
+    }
+
+    /// Decide which characters make up the words that are jumped over
+    /// and deleted by the word-wise actions.
+    pub fn word_chars(self, word_chars: fn(char) -> bool) -> Self {
+        Self { word_chars, ..self }
+    }
+
+    /// Only submit with `Enter` when the validator accepts the contents.
+    pub fn validator(self, validator: impl Validator + 'static) -> Self {
+        Self {
+            validator: Some(Box::new(validator)),
+            ..self
+        }
+    }
+
+    /// Ask to confirm submitting the contents when the closure gives a
+    /// message, such as for commands that cannot be undone, which is
+    /// answered with `Enter` or `y` and dismissed with `Esc` or `n`.
+    pub fn confirm_submit(self, confirmer: impl Confirm + 'static) -> Self {
+        Self {
+            confirmer: Some(Box::new(confirmer)),
+            ..self
+        }
+    }
+
+    /// Clean up the contents when they are submitted, such as by
+    /// trimming the whitespace at the end of every line.
+    pub fn submit_transform(self, submit_transform: SubmitTransform) -> Self {
+        Self {
+            submit_transform,
+            ..self
+        }
+    }
+
+    /// Show suggestions after the cursor, which are accepted
+    /// with right or tab at the end of the line.
+    pub fn hinter(self, hinter: impl Hinter + 'static) -> Self {
+        Self {
+            hinter: Some(Box::new(hinter)),
+            ..self
+        }
+    }
+
+    /// Indent new lines, such as with [`indent::KeepIndent`]
+    /// to keep the indentation of the line before. An indented last line
+    /// is not empty, so [`SubmitPolicy::BlankLastLine`] lets `Enter`
+    /// submit on it.
+    pub fn indenter(self, indenter: impl Indenter + 'static) -> Self {
+        Self {
+            indenter: Some(Box::new(indenter)),
+            ..self
+        }
+    }
+
+    /// Set what the Tab key does, such as [`TabBehavior::Hard`]
+    /// to insert tab characters. Defaults to four spaces.
+    pub fn tab_behavior(self, tab_behavior: TabBehavior) -> Self {
+        Self {
+            tab_behavior,
+            ..self
+        }
+    }
+
+    /// Set the number of columns between the tab stops
+    /// that tab characters are drawn up to. Defaults to four.
+    pub fn tab_width(self, tab_width: usize) -> Self {
+        Self {
+            tab_width: tab_width.max(1),
+            ..self
+        }
+    }
+
+    /// Set the prefix that comments out lines, such as `# `, which the
+    /// `ToggleComment` action adds and removes. Defaults to `// `.
+    pub fn comment_prefix(self, comment_prefix: &str) -> Self {
+        Self {
+            comment_prefix: comment_prefix.to_string(),
+            ..self
+        }
+    }
+
+    /// Set the brackets and quotes that are matched and closed
+    /// automatically, as pairs of opening and closing characters.
+    pub fn pairs(self, pairs: &[(char, char)]) -> Self {
+        Self {
+            pairs: pairs.to_vec(),
+            ..self
+        }
+    }
+
+    /// Close brackets and quotes automatically as they are typed.
+    pub fn auto_pair(self, auto_pair: bool) -> Self {
+        Self { auto_pair, ..self }
+    }
+
+    /// Set the snippets that are expanded with `Tab` or a space.
+    pub fn snippets(self, snippets: Snippets) -> Self {
+        Self { snippets, ..self }
+    }
+
+    /// Reject edits that make the buffer longer than `max_lines` lines.
+    /// Text that the editor starts with or that replaces the contents, such
+    /// as from the history, is cut down to the limits instead.
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.limits.max_lines = Some(max_lines);
+        self.clamp_to_limits();
+        self
+    }
+
+    /// Reject edits that make a line longer than `max_line_length` characters.
+    /// Text that the editor starts with or that replaces the contents, such
+    /// as from the history, is cut down to the limits instead.
+    pub fn max_line_length(mut self, max_line_length: usize) -> Self {
+        self.limits.max_line_length = Some(max_line_length);
+        self.clamp_to_limits();
+        self
+    }
+
+    /// Ring the terminal bell when an edit is rejected for going beyond
+    /// [`Editor::max_lines`] or [`Editor::max_line_length`].
+    pub fn limit_bell(mut self, bell: bool) -> Self {
+        self.limits.bell = bell;
+        self
+    }
+
+    /// Cut the contents down to the limits.
+    fn clamp_to_limits(&mut self) {
+        if self.limits.max_lines.is_none() && self.limits.max_line_length.is_none() {
+            return;
+        }
+        let text = self.buf.to_string();
+        if let Cow::Owned(clamped) = self.limits.clamp(&text) {
+            self.buf = Rope::from_str(&clamped);
+            self.dirty.mark_replaced();
+            let focus = &mut self.selection.focus;
+            focus.ln = focus.ln.min(self.buf.len_lines() - 1);
+            self.selection.anchor = None;
+            self.clamp();
+        }
+    }
+
+    /// Show a message like an invalid submit when an edit is rejected
+    /// for going beyond [`Editor::max_lines`] or [`Editor::max_line_length`].
+    pub fn limit_message(mut self, message: &str) -> Self {
+        self.limits.message = Some(message.to_string());
+        self
+    }
+
+    /// Decide what an interrupt does without a selection to copy.
+    pub fn interrupt(self, interrupt: Interrupt) -> Self {
+        Self { interrupt, ..self }
+    }
+
+    /// Decide what ending the input does when the buffer is not empty.
+    pub fn end_of_input(self, end_of_input: EndOfInput) -> Self {
+        Self {
+            end_of_input,
+            ..self
+        }
+    }
+
+    /// Decide when `Enter` submits instead of inserting a new line.
+    pub fn submit_policy(self, submit_policy: SubmitPolicy) -> Self {
+        Self {
+            submit_policy,
+            ..self
+        }
+    }
+
+    /// Draw every character as `mask`, such as for passwords and keys,
+    /// while keeping the real text in the buffer. Masked inputs are
+    /// not recorded in the history.
+    pub fn mask(self, mask: Option<char>) -> Self {
+        Self { mask, ..self }
+    }
+
+    /// Choose how the default keybindings interpret keys.
+    pub fn edit_mode(self, edit_mode: EditMode) -> Self {
+        Self { edit_mode, ..self }
+    }
+
+    /// Call a closure after each batch of events that changed the buffer
+    /// while reading, such as to update a preview.
+    pub fn on_change(self, on_change: impl Fn(&Editor) + 'static) -> Self {
+        Self {
+            on_change: Some(Box::new(on_change)),
+            ..self
+        }
+    }
+
+    /// Call a closure when the input is submitted,
+    /// after it was recorded in the history.
+    pub fn on_submit(self, on_submit: impl Fn(&Editor) + 'static) -> Self {
+        Self {
+            on_submit: Some(Box::new(on_submit)),
+            ..self
+        }
+    }
+
+    /// Call a closure when the read is cancelled or interrupted,
+    /// which is not done if a future of [`Editor::read_async`] is dropped.
+    pub fn on_cancel(self, on_cancel: impl Fn(&Editor) + 'static) -> Self {
+        Self {
+            on_cancel: Some(Box::new(on_cancel)),
+            ..self
+        }
+    }
+
+    /// Call a closure whenever no events came in for `timeout` while
+    /// reading, redrawing afterwards, such as to update a clock in the
+    /// footer. The read is cancelled if the closure returns `false`.
+    ///
+    /// This is not done by [`Editor::read_async`].
+    pub fn on_idle(
+        self,
+        timeout: Duration,
+        on_idle: impl Fn(&mut Editor) -> bool + 'static,
+    ) -> Self {
+        Self {
+            on_idle: Some((timeout, Box::new(on_idle))),
+            ..self
+        }
+    }
+
+    /// Call a closure with the line whose margin was clicked, instead of
+    /// moving the cursor, such as to toggle a [`LineFlag`](flags::LineFlag).
+    pub fn on_gutter_click(self, on_gutter_click: impl Fn(&mut Editor, usize) + 'static) -> Self {
+        Self {
+            on_gutter_click: Some(Box::new(on_gutter_click)),
+            ..self
+        }
+    }
+
+    /// Browse through and record submitted inputs.
+    pub fn history(self, history: History) -> Self {
+        Self {
+            history: Some(history),
+            ..self
+        }
+    }
+
+    /// Load the history from a file, and append each submitted input to it.
+    ///
+    /// Entries are loaded into the history that was already set,
+    /// or into a new unbounded one.
+    pub fn history_file(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        self.history
+            .get_or_insert_with(History::default)
+            .load(&path)?;
+        self.history_file = Some(path.as_ref().to_path_buf());
+        Ok(self)
+    }
+
+    /// Start with some text in the editor, with the cursor at its end.
+    pub fn initial_text(mut self, text: &str) -> Self {
+        self.buf = Rope::from_str(&self.limits.clamp(text));
+        self.dirty.mark_replaced();
+        self.selection = Selection {
+            focus: self.char_to_cursor(self.buf.len_chars()),
+            ..Selection::default()
+        };
+        self
+    }
+
+    /// Start with the cursor somewhere in the initial text,
+    /// clamped to the text.
+    pub fn initial_cursor(mut self, cursor: Cursor) -> Self {
+        let ln = cursor.ln.min(self.line_count() - 1);
+        self.selection = Selection::default();
+        self.selection.focus.ln = ln;
+        self.selection.focus.col = cursor.col;
+        self.clamp();
+        self
+    }
+
     /// Set the content of the editor.
     pub fn set_contents(&mut self, reader: impl Read) -> Result<()> {
-        self.buf = Rope::from_reader(reader)?;
+        self.reset_buffer(Rope::from_reader(reader)?);
         Ok(())
     }
 
+    /// Replace the buffer, along with everything that is tied to its lines.
+    fn reset_buffer(&mut self, buf: Rope) {
+        self.reset_input_filters();
+        self.buf = buf;
+        self.undo = UndoStack::default();
+        self.read_only = ReadOnlyLines::default();
+        self.line_flags = LineFlags::default();
+        self.diagnostics = Diagnostics::default();
+        self.cursors.clear();
+        self.dirty.mark_replaced();
+        self.clamp_to_limits();
+    }
+
+    /// Clear the content of the editor, so that it can read another input.
+    pub fn clear(&mut self) {
+        self.buf = Rope::new();
+        self.selection = Selection::default();
+        self.cursors.clear();
+        self.undo = UndoStack::default();
+        self.read_only = ReadOnlyLines::default();
+        self.line_flags = LineFlags::default();
+        self.diagnostics = Diagnostics::default();
+        self.dirty.mark_replaced();
+        self.completion = None;
+        self.validation_error = None;
+        self.ended = None;
+        self.vi = ViState::default();
+        self.argument = None;
+        self.recording = None;
+        self.scroll = 0;
+        self.suspended = false;
+        self.external_edit = false;
+        self.bell = false;
+        self.chord = None;
+        self.history_cursor = HistoryCursor::default();
+        self.history_search = None;
+        self.buffer_search = None;
+        self.reset_input_filters();
+    }
+
     /// Get the content of the editor.
     pub fn contents(&self) -> String {
         trimmed(self.buf.slice(..)).to_string()
     }
 
     /// Activate the editor and renderer, and read the input.
-    pub fn read(&mut self, keybinding: impl Keybinding, mut renderer: impl Renderer) -> Result<()> {
+    ///
+    /// The contents are left in the editor however the read ends.
+    pub fn read(
+        &mut self,
+        keybinding: impl Keybinding,
+        mut renderer: impl Renderer,
+    ) -> Result<ReadResult> {
         loop {
-            renderer.draw(self)?;
-            renderer.flush()?;
+            self.draw(&mut renderer)?;
 
-            if !keybinding.read(self)? {
+            if !self.wait_event(&mut renderer)? {
                 break;
             }
+
+            let mut inputs = vec![renderer.read_input()?];
+            while renderer.poll_event(Duration::from_secs(0))? {
+                inputs.push(renderer.read_input()?);
+            }
+            let mut locate = |data: &Editor, column, row| {
+                let cursor = renderer.locate(data, column, row)?;
+                Ok(cursor.map(|cursor| (cursor, renderer.in_margin(column))))
+            };
+            if !self.process_events(&keybinding, &mut locate, inputs)? {
+                break;
+            }
+            self.hand_over_terminal(&mut renderer)?;
         }
 
-        renderer.finish()?;
+        self.finish_read(renderer)
+    }
 
+    /// Activate the editor and renderer, and read the input from events
+    /// that are given instead of the events of the terminal, such as from
+    /// a channel of an application that owns the event loop, or keys that
+    /// were recorded to replay them.
+    ///
+    /// The editor is drawn before each event, and the read ends with
+    /// [`ReadResult::Eof`] if the events run out before it ends otherwise.
+    pub fn read_events<I>(
+        &mut self,
+        events: I,
+        keybinding: impl Keybinding,
+        mut renderer: impl Renderer,
+    ) -> Result<ReadResult>
+    where
+        I: IntoIterator,
+        I::Item: InputEvent,
+    {
+        let mut events = events.into_iter();
+        loop {
+            if self.check_cancelled() {
+                break;
+            }
+            self.print_external(&mut renderer)?;
+            self.draw(&mut renderer)?;
+
+            let event = match events.next() {
+                Some(event) => event.into_event(),
+                None => {
+                    self.end_read(Ok(ReadResult::Eof));
+                    break;
+                }
+            };
+            let mut locate = |data: &Editor, column, row| {
+                let cursor = renderer.locate(data, column, row)?;
+                Ok(cursor.map(|cursor| (cursor, renderer.in_margin(column))))
+            };
+            if !self.process_events(&keybinding, &mut locate, vec![Input::from(event)])? {
+                break;
+            }
+            self.hand_over_terminal(&mut renderer)?;
+        }
+
+        self.finish_read(renderer)
+    }
+
+    /// Act upon the editor with a batch of events that arrived together,
+    /// for applications that read events and draw the editor themselves.
+    ///
+    /// The events are handled like [`Editor::read`] handles them, except
+    /// that mouse clicks are ignored without a renderer to locate them.
+    /// Once the read ends, the outcome has what `read` would return,
+    /// and the editor should be cleared before handling more events.
+    pub fn handle_events(
+        &mut self,
+        keybinding: &impl Keybinding,
+        events: Vec<Event>,
+    ) -> Result<EditOutcome> {
+        let inputs = events.into_iter().map(Input::from).collect();
+        let proceed = self.process_events(keybinding, &mut |_, _, _| Ok(None), inputs)?;
+        self.suspended = false;
+        self.external_edit = false;
+        if proceed {
+            return Ok(EditOutcome::Continue);
+        }
+        self.conclude_read().map(EditOutcome::Ended)
+    }
+
+    /// Wait for the next event, printing messages, checking for a
+    /// cancellation and calling the idle callback meanwhile. Returns
+    /// `false` if the read was cancelled or the idle callback ended it.
+    fn wait_event(&mut self, renderer: &mut impl Renderer) -> Result<bool> {
+        let idle_timeout = self.on_idle.as_ref().map(|(timeout, _)| *timeout);
+        if self.check_cancelled() {
+            return Ok(false);
+        }
+        let unsaved = self.autosave.as_ref().and_then(Autosave::due_in).is_some();
+        if self.printer.is_none() && self.cancelled.is_none() && idle_timeout.is_none() && !unsaved
+        {
+            return Ok(true);
+        }
+        let mut idle_since = Instant::now();
+        loop {
+            let left = idle_timeout.map(|timeout| timeout.saturating_sub(idle_since.elapsed()));
+            let polled = self.printer.is_some() || self.cancelled.is_some();
+            let wait = match (polled, left) {
+                (true, Some(left)) => PRINT_INTERVAL.min(left),
+                (false, Some(left)) => left,
+                (_, None) => PRINT_INTERVAL,
+            };
+            let save_left = self.autosave.as_ref().and_then(Autosave::due_in);
+            let wait = save_left.map_or(wait, |left| wait.min(left));
+            if renderer.poll_event(wait)? {
+                return Ok(true);
+            }
+            if self.check_cancelled() {
+                return Ok(false);
+            }
+            self.autosave_due()?;
+
+            // Print messages while waiting, redrawing after each batch.
+            let mut redraw = self.print_external(renderer)?;
+            if idle_timeout.is_some_and(|timeout| idle_since.elapsed() >= timeout) {
+                if let Some((timeout, on_idle)) = self.on_idle.take() {
+                    let proceed = on_idle(self);
+                    self.on_idle = Some((timeout, on_idle));
+                    if !proceed {
+                        return Ok(self.end_read(Ok(ReadResult::Cancelled)));
+                    }
+                }
+                idle_since = Instant::now();
+                redraw = true;
+            }
+            if redraw {
+                self.draw(renderer)?;
+            }
+        }
+    }
+
+    /// Activate the editor and renderer, and read the input without
+    /// blocking the thread, so that it can run alongside other tasks.
+    ///
+    /// Events are read from the terminal through `crossterm` rather than
+    /// the renderer. Dropping the future cancels the read and restores
+    /// the terminal, leaving the contents in the editor. Messages of the
+    /// external printers are only printed, and cancellations only take
+    /// effect, once there is an event.
+    #[cfg(feature = "async")]
+    pub async fn read_async(
+        &mut self,
+        keybinding: impl Keybinding,
+        mut renderer: impl Renderer,
+    ) -> Result<ReadResult> {
+        use crossterm::event::EventStream;
+        use futures_core::Stream;
+        use std::{future::poll_fn, pin::Pin, task::Poll};
+
+        let mut stream = EventStream::new();
+        loop {
+            if self.check_cancelled() {
+                break;
+            }
+            self.print_external(&mut renderer)?;
+            self.draw(&mut renderer)?;
+
+            // Take every event that is ready, waiting only for the first.
+            let inputs = poll_fn(|cx| {
+                let mut events = Vec::new();
+                loop {
+                    match Pin::new(&mut stream).poll_next(cx) {
+                        Poll::Ready(Some(event)) => events.push(event),
+                        Poll::Ready(None) => return Poll::Ready(events),
+                        Poll::Pending if events.is_empty() => return Poll::Pending,
+                        Poll::Pending => return Poll::Ready(events),
+                    }
+                }
+            })
+            .await
+            .into_iter()
+            .map(|event| event.map(Input::from))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            let mut locate = |data: &Editor, column, row| {
+                let cursor = renderer.locate(data, column, row)?;
+                Ok(cursor.map(|cursor| (cursor, renderer.in_margin(column))))
+            };
+            if inputs.is_empty() || !self.process_events(&keybinding, &mut locate, inputs)? {
+                break;
+            }
+            self.hand_over_terminal(&mut renderer)?;
+        }
+
+        self.finish_read(renderer)
+    }
+
+    /// Stop the process to go back to the shell, once the events that
+    /// arrived along with [`Action::Suspend`](actions::Action::Suspend)
+    /// are handled. Without a renderer to suspend, such as when handling
+    /// events for an application, this does nothing.
+    pub fn suspend(&mut self) {
+        self.suspended = true;
+    }
+
+    /// Suspend the renderer or run the external editor if the editor was
+    /// asked to, which redraws everything afterwards.
+    fn hand_over_terminal(&mut self, renderer: &mut impl Renderer) -> Result<()> {
+        if std::mem::take(&mut self.suspended) {
+            renderer.suspend()?;
+            self.dirty.mark_replaced();
+        }
+        if std::mem::take(&mut self.external_edit) {
+            self.run_external_editor(renderer)?;
+        }
+        Ok(())
+    }
+
+    /// Draw the editor, which leaves none of its lines dirty.
+    fn draw(&mut self, renderer: &mut impl Renderer) -> Result<()> {
+        if std::mem::take(&mut self.bell) {
+            renderer.bell()?;
+        }
+        renderer.draw(self)?;
+        renderer.flush()?;
+        self.clear_dirty();
+        self.page_size = renderer.page_size();
+        if let Some(scroll) = renderer.first_line() {
+            self.scroll = scroll;
+        }
         Ok(())
     }
 
+    /// Get the first line that the last draw showed, which is how far the
+    /// editor is scrolled, and which the renderer starts from otherwise.
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Finish the renderer and record the submitted contents.
+    fn finish_read(&mut self, mut renderer: impl Renderer) -> Result<ReadResult> {
+        if self.ended.is_none() {
+            renderer.submit(self)?;
+        }
+        renderer.finish()?;
+        self.conclude_read()
+    }
+
+    /// End the read on the editor's side, recording a submitted input
+    /// in the history and calling the hooks.
+    fn conclude_read(&mut self) -> Result<ReadResult> {
+        self.history_cursor = HistoryCursor::default();
+        self.end_autosave()?;
+
+        if let Some(ended) = self.ended.take() {
+            if let (Ok(ReadResult::Cancelled) | Err(Error::Interrupted), Some(on_cancel)) =
+                (&ended, &self.on_cancel)
+            {
+                on_cancel(self);
+            }
+            return ended;
+        }
+
+        let contents = self.contents();
+        if let (Some(history), None) = (&mut self.history, self.mask) {
+            if let (Some(path), false) = (&self.history_file, contents.is_empty()) {
+                History::append(path, &contents)?;
+            }
+            history.push(contents.clone());
+        }
+        if let Some(on_submit) = &self.on_submit {
+            on_submit(self);
+        }
+
+        Ok(ReadResult::Submitted(self.submit_transform.apply(&contents)))
+    }
+
+    /// End the read without submitting the contents.
+    pub(crate) fn end_read(&mut self, result: Result<ReadResult>) -> bool {
+        self.ended = Some(result);
+        false
+    }
+
     /// Get the number of lines.
     pub fn line_count(&self) -> usize {
         self.buf.len_lines()
@@ -63,27 +935,42 @@ impl Editor {
         self.buf.len_chars()
     }
 
-    pub fn line(&self, index: usize) -> Cow<str> {
+    pub fn line(&self, index: usize) -> Cow<'_, str> {
         trimmed(self.buf.line(index)).into()
     }
 
+    /// Get a line as it is drawn, with every character
+    /// replaced by the mask if there is one.
+    pub fn shown_line(&self, index: usize) -> Cow<'_, str> {
+        match self.mask {
+            Some(mask) => mask
+                .to_string()
+                .repeat(trimmed(self.buf.line(index)).len_chars())
+                .into(),
+            None => self.line(index),
+        }
+    }
+
     // pub fn insert_line(&mut self, line_idx: usize, string: &str) {
     //     let line_start = self.buf.line_to_char(line_idx);
     //     self.buf.insert(line_start, &string);
     // }
 
-    #[cfg(feature = "unstable")]
+    /// Remove a line, returning it along with its new-line.
     pub fn remove_line(&mut self, line_idx: usize) -> String {
         let line_start = self.buf.line_to_char(line_idx);
         let line_end = self.buf.line_to_char(line_idx + 1);
         let rm = self.buf.line(line_idx).to_string();
-        self.buf.remove(line_start..line_end);
-
-        if self.selection.focus.ln == line_idx {
-            self.selection.focus.col = 0;
+        self.undo.begin(self.selection);
+        self.remove_text(line_start, line_end);
+        if self.end_edit() {
+            if self.selection.focus.ln == line_idx {
+                self.selection.focus.col = 0;
+            }
+            self.selection.focus.ln = self.selection.focus.ln.min(self.line_count() - 1);
         }
 
-        return rm;
+        rm
     }
 
     // pub fn push_line_str(&mut self, line_idx: usize, string: &str) {
@@ -96,6 +983,14 @@ impl Editor {
         self.selection.focus.col = self.selection.focus.col.min(self.curr_ln_len());
     }
 
+    /// Move a cursor that can be past the end of the buffer, such as one
+    /// from before an edit, onto the closest place in it.
+    pub(crate) fn clamp_cursor(&self, cursor: Cursor) -> Cursor {
+        let ln = cursor.ln.min(self.line_count() - 1);
+        let col = cursor.col.min(trimmed(self.buf.line(ln)).len_chars());
+        Cursor { ln, col }
+    }
+
     /// Get the length of the current line.
     pub fn curr_ln_len(&self) -> usize {
         trimmed(self.buf.line(self.selection.focus.ln)).len_chars()
@@ -107,12 +1002,12 @@ impl Editor {
     }
 
     /// Get the current line.
-    pub fn curr_ln(&self) -> Cow<str> {
+    pub fn curr_ln(&self) -> Cow<'_, str> {
         Cow::from(trimmed(self.buf.line(self.selection.focus.ln)))
     }
 
     /// Get the current selection of text.
-    pub fn curr_sel(&self) -> Option<Cow<str>> {
+    pub fn curr_sel(&self) -> Option<Cow<'_, str>> {
         if let Some(anchor) = self.selection.anchor {
             let anchor_idx = self.rope_idx(anchor, 0);
             let focus_idx = self.rope_idx(self.selection.focus, 0);
@@ -134,50 +1029,73 @@ impl Editor {
 
     fn delete_ln_range(&mut self, start: usize, end: usize) {
         let idx = self.buf.line_to_char(self.selection.focus.ln);
-        self.buf.remove((idx + start)..(idx + end));
+        self.remove_text(idx + start, idx + end);
         if self.selection.focus.col >= end {
             self.selection.focus.col -= end - start;
         }
     }
 
     fn delete_selection(&mut self, focus: Cursor, anchor: Cursor) {
-        let anchor_idx = self.rope_idx(anchor, 0);
-        let focus_idx = self.rope_idx(focus, 0);
-        if focus_idx < anchor_idx {
-            self.buf.remove(focus_idx..anchor_idx)
-        } else {
-            self.selection.focus = self.selection.anchor.unwrap();
-            self.buf.remove(anchor_idx..focus_idx)
+        self.delete_range(focus.min(anchor), focus.max(anchor));
+    }
+
+    /// Delete the text from `start` up to `end`, moving the cursor to `start`
+    /// and dropping the selection. Returns the deleted text.
+    pub fn delete_range(&mut self, start: Cursor, end: Cursor) -> String {
+        let clamp = |cursor: Cursor| Cursor {
+            col: cursor
+                .col
+                .min(trimmed(self.buf.line(cursor.ln)).len_chars()),
+            ..cursor
+        };
+        let (start, end) = (clamp(start), clamp(end));
+        let (start_idx, end_idx) = (self.rope_idx(start, 0), self.rope_idx(end, 0));
+        let text = self.buf.slice(start_idx..end_idx).to_string();
+        self.undo.begin(self.selection);
+        self.remove_text(start_idx, end_idx);
+        if self.end_edit() {
+            self.selection = Selection {
+                focus: start,
+                anchor: None,
+                marking: false,
+            };
         }
-        self.selection.anchor = None;
+        text
     }
 
     /// Execute a backspace.
     pub fn backspace(&mut self) {
         self.clamp();
+        self.undo.begin(self.selection);
 
         if let Some(anchor) = self.selection.anchor {
             self.delete_selection(self.selection.focus, anchor);
         } else if self.selection.focus.col > 0 {
-            self.delete_char(-1);
-            self.selection.focus.col -= 1;
+            let col = self.selection.focus.col;
+            self.delete_ln_range(prev_grapheme(&self.curr_ln(), col), col);
         } else if self.selection.focus.ln > 0 {
             let col = self.buf.line(self.selection.focus.ln - 1).len_chars();
             self.delete_char(-1);
             self.selection.focus.ln -= 1;
             self.selection.focus.col = col - 1;
         }
+        self.end_edit();
     }
 
     /// Execute a delete.
     pub fn delete(&mut self) {
         self.clamp();
+        self.undo.begin(self.selection);
 
         if let Some(anchor) = self.selection.anchor {
             self.delete_selection(self.selection.focus, anchor);
-        } else if self.selection.focus.col < self.curr_ln_len() || self.selection.focus.ln + 1 < self.line_count() {
+        } else if self.selection.focus.col < self.curr_ln_len() {
+            let col = self.selection.focus.col;
+            self.delete_ln_range(col, next_grapheme(&self.curr_ln(), col));
+        } else if self.selection.focus.ln + 1 < self.line_count() {
             self.delete_char(0);
         }
+        self.end_edit();
     }
 
     /// Move the cursor right.
@@ -186,7 +1104,7 @@ impl Editor {
         self.selection.set_anchor(anchored);
         let len = self.curr_ln_len();
         if self.selection.focus.col < len {
-            self.selection.focus.col += 1;
+            self.selection.focus.col = next_grapheme(&self.curr_ln(), self.selection.focus.col);
         } else if self.selection.focus.ln + 1 < self.line_count() {
             // Move to the beginning of the next line.
             self.selection.focus.ln += 1;
@@ -200,7 +1118,7 @@ impl Editor {
         self.clamp();
         self.selection.set_anchor(anchored);
         if self.selection.focus.col > 0 {
-            self.selection.focus.col -= 1;
+            self.selection.focus.col = prev_grapheme(&self.curr_ln(), self.selection.focus.col);
         } else if self.selection.focus.ln > 0 {
             // Move to the end of the previous line.
             self.selection.focus.ln -= 1;
@@ -215,6 +1133,7 @@ impl Editor {
         if self.selection.focus.ln == 0 {
             self.selection.focus.col = 0;
         } else {
+            self.selection.focus.col = self.col_on_line(self.selection.focus.ln - 1);
             self.selection.focus.ln -= 1;
         }
         self.selection.fix_anchor();
@@ -226,11 +1145,26 @@ impl Editor {
         if self.selection.focus.ln + 1 == self.line_count() {
             self.selection.focus.col = self.curr_ln_len();
         } else {
+            self.selection.focus.col = self.col_on_line(self.selection.focus.ln + 1);
             self.selection.focus.ln += 1;
         }
         self.selection.fix_anchor();
     }
 
+    /// Column of another line that is displayed under the cursor,
+    /// keeping how far past the end of its line the cursor is.
+    fn col_on_line(&self, ln: usize) -> usize {
+        let col = self.selection.focus.col;
+        let len = self.curr_ln_len();
+        let width = self.cursor_position().column + col.saturating_sub(len);
+        col_at_width(&self.line(ln), width, self.tab_width)
+    }
+
+    /// Get the number of terminal columns before the cursor on its line.
+    pub fn display_col(&self) -> usize {
+        self.cursor_position().column
+    }
+
     /// Move the cursor to a column.
     pub fn move_to_col(&mut self, col: usize, anchored: bool) {
         self.selection.set_anchor(anchored);
@@ -248,25 +1182,125 @@ impl Editor {
         self.selection.focus.ln = self.line_count() - 1;
     }
 
+    /// Move the cursor up by the number of lines that the renderer shows,
+    /// or to the top if it does not know.
+    pub fn move_page_up(&mut self, anchored: bool) {
+        self.selection.set_anchor(anchored);
+        match self.page_size {
+            Some(page) => self.selection.focus.ln = self.selection.focus.ln.saturating_sub(page),
+            None => self.selection.focus.ln = 0,
+        }
+        self.selection.fix_anchor();
+    }
+
+    /// Move the cursor down by the number of lines that the renderer shows,
+    /// or to the bottom if it does not know.
+    pub fn move_page_down(&mut self, anchored: bool) {
+        self.selection.set_anchor(anchored);
+        let last = self.line_count() - 1;
+        match self.page_size {
+            Some(page) => self.selection.focus.ln = (self.selection.focus.ln + page).min(last),
+            None => self.selection.focus.ln = last,
+        }
+        self.selection.fix_anchor();
+    }
+
     /// Move the cursor to the end of the current line.
     pub fn move_to_line_end(&mut self, anchored: bool) {
         self.move_to_col(self.curr_ln_len(), anchored);
     }
 
+    /// Move the cursor to the first non-whitespace character of the line,
+    /// or to the line start if it is already there.
+    pub fn move_home(&mut self, anchored: bool) {
+        let leading_spaces = self
+            .curr_ln_chars()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        if self.selection.focus.col == leading_spaces {
+            self.move_to_col(0, anchored);
+        } else {
+            self.move_to_col(leading_spaces, anchored);
+        }
+    }
+
+    /// Insert a tab character, or spaces up to the next tab stop,
+    /// depending on the tab behavior.
+    pub fn indent(&mut self) {
+        self.clamp();
+        if self.tab_behavior == TabBehavior::Hard {
+            self.type_char('\t');
+            return;
+        }
+        self.undo.begin(self.selection);
+        let width = self.indent_width();
+        let soft = width - self.selection.focus.col % width;
+        for _ in 0..soft {
+            self.insert_char(0, ' ');
+        }
+        self.selection.focus.col += soft;
+        self.end_edit();
+    }
+
+    /// Remove up to one level of leading indentation.
+    pub fn dedent(&mut self) {
+        self.clamp();
+        self.undo.begin(self.selection);
+
+        // A leading tab is a whole level on its own.
+        let leading_spaces = match self.curr_ln_chars().next() {
+            Some('\t') => 1,
+            _ => self
+                .curr_ln_chars()
+                .take(self.indent_width())
+                .take_while(|&c| c == ' ')
+                .count(),
+        };
+
+        self.delete_ln_range(0, leading_spaces);
+        self.end_edit();
+    }
+
+    /// Swap the grapheme before the cursor with the one under it, moving
+    /// the cursor forward. At the end of a line, the last two are swapped.
+    pub fn transpose_chars(&mut self) {
+        self.clamp();
+        let line = self.curr_ln();
+        let mut mid = self.selection.focus.col;
+        if mid == self.curr_ln_len() {
+            mid = prev_grapheme(&line, mid);
+        }
+        if mid == 0 {
+            return;
+        }
+        let start = prev_grapheme(&line, mid);
+        let end = next_grapheme(&line, mid);
+        let mut text: String = line.chars().skip(mid).take(end - mid).collect();
+        text.extend(line.chars().skip(start).take(mid - start));
+
+        self.selection.anchor = None;
+        self.undo.begin(self.selection);
+        self.selection.focus.col = end;
+        self.delete_ln_range(start, end);
+        self.insert_str(&text);
+        self.end_edit();
+    }
+
     /// Delete a character offset from the cursor.
     pub fn delete_char(&mut self, offset: isize) {
         let z = self.rope_idx(self.selection.focus, offset);
-        self.buf.remove(z..=z);
+        self.remove_text(z, z + 1);
     }
 
     pub fn insert_char(&mut self, offset: isize, c: char) {
         let z = self.rope_idx(self.selection.focus, offset);
-        self.buf.insert_char(z, c);
+        self.insert_text(z, c.encode_utf8(&mut [0; 4]));
     }
 
     /// Type a character at the cursor.
     pub fn type_char(&mut self, c: char) {
         self.clamp();
+        self.undo.begin(self.selection);
         if let Some(anchor) = self.selection.anchor {
             self.delete_selection(self.selection.focus, anchor);
         }
@@ -275,23 +1309,238 @@ impl Editor {
             self.selection.focus.col = 0;
             self.selection.focus.ln += 1;
         } else {
-            self.selection.focus.col = self.selection.focus.col + 1;
+            self.selection.focus.col += 1;
         }
+        self.end_edit();
     }
 
     /// Insert a string at the cursor.
     pub(crate) fn insert_str(&mut self, str: &str) {
         self.clamp();
+        self.undo.begin(self.selection);
         if let Some(anchor) = self.selection.anchor {
             self.delete_selection(self.selection.focus, anchor);
         }
         let z = self.rope_idx(self.selection.focus, 0);
-        self.buf.insert(z, str);
+        self.insert_text(z, str);
+
+        match str.rfind('\n') {
+            Some(last) => {
+                self.selection.focus.ln += str.matches('\n').count();
+                self.selection.focus.col = str[last + 1..].chars().count();
+            }
+            None => self.selection.focus.col += str.chars().count(),
+        }
+        self.end_edit();
+    }
+
+    /// Undo the last group of edits.
+    ///
+    /// Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let (read_only, line_flags) = (&mut self.read_only, &mut self.line_flags);
+        let (diagnostics, dirty) = (&mut self.diagnostics, &mut self.dirty);
+        let on_edit = |buf: &Rope, edit: &Edit| {
+            read_only.shift(buf, edit);
+            line_flags.shift(buf, edit);
+            if let Some(lines) = diagnostics.shift(buf, edit) {
+                dirty.mark(lines, 0);
+            }
+            dirty.mark_edit(buf, edit);
+        };
+        match self.undo.undo(&mut self.buf, on_edit) {
+            Some(selection) => {
+                self.selection = selection;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the last group of undone edits.
+    ///
+    /// Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let (read_only, line_flags) = (&mut self.read_only, &mut self.line_flags);
+        let (diagnostics, dirty) = (&mut self.diagnostics, &mut self.dirty);
+        let on_edit = |buf: &Rope, edit: &Edit| {
+            read_only.shift(buf, edit);
+            line_flags.shift(buf, edit);
+            if let Some(lines) = diagnostics.shift(buf, edit) {
+                dirty.mark(lines, 0);
+            }
+            dirty.mark_edit(buf, edit);
+        };
+        match self.undo.redo(&mut self.buf, on_edit) {
+            Some(idx) => {
+                self.selection = Selection {
+                    focus: self.char_to_cursor(idx),
+                    anchor: None,
+                    marking: false,
+                };
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replace the buffer with the previous history entry.
+    ///
+    /// Returns `false` if there is no older entry.
+    pub fn history_previous(&mut self) -> bool {
+        let history = match &self.history {
+            Some(history) => history,
+            None => return false,
+        };
+        let index = match self.history_cursor.index {
+            Some(0) => return false,
+            Some(index) => index - 1,
+            None if history.is_empty() => return false,
+            None => {
+                self.history_cursor.draft = self.contents();
+                history.len() - 1
+            }
+        };
+        let entry = history.get(index).unwrap_or_default().to_string();
+        self.history_cursor.index = Some(index);
+        self.replace_contents(&entry);
+        true
+    }
 
-        let lines = str.lines().count().max(1);
+    /// Replace the buffer with the next history entry,
+    /// or with the draft after the newest entry.
+    ///
+    /// Returns `false` if not browsing the history.
+    pub fn history_next(&mut self) -> bool {
+        let (index, history) = match (self.history_cursor.index, &self.history) {
+            (Some(index), Some(history)) => (index, history),
+            _ => return false,
+        };
+        let entry = if index + 1 < history.len() {
+            self.history_cursor.index = Some(index + 1);
+            history.get(index + 1).unwrap_or_default().to_string()
+        } else {
+            self.history_cursor.index = None;
+            std::mem::take(&mut self.history_cursor.draft)
+        };
+        self.replace_contents(&entry);
+        true
+    }
+
+    /// Replace the whole buffer as a single edit,
+    /// moving the cursor to the end.
+    pub(crate) fn replace_contents(&mut self, text: &str) {
+        let text = self.limits.clamp(text).into_owned();
+        self.reset_input_filters();
+        self.undo.begin(self.selection);
+        self.remove_text(0, self.buf.len_chars());
+        self.insert_text(0, &text);
+        if self.end_edit() {
+            self.selection = Selection {
+                focus: self.char_to_cursor(self.buf.len_chars()),
+                anchor: None,
+                marking: false,
+            };
+        }
+    }
+
+    /// Get the edit history of the editor.
+    pub fn undo_stack(&self) -> &UndoStack {
+        &self.undo
+    }
+
+    /// Insert text at a character index of the buffer, recording the edit.
+    fn insert_text(&mut self, idx: usize, text: &str) {
+        let edit = Edit::Insert {
+            idx,
+            text: text.to_string(),
+        };
+        if self.accept_edit(&edit) {
+            self.undo.record(edit, self.selection);
+            self.buf.insert(idx, text);
+        }
+    }
+
+    /// Remove a character range of the buffer, recording the edit.
+    fn remove_text(&mut self, start: usize, end: usize) {
+        let edit = Edit::Remove {
+            idx: start,
+            text: self.buf.slice(start..end).to_string(),
+        };
+        if self.accept_edit(&edit) {
+            self.undo.record(edit, self.selection);
+            self.buf.remove(start..end);
+        }
+    }
 
-        self.selection.focus.ln += lines - 1;
-        self.selection.focus.col = self.selection.focus.col + str.lines().last().unwrap_or_default().len();
+    /// Check an edit against the read-only lines and the limits before
+    /// applying it, rejecting its whole group if it is not allowed.
+    fn accept_edit(&mut self, edit: &Edit) -> bool {
+        if self.undo.rejecting() || !self.read_only.allows(&self.buf, edit) {
+            self.reject_edit();
+            return false;
+        }
+        if !self.limits.allows(&self.buf, edit) {
+            self.reject_edit();
+            if let Some(message) = &self.limits.message {
+                self.validation_error = Some(message.clone());
+            }
+            self.bell |= self.limits.bell;
+            return false;
+        }
+        self.shift_for_edit(edit);
+        true
+    }
+
+    /// Reject the group of edits in progress, reverting the edits
+    /// of it that were already applied.
+    fn reject_edit(&mut self) {
+        let applied = self.undo.reject(self.selection);
+        for edit in applied.iter().rev().map(Edit::inverse) {
+            self.shift_for_edit(&edit);
+            edit.apply(&mut self.buf);
+        }
+    }
+
+    /// Move what is kept by line along with an edit that is about to be
+    /// applied, and mark what it changes as dirty.
+    fn shift_for_edit(&mut self, edit: &Edit) {
+        self.read_only.shift(&self.buf, edit);
+        self.line_flags.shift(&self.buf, edit);
+        if let Some(lines) = self.diagnostics.shift(&self.buf, edit) {
+            self.dirty.mark(lines, 0);
+        }
+        self.dirty.mark_edit(&self.buf, edit);
+        if let Some(log) = &mut self.edit_log {
+            log.push(edit.clone());
+        }
+    }
+
+    /// Stop grouping edits, putting the cursor back to where it was
+    /// before the group if it was rejected.
+    ///
+    /// Returns `false` if the group was rejected.
+    fn end_edit(&mut self) -> bool {
+        let kept = !self.undo.rejecting();
+        self.undo.end();
+        self.restore_rejected();
+        kept
+    }
+
+    /// Put the cursor back to where it was before the last
+    /// group of edits that was rejected.
+    pub(crate) fn restore_rejected(&mut self) {
+        if let Some(selection) = self.undo.take_rejected() {
+            self.selection = selection;
+        }
+    }
+
+    pub(crate) fn char_to_cursor(&self, idx: usize) -> Cursor {
+        let ln = self.buf.char_to_line(idx);
+        Cursor {
+            ln,
+            col: idx - self.buf.line_to_char(ln),
+        }
     }
 
     fn rope_idx(&self, cursor: Cursor, offset: isize) -> usize {
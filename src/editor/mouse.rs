@@ -0,0 +1,86 @@
+use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+use super::{actions::Action, macros::Recorded, paste::Locate, Editor};
+use crate::Result;
+
+/// Number of lines that the cursor moves by for each step of the wheel.
+const SCROLL_LINES: usize = 3;
+
+impl Editor {
+    /// Act upon the editor with a mouse event, finding the position
+    /// that it points at through the renderer with `locate`.
+    ///
+    /// Clicking moves the cursor, extending the selection with `Shift`,
+    /// and dragging selects up to where the mouse is. The wheel moves
+    /// the cursor up and down, which scrolls the lines that are shown.
+    /// Clicking with `Alt` adds another cursor instead, and clicking
+    /// without it goes back to a single cursor. Clicking the margin
+    /// calls the gutter click callback if there is one.
+    pub(crate) fn process_mouse(
+        &mut self,
+        locate: &mut Locate<'_>,
+        event: MouseEvent,
+    ) -> Result<()> {
+        let anchored = match event.kind {
+            MouseEventKind::Down(MouseButton::Left)
+                if event.modifiers.contains(KeyModifiers::ALT) =>
+            {
+                if let Some((cursor, _)) = locate(self, event.column, event.row)? {
+                    self.add_cursor(cursor);
+                    self.record(Recorded::AddCursor(cursor));
+                }
+                return Ok(());
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                event.modifiers.contains(KeyModifiers::SHIFT)
+            }
+            MouseEventKind::Drag(MouseButton::Left) => true,
+            MouseEventKind::ScrollUp => {
+                for _ in 0..SCROLL_LINES.min(self.selection.focus.ln) {
+                    self.move_up(false);
+                    self.record(Recorded::Action(Action::MoveUp(false)));
+                }
+                return Ok(());
+            }
+            MouseEventKind::ScrollDown => {
+                let below = self.line_count() - 1 - self.selection.focus.ln;
+                for _ in 0..SCROLL_LINES.min(below) {
+                    self.move_down(false);
+                    self.record(Recorded::Action(Action::MoveDown(false)));
+                }
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+
+        let (cursor, in_margin) = match locate(self, event.column, event.row)? {
+            Some(location) => location,
+            None => return Ok(()),
+        };
+        if let (true, MouseEventKind::Down(_)) = (in_margin, event.kind) {
+            if let Some(on_gutter_click) = self.on_gutter_click.take() {
+                on_gutter_click(self, cursor.ln);
+                self.on_gutter_click = Some(on_gutter_click);
+                return Ok(());
+            }
+        }
+
+        self.leave_for_mouse();
+        self.selection.set_anchor(anchored);
+        self.selection.focus = cursor;
+        self.selection.fix_anchor();
+        self.record(Recorded::Select(self.selection));
+        Ok(())
+    }
+
+    /// Stop searching, completing, marking and composing, and go back to
+    /// a single cursor, before the mouse moves the cursor.
+    pub(crate) fn leave_for_mouse(&mut self) {
+        self.reset_input_filters();
+        self.accept_search();
+        self.accept_buffer_search();
+        self.dismiss_completion();
+        self.clear_cursors();
+        self.selection.marking = false;
+    }
+}
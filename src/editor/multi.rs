@@ -0,0 +1,245 @@
+use super::{
+    actions::Action,
+    selection::{Cursor, Selection},
+    undo::Edit,
+    Editor,
+};
+use crate::util::match_indices;
+
+/// Move a character index of the buffer along with an edit.
+fn shift(idx: usize, edit: &Edit) -> usize {
+    match edit {
+        Edit::Insert { idx: at, text } if idx >= *at => idx + text.chars().count(),
+        Edit::Remove { idx: at, text } => {
+            let end = at + text.chars().count();
+            if idx >= end {
+                idx - (end - at)
+            } else {
+                idx.min(*at)
+            }
+        }
+        _ => idx,
+    }
+}
+
+impl Editor {
+    /// Add a cursor besides the main one, which is moved and edits
+    /// along with it. Positions that already have a cursor are ignored.
+    pub fn add_cursor(&mut self, cursor: Cursor) {
+        let cursor = self.clamp_cursor(cursor);
+        let taken = std::iter::once(&self.selection)
+            .chain(&self.cursors)
+            .any(|selection| selection.focus == cursor);
+        if !taken {
+            self.cursors.push(Selection {
+                focus: cursor,
+                ..Selection::default()
+            });
+            self.mark_dirty(0..usize::MAX);
+        }
+    }
+
+    /// Get the cursors besides the main one, along with their selections.
+    pub fn cursors(&self) -> &[Selection] {
+        &self.cursors
+    }
+
+    /// Remove the cursors besides the main one.
+    pub fn clear_cursors(&mut self) {
+        if !self.cursors.is_empty() {
+            self.cursors.clear();
+            self.mark_dirty(0..usize::MAX);
+        }
+    }
+
+    /// Select the word under the cursor, or add a cursor that selects
+    /// the next occurrence of the text selected by the newest cursor.
+    pub fn add_next_match(&mut self) {
+        let newest = *self.cursors.last().unwrap_or(&self.selection);
+        let (start, end) = match newest.range() {
+            Some(range) => range,
+            None => {
+                self.select_word();
+                return;
+            }
+        };
+        let start = self.rope_idx(start, 0);
+        let end = self.rope_idx(end, 0);
+        let needle = self.buf.slice(start..end).to_string();
+        let (after, before): (Vec<_>, Vec<_>) = match_indices(self.buf.slice(..), &needle)
+            .into_iter()
+            .partition(|&idx| idx >= end);
+
+        // Look after the newest cursor first, wrapping around to the start.
+        let taken: Vec<usize> = std::iter::once(&self.selection)
+            .chain(&self.cursors)
+            .filter_map(|selection| selection.range())
+            .map(|(start, _)| self.rope_idx(start, 0))
+            .collect();
+        let found = after
+            .into_iter()
+            .chain(before)
+            .find(|idx| !taken.contains(idx));
+        if let Some(idx) = found {
+            self.cursors.push(Selection {
+                focus: self.char_to_cursor(idx + (end - start)),
+                anchor: Some(self.char_to_cursor(idx)),
+                marking: false,
+            });
+            self.mark_dirty(0..usize::MAX);
+        }
+    }
+
+    /// Select the word that the cursor is in or right after.
+    fn select_word(&mut self) {
+        self.clamp();
+        let is_word = self.word_chars;
+        let chars: Vec<char> = self.curr_ln_chars().collect();
+        let col = self.selection.focus.col;
+        let start = col
+            - chars[..col]
+                .iter()
+                .rev()
+                .take_while(|&&c| is_word(c))
+                .count();
+        let end = col + chars[col..].iter().take_while(|&&c| is_word(c)).count();
+        if start < end {
+            let ln = self.selection.focus.ln;
+            self.selection.anchor = Some(Cursor { ln, col: start });
+            self.selection.focus = Cursor { ln, col: end };
+        }
+    }
+
+    /// Apply an action at every cursor when there is more than one,
+    /// returning `None` if it should only be applied at the main cursor.
+    ///
+    /// Actions that cannot be repeated at every cursor remove the other
+    /// cursors first, except for `Submit` which only removes them.
+    pub(crate) fn apply_at_cursors(&mut self, action: Action) -> Option<bool> {
+        if self.cursors.is_empty() {
+            return None;
+        }
+        let repeated = matches!(
+            action,
+            Action::InsertChar(_)
+                | Action::InsertNewline
+                | Action::NewlineOrSubmit
+                | Action::DeleteBackward
+                | Action::DeleteForward
+                | Action::DeleteWordBackward
+                | Action::DeleteWordForward
+                | Action::Paste
+                | Action::MoveLeft(_)
+                | Action::MoveRight(_)
+                | Action::MoveWordLeft(_)
+                | Action::MoveWordRight(_)
+                | Action::MoveUp(_)
+                | Action::MoveDown(_)
+                | Action::MoveHome(_)
+                | Action::MoveEnd(_)
+                | Action::MoveLineStart(_)
+        );
+        if !repeated {
+            return match action {
+                Action::AddNextMatch => None,
+                Action::Submit => {
+                    self.clear_cursors();
+                    Some(true)
+                }
+                _ => {
+                    self.clear_cursors();
+                    None
+                }
+            };
+        }
+
+        // Cursors are kept as character indices, which are moved
+        // along with the edits made at each of the other cursors.
+        let cursors = std::mem::take(&mut self.cursors);
+        let mut all: Vec<(usize, Option<usize>, bool)> = std::iter::once(self.selection)
+            .chain(cursors.iter().copied())
+            .map(|selection| {
+                let focus = self.rope_idx(selection.focus, 0);
+                let anchor = selection.anchor.map(|anchor| self.rope_idx(anchor, 0));
+                (focus, anchor, selection.marking)
+            })
+            .collect();
+
+        self.undo.begin(self.selection);
+        for i in 0..all.len() {
+            let (focus, anchor, marking) = all[i];
+            self.selection = Selection {
+                focus: self.char_to_cursor(focus),
+                anchor: anchor.map(|anchor| self.char_to_cursor(anchor)),
+                marking,
+            };
+            self.edit_log = Some(Vec::new());
+            self.apply_at_cursor(action);
+            let edits = self.edit_log.take().unwrap_or_default();
+            if self.undo.rejecting() {
+                break;
+            }
+
+            for (j, (focus, anchor, _)) in all.iter_mut().enumerate() {
+                if j != i {
+                    for edit in &edits {
+                        *focus = shift(*focus, edit);
+                        *anchor = anchor.map(|anchor| shift(anchor, edit));
+                    }
+                }
+            }
+            all[i] = (
+                self.rope_idx(self.selection.focus, 0),
+                self.selection.anchor.map(|anchor| self.rope_idx(anchor, 0)),
+                self.selection.marking,
+            );
+        }
+        if !self.end_edit() {
+            // An edit rejected at any cursor leaves all of them as they were.
+            self.cursors = cursors;
+            return Some(true);
+        }
+
+        // Cursors that ran into each other become one.
+        let mut selections: Vec<Selection> = Vec::new();
+        for (focus, anchor, marking) in all {
+            let focus = self.char_to_cursor(focus);
+            if selections.iter().all(|other| other.focus != focus) {
+                selections.push(Selection {
+                    focus,
+                    anchor: anchor.map(|anchor| self.char_to_cursor(anchor)),
+                    marking,
+                });
+            }
+        }
+        self.selection = selections.remove(0);
+        self.cursors = selections;
+        self.mark_dirty(0..usize::MAX);
+        Some(true)
+    }
+
+    /// Apply an action at the main cursor, where it is one of the
+    /// actions that are repeated at every cursor.
+    fn apply_at_cursor(&mut self, action: Action) {
+        match action {
+            Action::InsertChar(c) => self.type_paired(c),
+            Action::InsertNewline | Action::NewlineOrSubmit => self.insert_newline(),
+            Action::DeleteBackward if self.delete_pair() || self.delete_soft_tab() => {}
+            Action::DeleteBackward => self.backspace(),
+            Action::DeleteForward => self.delete(),
+            Action::DeleteWordBackward => self.delete_word_backward(),
+            Action::DeleteWordForward => self.delete_word_forward(),
+            Action::Paste => self.paste(),
+            Action::MoveLeft(anchored) => self.move_left(anchored),
+            Action::MoveRight(anchored) => self.move_right(anchored),
+            Action::MoveWordLeft(anchored) => self.move_word_left(anchored),
+            Action::MoveWordRight(anchored) => self.move_word_right(anchored),
+            Action::MoveUp(anchored) => self.move_up(anchored),
+            Action::MoveDown(anchored) => self.move_down(anchored),
+            Action::MoveHome(anchored) => self.move_home(anchored),
+            Action::MoveEnd(anchored) => self.move_to_line_end(anchored),
+            Action::MoveLineStart(anchored) => self.move_to_col(0, anchored),
+            _ => {}
+        }
+    }
+}
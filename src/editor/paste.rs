@@ -0,0 +1,95 @@
+use crossterm::event::{Event, KeyEvent, KeyEventKind};
+
+use super::{keybindings::Keybinding, macros::Recorded, selection::Cursor, Editor};
+use crate::{
+    backend::{key_events, Input},
+    Result,
+};
+
+/// Finds the position in the buffer at a column and row of the terminal,
+/// which is what [`Renderer::locate`](crate::renderer::Renderer::locate) does,
+/// along with whether the column is in the margin.
+pub(crate) type Locate<'a> = dyn FnMut(&Editor, u16, u16) -> Result<Option<(Cursor, bool)>> + 'a;
+
+impl Editor {
+    /// Insert a block of pasted text as a single edit.
+    pub fn insert_paste(&mut self, text: &str) {
+        self.reset_input_filters();
+        self.undo.seal();
+        self.dismiss_completion();
+        self.selection.marking = false;
+        self.insert_str(text);
+        self.undo.seal();
+    }
+
+    /// Insert a paste from the terminal, recording it in the keyboard macro.
+    pub(crate) fn paste_input(&mut self, text: String) {
+        self.insert_paste(&text);
+        self.restore_rejected();
+        self.record(Recorded::Paste(text));
+    }
+
+    /// Process a batch of inputs that arrived together.
+    ///
+    /// Pastes are inserted as text instead of going through the keybinding,
+    /// which keeps `Enter` from submitting and lets the whole paste be drawn
+    /// and undone at once, except while searching where they are typed into
+    /// the search. Mouse events are located with `locate` instead of going
+    /// through the keybinding.
+    pub(crate) fn process_events(
+        &mut self,
+        keybinding: &impl Keybinding,
+        locate: &mut Locate<'_>,
+        inputs: Vec<Input>,
+    ) -> Result<bool> {
+        let revision = self.revision();
+        let proceed = self.process_batch(keybinding, locate, inputs);
+        if let (true, Some(on_change)) = (self.revision() != revision, &self.on_change) {
+            on_change(self);
+        }
+        self.autosave_edits(self.revision() - revision)?;
+        proceed
+    }
+
+    fn process_batch(
+        &mut self,
+        keybinding: &impl Keybinding,
+        locate: &mut Locate<'_>,
+        inputs: Vec<Input>,
+    ) -> Result<bool> {
+        for input in inputs {
+            let events: Vec<Event> = match input {
+                Input::Event(event) => vec![event],
+                Input::Paste(text)
+                    if self.history_search.is_some() || self.buffer_search.is_some() =>
+                {
+                    key_events(&text).collect()
+                }
+                Input::Paste(text) => {
+                    self.paste_input(text);
+                    continue;
+                }
+            };
+            for event in events {
+                // Releases are only reported on Windows, and are not typing.
+                if let Event::Key(KeyEvent {
+                    kind: KeyEventKind::Release,
+                    ..
+                }) = event
+                {
+                    continue;
+                }
+                if let Event::Mouse(mouse) = event {
+                    self.process_mouse(locate, mouse)?;
+                    continue;
+                }
+                let proceed = keybinding.process(self, event)?;
+                self.restore_rejected();
+                if !proceed {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
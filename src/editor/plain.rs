@@ -0,0 +1,153 @@
+use std::io::{self, BufRead};
+
+use crossterm::tty::IsTty;
+use ropey::Rope;
+
+use super::{keybindings::Keybinding, Editor, ReadResult};
+use crate::{renderer::Renderer, Result};
+
+/// Whether the input cannot be edited on the terminal, because the
+/// standard input is not a terminal, such as when it is piped, or the
+/// terminal cannot move the cursor, as with `TERM=dumb`.
+pub fn is_dumb_terminal() -> bool {
+    !io::stdin().is_tty() || std::env::var("TERM").is_ok_and(|term| term == "dumb")
+}
+
+/// Turns off the echo of the terminal on the standard input until it is
+/// dropped, for masked input that cannot be drawn with the mask.
+struct NoEcho {
+    #[cfg(unix)]
+    termios: libc::termios,
+}
+
+impl NoEcho {
+    #[cfg(unix)]
+    fn new() -> io::Result<Self> {
+        // SAFETY: the termios is only read once `tcgetattr` filled it in.
+        unsafe {
+            let mut termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut no_echo = termios;
+            no_echo.c_lflag &= !libc::ECHO;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &no_echo) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self { termios })
+        }
+    }
+
+    /// Masked input is refused where the echo cannot be turned off.
+    #[cfg(not(unix))]
+    fn new() -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "cannot hide masked input on this terminal",
+        ))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for NoEcho {
+    fn drop(&mut self) {
+        // SAFETY: the termios is the one that `tcgetattr` filled in.
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.termios);
+        }
+    }
+}
+
+impl Editor {
+    /// End plain reads once a line with just the sentinel is read, such
+    /// as `.`, which is left out of the contents. Without a sentinel, they
+    /// read until the input ends.
+    pub fn sentinel(self, sentinel: &str) -> Self {
+        Self {
+            sentinel: Some(sentinel.to_string()),
+            ..self
+        }
+    }
+
+    /// Read the input line by line without drawing anything, such as from
+    /// a pipe, until the sentinel or the end of the input. The lines that
+    /// were read replace the contents and are submitted like [`Editor::read`]
+    /// submits them, except that they are not validated.
+    ///
+    /// The read ends with [`ReadResult::Eof`] if the input ends before
+    /// any lines, or the sentinel, are read.
+    pub fn read_plain(&mut self, input: impl BufRead) -> Result<ReadResult> {
+        let mut lines = Vec::new();
+        let mut sentinel = false;
+        for line in input.lines() {
+            let line = line?;
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+            if self.sentinel.as_deref() == Some(line) {
+                sentinel = true;
+                break;
+            }
+            lines.push(line.to_string());
+        }
+        if lines.is_empty() && !sentinel {
+            self.end_read(Ok(ReadResult::Eof));
+        } else {
+            self.reset_buffer(Rope::from_str(&lines.join("\n")));
+            self.move_to_bottom();
+            self.move_to_line_end(false);
+        }
+        self.conclude_read()
+    }
+
+    /// Read the input with a renderer, or line by line from the standard
+    /// input with [`Editor::read_plain`] where [`is_dumb_terminal`] says
+    /// that it cannot be edited on the terminal. The renderer is only made
+    /// when it is used, since making one takes over the terminal.
+    ///
+    /// With a [mask](Editor::mask), the terminal stops echoing the input
+    /// while it is read line by line, and the read fails where the echo
+    /// cannot be turned off.
+    pub fn read_or_plain<R: Renderer>(
+        &mut self,
+        keybinding: impl Keybinding,
+        renderer: impl FnOnce() -> R,
+    ) -> Result<ReadResult> {
+        if is_dumb_terminal() {
+            let stdin = io::stdin();
+            let _no_echo = match self.mask {
+                Some(_) if stdin.is_tty() => Some(NoEcho::new()?),
+                _ => None,
+            };
+            return self.read_plain(stdin.lock());
+        }
+        self.read(keybinding, renderer())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_until_the_input_ends() {
+        let mut editor = Editor::default();
+        let read = editor.read_plain("one\r\ntwo\n".as_bytes()).unwrap();
+        assert_eq!(read, ReadResult::Submitted("one\ntwo".to_string()));
+    }
+
+    #[test]
+    fn reads_until_the_sentinel() {
+        let mut editor = Editor::default().sentinel(".");
+        let read = editor.read_plain("one\n.\ntwo\n".as_bytes()).unwrap();
+        assert_eq!(read, ReadResult::Submitted("one".to_string()));
+    }
+
+    #[test]
+    fn ends_without_lines() {
+        let mut editor = Editor::default();
+        assert_eq!(editor.read_plain("".as_bytes()).unwrap(), ReadResult::Eof);
+
+        let mut editor = Editor::default().sentinel(".");
+        let read = editor.read_plain(".\n".as_bytes()).unwrap();
+        assert_eq!(read, ReadResult::Submitted(String::new()));
+    }
+}
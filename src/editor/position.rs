@@ -0,0 +1,155 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{selection::Cursor, Editor};
+use crate::util::{cell_width, prev_grapheme};
+
+/// Unit that a place in a line is counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineIndex {
+    /// Bytes of the line as UTF-8.
+    Byte(usize),
+    /// Characters, which is what the column of a [`Cursor`] counts.
+    Char(usize),
+    /// Graphemes, which are the characters that are seen on the screen.
+    Grapheme(usize),
+    /// Terminal columns that the line is drawn in, where tabs go up to
+    /// the next tab stop and wide characters take up two columns.
+    Column(usize),
+}
+
+/// Place in a line, counted in every unit of [`LineIndex`].
+///
+/// A place inside of a character is moved to the start of it, and a
+/// terminal column inside of a grapheme to the start of the grapheme,
+/// like where a click in the column puts the cursor. Places past the
+/// end of the line are at its end.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// Index of the line.
+    pub ln: usize,
+    /// Byte offset in the line.
+    pub byte: usize,
+    /// Character offset in the line.
+    pub col: usize,
+    /// Index of the grapheme in the line.
+    pub grapheme: usize,
+    /// Terminal column that the place is drawn at, from the start of the line.
+    pub column: usize,
+}
+
+impl Position {
+    /// Find a place in a line, with tab stops every `tab_width` columns.
+    pub fn in_line(ln: usize, line: &str, index: LineIndex, tab_width: usize) -> Self {
+        let mut position = Self {
+            ln,
+            ..Self::default()
+        };
+        for (grapheme, text) in line.graphemes(true).enumerate() {
+            let width = text.chars().fold(0, |width, c| {
+                width + cell_width(c, position.column + width, tab_width)
+            });
+            let within = match index {
+                LineIndex::Byte(byte) => byte < position.byte + text.len(),
+                LineIndex::Char(col) => col < position.col + text.chars().count(),
+                LineIndex::Grapheme(index) => index == grapheme,
+                LineIndex::Column(column) => column < position.column + width,
+            };
+            if within {
+                // Bytes and characters can be inside of the grapheme.
+                for c in text.chars() {
+                    let before = match index {
+                        LineIndex::Byte(byte) => position.byte + c.len_utf8() <= byte,
+                        LineIndex::Char(col) => position.col < col,
+                        _ => false,
+                    };
+                    if !before {
+                        break;
+                    }
+                    position.byte += c.len_utf8();
+                    position.col += 1;
+                    position.column += cell_width(c, position.column, tab_width);
+                }
+                return position;
+            }
+            position.byte += text.len();
+            position.col += text.chars().count();
+            position.grapheme += 1;
+            position.column += width;
+        }
+        position
+    }
+
+    /// Find the place that is drawn at a row and terminal column of a line
+    /// wrapped to `width` columns, which is the end of the row if the row
+    /// is shorter.
+    pub fn in_wrapped_line(
+        ln: usize,
+        line: &str,
+        row: usize,
+        column: usize,
+        width: usize,
+        tab_width: usize,
+    ) -> Self {
+        let (mut r, mut x) = (0, 0);
+        let mut at = line.chars().count();
+        for (col, c) in line.chars().enumerate() {
+            if x > 0 && x + cell_width(c, x, tab_width) > width {
+                if r == row {
+                    at = prev_grapheme(line, col);
+                    break;
+                }
+                r += 1;
+                x = 0;
+            }
+            let w = cell_width(c, x, tab_width);
+            if r == row && x + w > column {
+                at = prev_grapheme(line, col + 1);
+                break;
+            }
+            x += w;
+        }
+        Self::in_line(ln, line, LineIndex::Char(at), tab_width)
+    }
+
+    /// Get the row and the terminal column of the row that the place is
+    /// drawn at when its line is wrapped to `width` columns.
+    pub fn wrapped(&self, line: &str, width: usize, tab_width: usize) -> (usize, usize) {
+        let (mut row, mut x) = (0, 0);
+        let mut chars = line.chars();
+        for c in chars.by_ref().take(self.col) {
+            if x > 0 && x + cell_width(c, x, tab_width) > width {
+                row += 1;
+                x = 0;
+            }
+            x += cell_width(c, x, tab_width);
+        }
+        // Characters that do not fit are drawn on the next row.
+        match chars.next() {
+            Some(c) if x > 0 && x + cell_width(c, x, tab_width) > width => (row + 1, 0),
+            _ => (row, x),
+        }
+    }
+
+    /// Get the cursor at the place.
+    pub fn cursor(&self) -> Cursor {
+        Cursor {
+            ln: self.ln,
+            col: self.col,
+        }
+    }
+}
+
+impl Editor {
+    /// Find a place in a line of the buffer, counted in any unit,
+    /// with the tab stops of the editor.
+    pub fn position(&self, ln: usize, index: LineIndex) -> Position {
+        Position::in_line(ln, &self.line(ln), index, self.tab_width)
+    }
+
+    /// Get the place of the cursor, which is at the end of its line
+    /// if it is past it.
+    pub fn cursor_position(&self) -> Position {
+        let focus = self.selection.focus;
+        self.position(focus.ln, LineIndex::Char(focus.col))
+    }
+}
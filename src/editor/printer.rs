@@ -0,0 +1,60 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use super::Editor;
+use crate::{renderer::Renderer, Result};
+
+/// Handle that prints lines above the editor while it is reading.
+///
+/// The handle can be cloned and sent to other threads.
+#[derive(Debug, Clone)]
+pub struct ExternalPrinter {
+    sender: Sender<String>,
+}
+
+impl ExternalPrinter {
+    /// Queue a message to be printed above the editor.
+    ///
+    /// Returns `false` if the editor no longer exists.
+    pub fn print(&self, message: impl Into<String>) -> bool {
+        self.sender.send(message.into()).is_ok()
+    }
+}
+
+/// Both ends of the channel of the external printers.
+pub(crate) struct PrinterQueue {
+    sender: Sender<String>,
+    receiver: Receiver<String>,
+}
+
+impl Editor {
+    /// Get a handle that prints lines above the editor while it is reading.
+    ///
+    /// Renderers have to support polling for events, as the editor checks
+    /// for messages in between them.
+    pub fn external_printer(&mut self) -> ExternalPrinter {
+        let queue = self.printer.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            PrinterQueue { sender, receiver }
+        });
+        ExternalPrinter {
+            sender: queue.sender.clone(),
+        }
+    }
+
+    /// Print the queued messages above the editor, returning `true`
+    /// if anything was printed and the editor needs to be redrawn.
+    pub(crate) fn print_external(&mut self, renderer: &mut impl Renderer) -> Result<bool> {
+        let queue = match &self.printer {
+            Some(queue) => queue,
+            None => return Ok(false),
+        };
+        let messages: Vec<String> = queue.receiver.try_iter().collect();
+        if messages.is_empty() {
+            return Ok(false);
+        }
+        messages
+            .iter()
+            .try_for_each(|message| renderer.print(message))?;
+        Ok(true)
+    }
+}
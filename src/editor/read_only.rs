@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+
+use ropey::Rope;
+
+use super::{undo::Edit, Editor};
+
+/// Lines of the buffer that cannot be edited, which move along
+/// with the lines that are inserted and removed around them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReadOnlyLines {
+    lines: BTreeSet<usize>,
+}
+
+/// Lines that an edit changes, and whether it only
+/// inserts or removes whole lines before the last one.
+pub(crate) fn span(buf: &Rope, edit: &Edit) -> (usize, usize, bool) {
+    let line_col = |idx: usize| {
+        let ln = buf.char_to_line(idx);
+        (ln, idx - buf.line_to_char(ln))
+    };
+    match edit {
+        Edit::Insert { idx, text } => {
+            let (ln, col) = line_col(*idx);
+            (ln, ln, col == 0 && text.ends_with('\n'))
+        }
+        Edit::Remove { idx, text } => {
+            let (start, start_col) = line_col(*idx);
+            let (end, end_col) = line_col(idx + text.chars().count());
+            (start, end, start_col == 0 && end_col == 0)
+        }
+    }
+}
+
+impl ReadOnlyLines {
+    /// Whether an edit leaves the contents of the read-only lines alone.
+    pub fn allows(&self, buf: &Rope, edit: &Edit) -> bool {
+        if self.lines.is_empty() {
+            return true;
+        }
+        let (start, end, whole_lines) = span(buf, edit);
+        let mut touched = match (edit, whole_lines) {
+            (Edit::Insert { .. }, true) => return true,
+            (Edit::Remove { .. }, true) => self.lines.range(start..end),
+            _ => self.lines.range(start..=end),
+        };
+        touched.next().is_none()
+    }
+
+    /// Move the read-only lines for an edit that is about to be applied.
+    pub fn shift(&mut self, buf: &Rope, edit: &Edit) {
+        if self.lines.is_empty() {
+            return;
+        }
+        let moved = moved_line(buf, edit);
+        self.lines = self.lines.iter().filter_map(|&ln| moved(ln)).collect();
+    }
+}
+
+/// Where a line goes for an edit that is about to be applied,
+/// or `None` if the edit joins it into the line before it.
+pub(crate) fn moved_line(buf: &Rope, edit: &Edit) -> impl Fn(usize) -> Option<usize> {
+    let (start, end, whole_lines) = span(buf, edit);
+    let added = match edit {
+        Edit::Insert { text, .. } => Some(text.matches('\n').count()),
+        Edit::Remove { .. } => None,
+    };
+    move |ln| match added {
+        Some(added) => {
+            let first_moved = if whole_lines { start } else { start + 1 };
+            Some(if ln >= first_moved { ln + added } else { ln })
+        }
+        None => {
+            let (first_dropped, first_moved) = if whole_lines {
+                (start, end)
+            } else {
+                (start + 1, end + 1)
+            };
+            if ln >= first_moved {
+                Some(ln - (end - start))
+            } else if ln < first_dropped {
+                Some(ln)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+impl Editor {
+    /// Protect a line from being edited, or allow editing it again.
+    ///
+    /// Edits that would change a read-only line are rejected, along with
+    /// the rest of the action that they are part of. Lines can still be
+    /// inserted before a read-only line, and the flag moves along with it.
+    pub fn set_read_only(&mut self, line_idx: usize, read_only: bool) {
+        self.mark_dirty(line_idx..line_idx + 1);
+        if read_only {
+            self.read_only.lines.insert(line_idx);
+        } else {
+            self.read_only.lines.remove(&line_idx);
+        }
+    }
+
+    /// Whether a line is protected from being edited.
+    pub fn is_read_only(&self, line_idx: usize) -> bool {
+        self.read_only.lines.contains(&line_idx)
+    }
+}
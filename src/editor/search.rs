@@ -0,0 +1,161 @@
+use super::{actions::Action, selection::Selection, Editor};
+use crate::util::match_indices;
+
+/// State of an incremental search through the buffer.
+#[derive(Debug, Clone)]
+pub struct BufferSearch {
+    query: String,
+    failed: bool,
+    selection: Selection,
+}
+
+impl BufferSearch {
+    /// Get the text being searched for.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Whether the buffer does not contain the query.
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+}
+
+impl Editor {
+    /// Start an incremental search through the buffer, or jump to the
+    /// next match if already searching, wrapping around at the end.
+    pub fn search_buffer(&mut self) {
+        match &self.buffer_search {
+            Some(_) => {
+                let from = self.match_start() + 1;
+                self.find_match(from, true);
+            }
+            None => {
+                self.buffer_search = Some(BufferSearch {
+                    query: String::new(),
+                    failed: false,
+                    selection: self.selection,
+                });
+            }
+        }
+    }
+
+    /// Get the buffer search in progress.
+    pub fn buffer_search(&self) -> Option<&BufferSearch> {
+        self.buffer_search.as_ref()
+    }
+
+    /// Columns of the matches of the search query on a line,
+    /// as ranges of characters. There are none while the input is
+    /// masked, which would show where the hidden text matches.
+    pub fn search_matches(&self, ln: usize) -> Vec<(usize, usize)> {
+        let query = match &self.buffer_search {
+            Some(search) if !search.query.is_empty() && self.mask.is_none() => &search.query,
+            _ => return Vec::new(),
+        };
+        let line = self.line(ln);
+        let len = query.chars().count();
+        line.match_indices(query.as_str())
+            .map(|(byte, _)| {
+                let col = line[..byte].chars().count();
+                (col, col + len)
+            })
+            .collect()
+    }
+
+    /// Stop searching, leaving the match selected.
+    pub fn accept_buffer_search(&mut self) {
+        if self.buffer_search.take().is_some() {
+            self.mark_dirty(0..usize::MAX);
+        }
+    }
+
+    /// Stop searching, putting the selection back to where it was
+    /// before the search.
+    pub fn abort_buffer_search(&mut self) {
+        if let Some(search) = self.buffer_search.take() {
+            self.selection = search.selection;
+            self.mark_dirty(0..usize::MAX);
+        }
+    }
+
+    /// Apply an action while searching, returning `None`
+    /// if it should be applied to the editor as usual.
+    pub(crate) fn apply_buffer_search(&mut self, action: Action) -> Option<bool> {
+        let search = self.buffer_search.as_mut()?;
+        match action {
+            Action::InsertChar(c) => {
+                search.query.push(c);
+                let from = self.match_start();
+                self.find_match(from, true);
+            }
+            Action::DeleteBackward => {
+                search.query.pop();
+                let from = self.match_start();
+                self.find_match(from, true);
+            }
+            Action::SearchBuffer => self.search_buffer(),
+            Action::SearchHistory => {
+                let from = self.match_start();
+                self.find_match(from, false);
+            }
+            Action::Submit | Action::NewlineOrSubmit | Action::InsertNewline => {
+                self.accept_buffer_search()
+            }
+            Action::Interrupt => self.abort_buffer_search(),
+            _ => {
+                self.accept_buffer_search();
+                return None;
+            }
+        }
+        Some(true)
+    }
+
+    /// Character index of the start of the current match,
+    /// or of the cursor if nothing matched.
+    fn match_start(&self) -> usize {
+        let start = match self.selection.range() {
+            Some((start, _)) => start,
+            None => self.selection.focus,
+        };
+        self.rope_idx(start, 0)
+    }
+
+    /// Select the first match of the query from a character index,
+    /// or the last one before it if not `forward`, wrapping around
+    /// the buffer. Without a match, the selection is left alone.
+    fn find_match(&mut self, from: usize, forward: bool) {
+        self.mark_dirty(0..usize::MAX);
+        let search = match &mut self.buffer_search {
+            Some(search) => search,
+            None => return,
+        };
+        if search.query.is_empty() {
+            search.failed = false;
+            self.selection = search.selection;
+            return;
+        }
+
+        let starts = match_indices(self.buf.slice(..), &search.query).into_iter();
+        let found = if forward {
+            let (after, before): (Vec<_>, Vec<_>) = starts.partition(|&i| i >= from);
+            after.first().or_else(|| before.first()).copied()
+        } else {
+            let (before, after): (Vec<_>, Vec<_>) = starts.partition(|&i| i < from);
+            before.last().or_else(|| after.last()).copied()
+        };
+
+        match found {
+            Some(start) => {
+                search.failed = false;
+                let end = start + search.query.chars().count();
+                self.selection = Selection {
+                    focus: self.char_to_cursor(end),
+                    anchor: Some(self.char_to_cursor(start)),
+                    marking: false,
+                };
+            }
+            None => search.failed = true,
+        }
+    }
+}
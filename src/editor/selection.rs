@@ -1,13 +1,20 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Cursor {
     pub ln: usize,
     pub col: usize,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Selection {
     pub focus: Cursor,
-    pub anchor: Option<Cursor>
+    pub anchor: Option<Cursor>,
+    /// Whether movement extends the selection even when it is not anchored.
+    pub marking: bool,
 }
 
 impl Selection {
@@ -18,14 +25,31 @@ impl Selection {
         }
     }
 
-    /// Anchor if there was not already an anchor, or unanchor.
+    /// Whether any part of a line is selected.
+    pub fn spans_line(&self, ln: usize) -> bool {
+        match self.anchor {
+            Some(anchor) => {
+                self.focus.ln.min(anchor.ln) <= ln && ln <= self.focus.ln.max(anchor.ln)
+            }
+            None => false,
+        }
+    }
+
+    /// Anchor if there was not already an anchor, or unanchor
+    /// unless marking.
     pub fn set_anchor(&mut self, anchored: bool) {
-        if anchored {
-            if self.anchor == None {
+        if anchored || self.marking {
+            if self.anchor.is_none() {
                 self.anchor = Some(self.focus);
             }
         } else {
             self.anchor = None
         }
     }
-}
\ No newline at end of file
+
+    /// Get the start and end of the selected range, if any.
+    pub fn range(&self) -> Option<(Cursor, Cursor)> {
+        self.anchor
+            .map(|anchor| (self.focus.min(anchor), self.focus.max(anchor)))
+    }
+}
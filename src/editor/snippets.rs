@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use super::Editor;
+
+/// Marks where the cursor goes in the expansion of a snippet.
+const PLACEHOLDER: &str = "$0";
+
+/// Abbreviations that expand into longer text when they are typed
+/// right before `Tab` or a space.
+///
+/// The cursor is put where `$0` is in the expansion, or after it
+/// followed by the space that was typed. Lines after the first
+/// are indented like the line of the trigger.
+#[derive(Debug, Clone, Default)]
+pub struct Snippets {
+    expansions: HashMap<String, String>,
+}
+
+impl Snippets {
+    /// Create a set without any snippets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a snippet, replacing the one with the same trigger.
+    pub fn add(mut self, trigger: &str, expansion: &str) -> Self {
+        self.expansions
+            .insert(trigger.to_string(), expansion.to_string());
+        self
+    }
+
+    /// Get the expansion of a trigger.
+    pub fn get(&self, trigger: &str) -> Option<&str> {
+        self.expansions.get(trigger).map(String::as_str)
+    }
+
+    /// Whether there are no snippets.
+    pub fn is_empty(&self) -> bool {
+        self.expansions.is_empty()
+    }
+}
+
+impl Editor {
+    /// Expand the snippet whose trigger is right before the cursor,
+    /// returning `false` if there is none. What is typed next is undone
+    /// separately from the expansion.
+    pub fn expand_snippet(&mut self) -> bool {
+        let expanded = self.expand().is_some();
+        if expanded {
+            self.undo.seal();
+        }
+        expanded
+    }
+
+    /// Expand the snippet before the cursor for a typed space, which
+    /// is kept unless the expansion placed the cursor, and is undone
+    /// along with the expansion.
+    pub(crate) fn expand_snippet_on_space(&mut self) -> bool {
+        self.undo.begin(self.selection);
+        let expanded = match self.expand() {
+            Some(false) => {
+                self.type_char(' ');
+                true
+            }
+            Some(true) => true,
+            None => false,
+        };
+        self.end_edit();
+        if expanded {
+            self.undo.seal();
+        }
+        expanded
+    }
+
+    /// Expand the snippet before the cursor, returning
+    /// whether its expansion had a placeholder.
+    fn expand(&mut self) -> Option<bool> {
+        if self.snippets.is_empty() || self.selection.anchor.is_some() {
+            return None;
+        }
+        self.clamp();
+        let col = self.selection.focus.col;
+        let before: Vec<char> = self.curr_ln_chars().take(col).collect();
+        let start = col
+            - before
+                .iter()
+                .rev()
+                .take_while(|c| !c.is_whitespace())
+                .count();
+        let trigger: String = before[start..].iter().collect();
+        let expansion = self.snippets.get(&trigger)?;
+
+        let indent: String = before.iter().take_while(|c| c.is_whitespace()).collect();
+        let expansion = expansion.replace('\n', &format!("\n{}", indent));
+        let placeholder = expansion.find(PLACEHOLDER);
+        let (text, offset) = match placeholder {
+            Some(at) => (
+                expansion.replacen(PLACEHOLDER, "", 1),
+                expansion[..at].chars().count(),
+            ),
+            None => (expansion.clone(), expansion.chars().count()),
+        };
+
+        self.undo.begin(self.selection);
+        self.delete_ln_range(start, col);
+        let idx = self.rope_idx(self.selection.focus, 0);
+        self.insert_str(&text);
+        if self.end_edit() && self.buf.len_chars() >= idx + offset {
+            self.selection.focus = self.char_to_cursor(idx + offset);
+        }
+        Some(placeholder.is_some())
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use ropey::Rope;
+
+use super::{selection::Selection, Editor};
+
+/// Snapshot of what is being edited, which can be restored into an editor
+/// to resume editing, such as after reconnecting to an application. With
+/// the `serde` feature, it can be serialized to be kept elsewhere.
+///
+/// Only the contents and where the editor is in them are kept, not the
+/// edit history or how the editor is set up.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EditorState {
+    /// Contents of the buffer.
+    pub contents: String,
+    /// Selection of the main cursor.
+    pub selection: Selection,
+    /// Selections of the cursors besides the main one.
+    pub cursors: Vec<Selection>,
+    /// First line that was shown, which is how far the editor was scrolled.
+    pub scroll: usize,
+}
+
+impl Editor {
+    /// Take a snapshot of the contents, the cursors and the scroll.
+    pub fn snapshot(&self) -> EditorState {
+        EditorState {
+            contents: self.contents(),
+            selection: self.selection,
+            cursors: self.cursors.clone(),
+            scroll: self.scroll,
+        }
+    }
+
+    /// Replace the contents, the cursors and the scroll with a snapshot,
+    /// starting a new edit history. Cursors are clamped to the contents.
+    pub fn restore(&mut self, state: &EditorState) {
+        self.reset_buffer(Rope::from_str(&state.contents));
+        let clamp_selection = |selection: &Selection| Selection {
+            focus: self.clamp_cursor(selection.focus),
+            anchor: selection.anchor.map(|anchor| self.clamp_cursor(anchor)),
+            marking: selection.marking,
+        };
+        let selection = clamp_selection(&state.selection);
+        let cursors = state.cursors.iter().map(clamp_selection).collect();
+        self.selection = selection;
+        self.selection.fix_anchor();
+        self.cursors = cursors;
+        self.scroll = state.scroll.min(self.line_count() - 1);
+    }
+}
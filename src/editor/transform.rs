@@ -0,0 +1,59 @@
+/// Cleanup of the contents when they are submitted, so that the
+/// submitted text does not have to be tidied up afterwards.
+///
+/// The history keeps the contents as they were typed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubmitTransform {
+    trim_trailing_whitespace: bool,
+    drop_trailing_blank_lines: bool,
+    crlf: bool,
+}
+
+impl SubmitTransform {
+    /// Create a transform that leaves the contents as they are.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove the whitespace at the end of every line.
+    pub fn trim_trailing_whitespace(self, trim_trailing_whitespace: bool) -> Self {
+        Self {
+            trim_trailing_whitespace,
+            ..self
+        }
+    }
+
+    /// Remove the blank lines at the end of the contents.
+    pub fn drop_trailing_blank_lines(self, drop_trailing_blank_lines: bool) -> Self {
+        Self {
+            drop_trailing_blank_lines,
+            ..self
+        }
+    }
+
+    /// Join the lines with `\r\n` instead of `\n`.
+    pub fn crlf(self, crlf: bool) -> Self {
+        Self { crlf, ..self }
+    }
+
+    /// Apply the transform to submitted contents.
+    pub fn apply(&self, contents: &str) -> String {
+        let mut lines: Vec<&str> = contents.split('\n').collect();
+        if self.trim_trailing_whitespace {
+            for line in &mut lines {
+                *line = line.trim_end();
+            }
+        }
+        if self.drop_trailing_blank_lines {
+            while lines.len() > 1 && lines.last().is_some_and(|line| line.trim().is_empty()) {
+                lines.pop();
+            }
+            if let [line] = lines.as_mut_slice() {
+                if line.trim().is_empty() {
+                    *line = "";
+                }
+            }
+        }
+        lines.join(if self.crlf { "\r\n" } else { "\n" })
+    }
+}
@@ -0,0 +1,242 @@
+use ropey::Rope;
+
+use super::selection::Selection;
+
+/// A single change to the buffer, indexed by characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Edit {
+    Insert { idx: usize, text: String },
+    Remove { idx: usize, text: String },
+}
+
+impl Edit {
+    pub(crate) fn apply(&self, buf: &mut Rope) {
+        match self {
+            Edit::Insert { idx, text } => buf.insert(*idx, text),
+            Edit::Remove { idx, text } => buf.remove(*idx..*idx + text.chars().count()),
+        }
+    }
+
+    /// Get the edit that reverts this one.
+    pub(crate) fn inverse(&self) -> Edit {
+        match self.clone() {
+            Edit::Insert { idx, text } => Edit::Remove { idx, text },
+            Edit::Remove { idx, text } => Edit::Insert { idx, text },
+        }
+    }
+
+    /// Character index of the cursor after applying the edit.
+    fn end(&self) -> usize {
+        match self {
+            Edit::Insert { idx, text } => idx + text.chars().count(),
+            Edit::Remove { idx, .. } => *idx,
+        }
+    }
+
+    /// Whether `next` continues this edit as part of the same word.
+    fn continued_by(&self, next: &Edit) -> bool {
+        fn single(text: &str) -> Option<char> {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c != '\n' => Some(c),
+                _ => None,
+            }
+        }
+
+        match (self, next) {
+            (
+                Edit::Insert { idx, text },
+                Edit::Insert {
+                    idx: next_idx,
+                    text: next_text,
+                },
+            ) => {
+                match (text.chars().last(), single(next_text)) {
+                    (Some(last), Some(c)) => {
+                        // A new word starts a new group.
+                        let word_start = last.is_whitespace() && !c.is_whitespace();
+                        last != '\n' && idx + text.chars().count() == *next_idx && !word_start
+                    }
+                    _ => false,
+                }
+            }
+            (
+                Edit::Remove { idx, text },
+                Edit::Remove {
+                    idx: next_idx,
+                    text: next_text,
+                },
+            ) => {
+                single(text).is_some()
+                    && single(next_text).is_some()
+                    && (*next_idx + 1 == *idx || next_idx == idx)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A group of edits that are undone and redone together.
+#[derive(Debug, Clone)]
+struct Group {
+    edits: Vec<Edit>,
+    /// Selection before the first edit of the group.
+    selection: Selection,
+    /// Whether an edit of the group was rejected.
+    rejected: bool,
+}
+
+/// Edit history of the buffer.
+///
+/// Consecutive typing or deletion is grouped by word, while anything
+/// that spans lines gets a group of its own.
+#[derive(Debug, Clone, Default)]
+pub struct UndoStack {
+    undo: Vec<Group>,
+    redo: Vec<Group>,
+    pending: Option<Group>,
+    depth: usize,
+    sealed: bool,
+    rejected: Option<Selection>,
+}
+
+impl UndoStack {
+    /// Start grouping edits. Calls can be nested, in which case
+    /// the outermost call decides the group.
+    pub(crate) fn begin(&mut self, selection: Selection) {
+        if self.depth == 0 {
+            self.pending = Some(Group {
+                edits: Vec::new(),
+                selection,
+                rejected: false,
+            });
+        }
+        self.depth += 1;
+    }
+
+    /// Stop grouping edits, committing them to the history.
+    pub(crate) fn end(&mut self) {
+        self.depth -= 1;
+        if self.depth > 0 {
+            return;
+        }
+        let group = match self.pending.take() {
+            Some(group) => group,
+            None => return,
+        };
+        if group.rejected {
+            self.rejected = Some(group.selection);
+        }
+        if group.edits.is_empty() {
+            return;
+        }
+
+        self.redo.clear();
+
+        if let ([edit], false) = (group.edits.as_slice(), self.sealed) {
+            if let Some(last) = self.undo.last_mut() {
+                if last
+                    .edits
+                    .last()
+                    .is_some_and(|prev| prev.continued_by(edit))
+                {
+                    last.edits.push(edit.clone());
+                    return;
+                }
+            }
+        }
+
+        self.sealed = false;
+        self.undo.push(group);
+    }
+
+    /// Record an edit that was applied to the buffer.
+    pub(crate) fn record(&mut self, edit: Edit, selection: Selection) {
+        self.begin(selection);
+        if let Some(group) = &mut self.pending {
+            group.edits.push(edit);
+        }
+        self.end();
+    }
+
+    /// Reject the group in progress as a whole, such as after an edit to
+    /// a read-only line, returning the edits of it that were already
+    /// applied to the buffer for them to be reverted.
+    pub(crate) fn reject(&mut self, selection: Selection) -> Vec<Edit> {
+        self.begin(selection);
+        let applied = match &mut self.pending {
+            Some(group) => {
+                group.rejected = true;
+                std::mem::take(&mut group.edits)
+            }
+            None => Vec::new(),
+        };
+        self.end();
+        applied
+    }
+
+    /// Whether the group in progress had an edit rejected.
+    pub(crate) fn rejecting(&self) -> bool {
+        self.pending.as_ref().is_some_and(|group| group.rejected)
+    }
+
+    /// Take the selection from before the last group that had
+    /// an edit rejected, if there was one.
+    pub(crate) fn take_rejected(&mut self) -> Option<Selection> {
+        self.rejected.take()
+    }
+
+    /// Prevent the next edit from merging into the last group.
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// Revert the last group of edits, returning the selection to restore.
+    ///
+    /// Each edit that is applied to the buffer is passed to `on_edit` first.
+    pub(crate) fn undo(
+        &mut self,
+        buf: &mut Rope,
+        mut on_edit: impl FnMut(&Rope, &Edit),
+    ) -> Option<Selection> {
+        let group = self.undo.pop()?;
+        for edit in group.edits.iter().rev().map(Edit::inverse) {
+            on_edit(buf, &edit);
+            edit.apply(buf);
+        }
+        let selection = group.selection;
+        self.redo.push(group);
+        self.sealed = true;
+        Some(selection)
+    }
+
+    /// Reapply the last undone group of edits, returning the
+    /// character index to put the cursor at.
+    ///
+    /// Each edit that is applied to the buffer is passed to `on_edit` first.
+    pub(crate) fn redo(
+        &mut self,
+        buf: &mut Rope,
+        mut on_edit: impl FnMut(&Rope, &Edit),
+    ) -> Option<usize> {
+        let group = self.redo.pop()?;
+        for edit in &group.edits {
+            on_edit(buf, edit);
+            edit.apply(buf);
+        }
+        let end = group.edits.last().map(Edit::end);
+        self.undo.push(group);
+        self.sealed = true;
+        end
+    }
+
+    /// Whether there is anything to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Whether there is anything to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
@@ -0,0 +1,61 @@
+use super::Editor;
+
+/// Whether the contents of the editor can be submitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Validation {
+    /// The input is finished and can be submitted.
+    Complete,
+    /// The input is not finished, so a new line is inserted instead.
+    Incomplete,
+    /// The input cannot be submitted, with a message explaining why.
+    Invalid(String),
+}
+
+/// Decides whether the contents of the editor can be submitted.
+pub trait Validator {
+    /// Validate the lines of the editor.
+    fn validate(&self, lines: &[String]) -> Validation;
+}
+
+impl<F: Fn(&[String]) -> Validation> Validator for F {
+    fn validate(&self, lines: &[String]) -> Validation {
+        self(lines)
+    }
+}
+
+impl Editor {
+    /// Validate the contents before submitting, returning `true`
+    /// if they can be submitted.
+    ///
+    /// Incomplete contents get a new line at the cursor, and invalid
+    /// contents keep the message until the next action. Valid contents
+    /// wait for the submit to be confirmed if the editor asks for it.
+    pub fn try_submit(&mut self) -> bool {
+        match self.validate() {
+            Validation::Complete => !self.ask_confirmation(),
+            Validation::Incomplete => {
+                self.insert_newline();
+                false
+            }
+            Validation::Invalid(message) => {
+                self.validation_error = Some(message);
+                false
+            }
+        }
+    }
+
+    /// Validate the contents, which are complete without a validator.
+    pub(crate) fn validate(&self) -> Validation {
+        let validator = match &self.validator {
+            Some(validator) => validator,
+            None => return Validation::Complete,
+        };
+        let lines: Vec<String> = self.contents().lines().map(String::from).collect();
+        validator.validate(&lines)
+    }
+
+    /// Get the message of the last submit that was invalid.
+    pub fn validation_error(&self) -> Option<&str> {
+        self.validation_error.as_deref()
+    }
+}
@@ -0,0 +1,440 @@
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::{keybindings::NormalKeybinding, selection::Cursor, words, Editor};
+use crate::util::{next_grapheme, prev_grapheme};
+
+/// How keys are interpreted by the default keybindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditMode {
+    /// Every key edits the buffer directly.
+    Standard,
+    /// Keys are interpreted by the mode of a modal vi editor.
+    Vi,
+}
+
+/// Mode of the vi editing mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViMode {
+    /// Keys are motions, operators and commands.
+    Normal,
+    /// Keys type text like they do in the standard mode.
+    Insert,
+    /// Motions extend the selection, which operators then act on.
+    Visual,
+}
+
+impl fmt::Display for ViMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ViMode::Normal => "NORMAL",
+            ViMode::Insert => "INSERT",
+            ViMode::Visual => "VISUAL",
+        })
+    }
+}
+
+/// State of the vi editing mode between keys.
+#[derive(Debug, Clone)]
+pub(crate) struct ViState {
+    mode: ViMode,
+    operator: Option<char>,
+    g: bool,
+}
+
+impl Default for ViState {
+    fn default() -> Self {
+        Self {
+            mode: ViMode::Insert,
+            operator: None,
+            g: false,
+        }
+    }
+}
+
+/// Where a motion moves the cursor, whether operators act on whole lines
+/// with it, and whether they include the character at the target.
+struct Motion {
+    target: Cursor,
+    linewise: bool,
+    inclusive: bool,
+}
+
+impl Editor {
+    /// Get the mode of the vi editing mode, if it is enabled.
+    pub fn vi_mode(&self) -> Option<ViMode> {
+        match self.edit_mode {
+            EditMode::Vi => Some(self.vi.mode),
+            EditMode::Standard => None,
+        }
+    }
+
+    fn set_vi_mode(&mut self, mode: ViMode) {
+        self.vi.mode = mode;
+        self.vi.operator = None;
+        self.vi.g = false;
+        self.selection.anchor = match mode {
+            ViMode::Visual => Some(self.selection.focus),
+            ViMode::Normal | ViMode::Insert => None,
+        };
+    }
+
+    /// Process a key of the vi editing mode.
+    ///
+    /// Returns `false` if the key ends the read.
+    pub(crate) fn process_vi_key(&mut self, event: KeyEvent) -> bool {
+        // Keys answer a confirmation the same way as in other modes.
+        if self.confirmation.is_some() {
+            return NormalKeybinding::action(event).is_none_or(|action| self.apply(action));
+        }
+        match (self.vi.mode, event.code) {
+            (ViMode::Insert, KeyCode::Esc) => {
+                self.set_vi_mode(ViMode::Normal);
+                self.clamp();
+                if self.selection.focus.col > 0 {
+                    self.move_left(false);
+                }
+                true
+            }
+            (ViMode::Insert, _) => {
+                NormalKeybinding::action(event).is_none_or(|action| self.apply(action))
+            }
+            (_, KeyCode::Esc) => {
+                self.set_vi_mode(ViMode::Normal);
+                true
+            }
+            // Submitting is left to the insert mode.
+            (_, KeyCode::Enter) => self.vi_command('j'),
+            (_, KeyCode::Char('r')) if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.redo();
+                true
+            }
+            (_, KeyCode::Char(c))
+                if !event
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.vi_command(c);
+                true
+            }
+            (_, KeyCode::Left) => self.vi_command('h'),
+            (_, KeyCode::Right) => self.vi_command('l'),
+            (_, KeyCode::Up) => self.vi_command('k'),
+            (_, KeyCode::Down) => self.vi_command('j'),
+            _ => NormalKeybinding::action(event).is_none_or(|action| self.apply(action)),
+        }
+    }
+
+    /// Run a command of the normal or visual mode.
+    fn vi_command(&mut self, c: char) -> bool {
+        if let Some(motion) = self.vi_motion(c) {
+            match (self.vi.operator.take(), self.vi.mode) {
+                // Changing a word leaves the space after it, like vi does.
+                (Some('c'), _) if c == 'w' => {
+                    let (focus, end) = (self.selection.focus, self.vi_word_end());
+                    let end = self.vi_past(end);
+                    self.vi_operate('c', focus, end, false);
+                }
+                (Some(op), _) => {
+                    let (focus, mut end) = (self.selection.focus, motion.target);
+                    if motion.inclusive {
+                        end = self.vi_past(end);
+                    }
+                    self.vi_operate(op, focus, end, motion.linewise);
+                }
+                (None, ViMode::Visual) => {
+                    self.selection.set_anchor(true);
+                    self.selection.focus = motion.target;
+                }
+                (None, _) => {
+                    self.selection.anchor = None;
+                    self.selection.focus = motion.target;
+                }
+            }
+            return true;
+        }
+        if self.vi.g {
+            self.vi.g = false;
+            self.vi.operator = None;
+            return true;
+        }
+
+        let ln = self.selection.focus.ln;
+        match c {
+            'g' => self.vi.g = true,
+            'd' | 'c' | 'y' if self.vi.mode == ViMode::Visual => {
+                // The selection includes the character under its end.
+                if let Some((start, end)) = self.selection.range() {
+                    self.set_vi_mode(ViMode::Normal);
+                    let end = self.vi_past(end);
+                    self.vi_operate(c, start, end, false);
+                }
+            }
+            'd' | 'c' | 'y' => match self.vi.operator.take() {
+                // Doubling an operator acts on the current line.
+                Some(op) if op == c => {
+                    let focus = self.selection.focus;
+                    self.vi_operate(op, focus, focus, true);
+                }
+                _ => self.vi.operator = Some(c),
+            },
+            'x' if self.vi.mode == ViMode::Visual => return self.vi_command('d'),
+            'D' | 'C' => {
+                let (focus, len) = (self.selection.focus, self.curr_ln_len());
+                let op = c.to_ascii_lowercase();
+                self.vi_operate(op, focus, Cursor { ln, col: len }, false);
+            }
+            'x' => {
+                self.selection.anchor = None;
+                if self.selection.focus.col < self.curr_ln_len() {
+                    let focus = self.selection.focus;
+                    let next = self.vi_past(focus);
+                    self.vi_operate('d', focus, next, false);
+                }
+            }
+            'v' if self.vi.mode == ViMode::Visual => self.set_vi_mode(ViMode::Normal),
+            'v' => self.set_vi_mode(ViMode::Visual),
+            'i' => self.set_vi_mode(ViMode::Insert),
+            'a' => {
+                self.set_vi_mode(ViMode::Insert);
+                if self.selection.focus.col < self.curr_ln_len() {
+                    self.move_right(false);
+                }
+            }
+            'I' => {
+                self.set_vi_mode(ViMode::Insert);
+                self.move_to_col(0, false);
+            }
+            'A' => {
+                self.set_vi_mode(ViMode::Insert);
+                self.move_to_line_end(false);
+            }
+            'o' => {
+                self.set_vi_mode(ViMode::Insert);
+                self.move_to_line_end(false);
+                self.insert_newline();
+            }
+            'O' => {
+                self.set_vi_mode(ViMode::Insert);
+                self.move_to_col(0, false);
+                self.type_char('\n');
+                self.move_up(false);
+            }
+            'p' | 'P' => self.vi_paste(c == 'p'),
+            'u' => {
+                self.undo();
+            }
+            _ => {
+                self.vi.operator = None;
+            }
+        }
+        true
+    }
+
+    /// Get where a motion key moves the cursor.
+    fn vi_motion(&mut self, c: char) -> Option<Motion> {
+        self.clamp();
+        let Cursor { ln, col } = self.selection.focus;
+        let len = self.curr_ln_len();
+        let last = self.line_count() - 1;
+
+        let (target, linewise) = match c {
+            'g' if self.vi.g => {
+                self.vi.g = false;
+                (Cursor { ln: 0, col: 0 }, true)
+            }
+            _ if self.vi.g => return None,
+            'h' => (
+                Cursor {
+                    ln,
+                    col: prev_grapheme(&self.curr_ln(), col),
+                },
+                false,
+            ),
+            'l' => (
+                Cursor {
+                    ln,
+                    col: next_grapheme(&self.curr_ln(), col),
+                },
+                false,
+            ),
+            'j' => (
+                Cursor {
+                    ln: (ln + 1).min(last),
+                    col,
+                },
+                true,
+            ),
+            'k' => (
+                Cursor {
+                    ln: ln.saturating_sub(1),
+                    col,
+                },
+                true,
+            ),
+            '0' => (Cursor { ln, col: 0 }, false),
+            '^' => {
+                let indent = self.curr_ln_chars().take_while(|c| c.is_whitespace());
+                (
+                    Cursor {
+                        ln,
+                        col: indent.count(),
+                    },
+                    false,
+                )
+            }
+            '$' => (Cursor { ln, col: len }, false),
+            'G' => (Cursor { ln: last, col: 0 }, true),
+            'w' => (self.vi_next_word_start(), false),
+            'b' => (self.vi_prev_word_start(), false),
+            'e' => (self.vi_word_end(), false),
+            _ => return None,
+        };
+        Some(Motion {
+            target,
+            linewise,
+            inclusive: c == 'e',
+        })
+    }
+
+    /// Cursor at the start of the next word, or of the next line.
+    fn vi_next_word_start(&self) -> Cursor {
+        let Cursor { ln, col } = self.selection.focus;
+        let chars: Vec<char> = self.curr_ln_chars().collect();
+        let is_word = self.word_chars;
+        let class = |c: char| (!c.is_whitespace(), is_word(c));
+
+        let mut i = col;
+        if let Some(&start) = chars.get(i) {
+            while chars.get(i).is_some_and(|&c| class(c) == class(start)) {
+                i += 1;
+            }
+        }
+        while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+            i += 1;
+        }
+        if i >= chars.len() && ln + 1 < self.line_count() && self.vi.operator.is_none() {
+            Cursor { ln: ln + 1, col: 0 }
+        } else {
+            Cursor { ln, col: i }
+        }
+    }
+
+    /// Cursor at the start of the previous word on the line.
+    fn vi_prev_word_start(&self) -> Cursor {
+        let Cursor { ln, col } = self.selection.focus;
+        let line = self.curr_ln();
+        Cursor {
+            ln,
+            col: words::prev_word(&line, col, self.word_chars),
+        }
+    }
+
+    /// Cursor on the last character of the word, or of the next word
+    /// when already on it.
+    fn vi_word_end(&self) -> Cursor {
+        let Cursor { ln, col } = self.selection.focus;
+        let line = self.curr_ln();
+        let end = words::next_word(&line, next_grapheme(&line, col), self.word_chars);
+        Cursor {
+            ln,
+            col: prev_grapheme(&line, end).max(col),
+        }
+    }
+
+    /// Cursor after the character under `cursor`, which ends a range that
+    /// includes it.
+    fn vi_past(&self, cursor: Cursor) -> Cursor {
+        Cursor {
+            col: next_grapheme(&self.line(cursor.ln), cursor.col),
+            ..cursor
+        }
+    }
+
+    /// Apply an operator to the text between two cursors.
+    fn vi_operate(&mut self, op: char, a: Cursor, b: Cursor, linewise: bool) {
+        let (mut start, mut end) = (a.min(b), a.max(b));
+        let len_of = |editor: &Self, ln: usize| editor.line(ln).chars().count();
+        if linewise {
+            start.col = 0;
+            end = if end.ln + 1 < self.line_count() && op != 'c' {
+                Cursor {
+                    ln: end.ln + 1,
+                    col: 0,
+                }
+            } else {
+                Cursor {
+                    ln: end.ln,
+                    col: len_of(self, end.ln),
+                }
+            };
+        }
+
+        let text = {
+            let idx = |c: Cursor| self.buf.line_to_char(c.ln) + c.col.min(len_of(self, c.ln));
+            let mut text = self.buf.slice(idx(start)..idx(end)).to_string();
+            if linewise && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text
+        };
+        self.kill_ring.push(text);
+
+        match op {
+            'y' => {
+                self.selection.anchor = None;
+                self.selection.focus = start;
+            }
+            'd' => {
+                // Deleting the last lines takes the new-line before them.
+                if linewise && end.ln + 1 == self.line_count() && start.ln > 0 {
+                    start = Cursor {
+                        ln: start.ln - 1,
+                        col: len_of(self, start.ln - 1),
+                    };
+                }
+                self.delete_range(start, end);
+                if linewise {
+                    let indent = self.curr_ln_chars().take_while(|c| c.is_whitespace());
+                    self.selection.focus.col = indent.count();
+                }
+            }
+            'c' => {
+                self.delete_range(start, end);
+                self.set_vi_mode(ViMode::Insert);
+            }
+            _ => {}
+        }
+    }
+
+    /// Paste the newest entry of the kill ring after or before the cursor,
+    /// on its own line if it was yanked with whole lines.
+    fn vi_paste(&mut self, after: bool) {
+        let text = match self.kill_ring.yank() {
+            Some(text) => text.to_string(),
+            None => return,
+        };
+        self.clamp();
+        self.selection.anchor = None;
+        match (text.strip_suffix('\n'), after) {
+            (Some(lines), true) => {
+                let ln = self.selection.focus.ln;
+                self.move_to_line_end(false);
+                self.insert_str(&format!("\n{}", lines));
+                self.selection.focus = Cursor { ln: ln + 1, col: 0 };
+            }
+            (Some(_), false) => {
+                self.move_to_col(0, false);
+                let ln = self.selection.focus.ln;
+                self.insert_str(&text);
+                self.selection.focus = Cursor { ln, col: 0 };
+            }
+            (None, true) => {
+                self.selection.focus = self.vi_past(self.selection.focus);
+                self.insert_str(&text);
+            }
+            (None, false) => self.insert_str(&text),
+        }
+    }
+}
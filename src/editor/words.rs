@@ -0,0 +1,126 @@
+use super::Editor;
+
+/// Whether a character is part of a word by default,
+/// which is true of letters, digits and underscores.
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Column of the start of the word before `col`.
+pub(crate) fn prev_word(line: &str, col: usize, is_word: fn(char) -> bool) -> usize {
+    let chars: Vec<char> = line.chars().take(col).collect();
+    let mut col = chars.len();
+    while col > 0 && !is_word(chars[col - 1]) {
+        col -= 1;
+    }
+    while col > 0 && is_word(chars[col - 1]) {
+        col -= 1;
+    }
+    col
+}
+
+/// Column of the end of the word after `col`.
+pub(crate) fn next_word(line: &str, col: usize, is_word: fn(char) -> bool) -> usize {
+    let mut chars = line.chars().skip(col).peekable();
+    let mut col = col;
+    while chars.next_if(|&c| !is_word(c)).is_some() {
+        col += 1;
+    }
+    while chars.next_if(|&c| is_word(c)).is_some() {
+        col += 1;
+    }
+    col
+}
+
+impl Editor {
+    /// Move the cursor to the start of the previous word,
+    /// or to the end of the previous line.
+    pub fn move_word_left(&mut self, anchored: bool) {
+        self.clamp();
+        let col = self.selection.focus.col;
+        if col == 0 {
+            self.move_left(anchored);
+        } else {
+            self.move_to_col(prev_word(&self.curr_ln(), col, self.word_chars), anchored);
+        }
+    }
+
+    /// Move the cursor to the end of the next word,
+    /// or to the start of the next line.
+    pub fn move_word_right(&mut self, anchored: bool) {
+        self.clamp();
+        let col = self.selection.focus.col;
+        if col == self.curr_ln_len() {
+            self.move_right(anchored);
+        } else {
+            self.move_to_col(next_word(&self.curr_ln(), col, self.word_chars), anchored);
+        }
+    }
+
+    /// Uppercase up to the end of the next word, moving the cursor there.
+    pub fn upcase_word(&mut self) {
+        self.change_word_case(str::to_uppercase);
+    }
+
+    /// Lowercase up to the end of the next word, moving the cursor there.
+    pub fn downcase_word(&mut self) {
+        self.change_word_case(str::to_lowercase);
+    }
+
+    /// Uppercase the first letter of the next word and lowercase the
+    /// rest of it, moving the cursor to its end.
+    pub fn capitalize_word(&mut self) {
+        let is_word = self.word_chars;
+        self.change_word_case(|text| {
+            let start = text.find(is_word).unwrap_or(text.len());
+            let mut rest = text[start..].chars();
+            let first = rest.next().map(char::to_uppercase);
+            let mut text = text[..start].to_string();
+            text.extend(first.into_iter().flatten());
+            text.push_str(&rest.as_str().to_lowercase());
+            text
+        });
+    }
+
+    fn change_word_case(&mut self, f: impl Fn(&str) -> String) {
+        self.clamp();
+        let col = self.selection.focus.col;
+        if col == self.curr_ln_len() {
+            self.move_right(false);
+            return;
+        }
+        let end = next_word(&self.curr_ln(), col, self.word_chars);
+        let word: String = self.curr_ln_chars().skip(col).take(end - col).collect();
+        self.selection.anchor = None;
+        self.undo.begin(self.selection);
+        self.delete_ln_range(col, end);
+        self.insert_str(&f(&word));
+        self.end_edit();
+    }
+
+    /// Delete back to the start of the previous word, or the selection.
+    pub fn delete_word_backward(&mut self) {
+        self.clamp();
+        let col = self.selection.focus.col;
+        if self.selection.anchor.is_some() || col == 0 {
+            self.backspace();
+        } else {
+            self.undo.begin(self.selection);
+            self.delete_ln_range(prev_word(&self.curr_ln(), col, self.word_chars), col);
+            self.end_edit();
+        }
+    }
+
+    /// Delete up to the end of the next word, or the selection.
+    pub fn delete_word_forward(&mut self) {
+        self.clamp();
+        let col = self.selection.focus.col;
+        if self.selection.anchor.is_some() || col == self.curr_ln_len() {
+            self.delete();
+        } else {
+            self.undo.begin(self.selection);
+            self.delete_ln_range(col, next_word(&self.curr_ln(), col, self.word_chars));
+            self.end_edit();
+        }
+    }
+}
@@ -4,8 +4,8 @@ use thiserror::Error;
 pub enum Error {
     #[error("IO error")]
     Io(#[from] std::io::Error),
-    #[error("Terminal error: {0}")]
-    Terminal(#[from] crossterm::ErrorKind),
+    #[error("Read interrupted")]
+    Interrupted,
     #[allow(dead_code)]
     #[error("Unknown error")]
     Unknown,
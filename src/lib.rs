@@ -1,3 +1,5 @@
+/// Module that abstracts the terminal that the editor runs in.
+pub mod backend;
 /// Module that contains core editor functionalities.
 pub mod editor;
 /// Module that handles rendering the editor.
@@ -8,7 +10,7 @@ mod error;
 mod util;
 
 /// The `minime` result type.
-pub use error::Result;
+pub use error::{Error, Result};
 
 /// Re-export of crossterm crate.
 pub use crossterm;
\ No newline at end of file
@@ -7,7 +7,7 @@ use std::{
 use clap::{App, Arg};
 
 use minime::{
-    editor::{keybindings::NormalKeybinding, Editor},
+    editor::{keybindings::NormalKeybinding, Editor, ReadResult},
     renderer::{
         full::CrosstermRenderer,
         styles::fancy::{FancyFooter, FancyGutter},
@@ -15,7 +15,7 @@ use minime::{
     Result,
 };
 
-const CARGO_PKG_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+const CARGO_PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 fn main() -> Result<()> {
     let matches = App::new("Mini-Me")
@@ -75,11 +75,6 @@ fn main() -> Result<()> {
     let stderr = std::io::stderr();
     let mut lock = BufWriter::new(stderr.lock());
 
-    let renderer = CrosstermRenderer::render_to(&mut lock)
-        .max_height(max_height)
-        .margin(FancyGutter)
-        .footer(FancyFooter);
-
     let mut term = Editor::default();
 
     if let Some(file) = file {
@@ -88,9 +83,17 @@ fn main() -> Result<()> {
         term.move_to_line_end(false);
     }
 
-    term.read(NormalKeybinding, renderer)?;
-
-    let contents = term.contents();
+    // Piped input is read as is, without drawing the editor.
+    let read = term.read_or_plain(NormalKeybinding, || {
+        CrosstermRenderer::render_to(&mut lock)
+            .max_height(max_height)
+            .margin(FancyGutter)
+            .footer(FancyFooter)
+    });
+    let contents = match read? {
+        ReadResult::Submitted(contents) => contents,
+        ReadResult::Cancelled | ReadResult::Eof => return Ok(()),
+    };
 
     if let Some(file) = output_path.and_then(|path| {
         OpenOptions::new()
@@ -106,7 +109,7 @@ fn main() -> Result<()> {
         let stdout = std::io::stdout();
         let mut writer = stdout.lock();
         writer.write_all(contents.as_bytes())?;
-        writer.write(b"\n")?;
+        writer.write_all(b"\n")?;
     }
 
     Ok(())
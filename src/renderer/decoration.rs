@@ -0,0 +1,108 @@
+use crossterm::style::ContentStyle;
+
+use super::theme::{merge, queue_style};
+use crate::editor::Editor;
+
+/// Range of a line that is drawn in a style, such as a misspelled word
+/// or a keyword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decoration {
+    /// Column of the first character of the range.
+    pub start: usize,
+    /// Column after the last character of the range.
+    pub end: usize,
+    /// Style that the range is drawn in, over the style of the highlighter.
+    pub style: ContentStyle,
+}
+
+impl Decoration {
+    /// Create a decoration of the characters from `start` up to `end`.
+    pub fn new(start: usize, end: usize, style: ContentStyle) -> Self {
+        Self { start, end, style }
+    }
+}
+
+/// Marks ranges of each line to be drawn in a style, over whatever the
+/// highlighter or the renderer draws the line in.
+///
+/// Decorations are a simpler alternative to a highlighter for marking
+/// a few spans, since the style only has to be given for its range.
+pub trait DecorationProvider {
+    /// Get the decorations of a line as it is shown, in any order.
+    /// Where decorations overlap, the later ones take precedence.
+    fn decorate(&self, line_idx: usize, line: &str, editor: &Editor) -> Vec<Decoration>;
+}
+
+impl<F: Fn(usize, &str, &Editor) -> Vec<Decoration>> DecorationProvider for F {
+    fn decorate(&self, line_idx: usize, line: &str, editor: &Editor) -> Vec<Decoration> {
+        self(line_idx, line, editor)
+    }
+}
+
+/// Escape sequences that start drawing in a style.
+fn style_sequence(style: ContentStyle) -> String {
+    let mut sequence = Vec::new();
+    // Writing to memory does not fail.
+    let _ = queue_style(&mut sequence, style);
+    String::from_utf8_lossy(&sequence).into_owned()
+}
+
+/// Draw decorations over a styled line, starting the style of the
+/// decorations again after every escape sequence within them, and the
+/// styles of the line again after the end of each decoration.
+pub(crate) fn overlay(content: &str, decorations: &[Decoration]) -> String {
+    if decorations.is_empty() {
+        return content.to_string();
+    }
+    let mut decorated = String::with_capacity(content.len());
+    // Sequences since the last reset, which make up the style of the line.
+    let mut active = String::new();
+    let mut current: Option<ContentStyle> = None;
+    let mut col = 0;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            let mut seq = String::from(c);
+            if chars.peek() == Some(&'[') {
+                seq.extend(chars.next());
+                for c in chars.by_ref() {
+                    seq.push(c);
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            decorated.push_str(&seq);
+            match seq.as_str() {
+                "\x1b[0m" | "\x1b[m" => active.clear(),
+                _ => active.push_str(&seq),
+            }
+            if let Some(style) = current {
+                decorated.push_str(&style_sequence(style));
+            }
+            continue;
+        }
+        let style = decorations
+            .iter()
+            .filter(|decoration| decoration.start <= col && col < decoration.end)
+            .map(|decoration| decoration.style)
+            .reduce(merge);
+        if style != current {
+            if current.is_some() {
+                decorated.push_str("\x1b[0m");
+                decorated.push_str(&active);
+            }
+            if let Some(style) = style {
+                decorated.push_str(&style_sequence(style));
+            }
+            current = style;
+        }
+        decorated.push(c);
+        col += 1;
+    }
+    if current.is_some() {
+        decorated.push_str("\x1b[0m");
+        decorated.push_str(&active);
+    }
+    decorated
+}
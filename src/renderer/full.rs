@@ -1,103 +1,530 @@
 use std::{
+    collections::hash_map::DefaultHasher,
     convert::TryInto,
-    io::{stdout, Stdout, Write},
+    hash::{Hash, Hasher},
+    io::{Stderr, Stdout, Write},
+    time::Duration,
 };
 
 use super::{
+    decoration::{overlay, DecorationProvider},
+    diagnostic_decorations, severity_color,
     styles::{Footer, Header, Margin, NoStyle},
+    theme::{merge, queue_style, Theme},
     Editor, Renderer,
 };
-use crate::{editor::selection::Cursor, Result};
+use crate::{
+    backend::{Backend, CrosstermBackend, Input},
+    editor::{
+        position::{LineIndex, Position},
+        selection::Cursor,
+    },
+    util::{cell_width, char_width, prev_grapheme},
+    Result,
+};
 
 use crossterm::{
     cursor::*,
-    terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+    event::Event,
+    style::{Attribute, Color, ContentStyle, SetAttribute, Stylize},
+    terminal::{Clear, ClearType},
     QueueableCommand,
 };
-use raw_mode::RawModeGuard;
+use guard::TerminalGuard;
+
+mod guard {
+    use std::{
+        io::{stderr, stdout, Write},
+        ops::{Deref, DerefMut},
+        panic,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex, MutexGuard, Once, TryLockError,
+        },
+    };
+
+    use crossterm::{
+        cursor::Show,
+        event::{DisableMouseCapture, EnableMouseCapture},
+        style::{Attribute, SetAttribute},
+        terminal::{disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+        QueueableCommand,
+    };
+
+    use super::{CursorStyle, Result};
+    use crate::backend::{Backend, Console};
+
+    const ENABLE_BRACKETED_PASTE: &[u8] = b"\x1b[?2004h";
+    const DISABLE_BRACKETED_PASTE: &[u8] = b"\x1b[?2004l";
+    const RESET_CURSOR_STYLE: &[u8] = b"\x1b[0 q";
+
+    /// Terminal modes that a guard turned on.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    struct Modes {
+        raw: bool,
+        altscreen: bool,
+        mouse: bool,
+        bracketed_paste: bool,
+        cursor_style: Option<CursorStyle>,
+    }
+
+    /// Guard that the panic hook restores the terminal of.
+    #[derive(Debug, Clone, Copy)]
+    struct Registered {
+        id: usize,
+        console: Console,
+        modes: Modes,
+    }
 
-mod raw_mode {
-    use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+    /// Number that the next guard is known by to the panic hook.
+    static NEXT_GUARD: AtomicUsize = AtomicUsize::new(0);
+    /// Guards that have not been dropped yet whose backend draws on the
+    /// console of the process.
+    static REGISTERED: Mutex<Vec<Registered>> = Mutex::new(Vec::new());
+    static PANIC_HOOK: Once = Once::new();
 
-    use super::Result;
+    /// Restore the terminal before the panic message is printed, which
+    /// would otherwise be printed in raw mode or onto the alternate screen
+    /// before the guard is dropped. Only the modes that the guards turned
+    /// on are turned off, on the console that each of them draws on.
+    fn install_panic_hook() {
+        PANIC_HOOK.call_once(|| {
+            let hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                let registered = match REGISTERED.try_lock() {
+                    Ok(registered) => registered.clone(),
+                    Err(TryLockError::Poisoned(registered)) => registered.into_inner().clone(),
+                    Err(TryLockError::WouldBlock) => Vec::new(),
+                };
+                for guard in &registered {
+                    let mut terminal: Box<dyn Write> = match guard.console {
+                        Console::Stdout => Box::new(stdout()),
+                        Console::Stderr => Box::new(stderr()),
+                    };
+                    if guard.modes.altscreen {
+                        let _ = terminal.queue(LeaveAlternateScreen);
+                    }
+                    if guard.modes.mouse {
+                        let _ = terminal.queue(DisableMouseCapture);
+                    }
+                    if guard.modes.bracketed_paste {
+                        let _ = terminal.write_all(DISABLE_BRACKETED_PASTE);
+                    }
+                    if guard.modes.cursor_style.is_some() {
+                        let _ = terminal.write_all(RESET_CURSOR_STYLE);
+                    }
+                    let _ = terminal.queue(SetAttribute(Attribute::Reset));
+                    let _ = terminal.queue(Show);
+                    let _ = terminal.flush();
+                }
+                if registered.iter().any(|guard| guard.modes.raw) {
+                    let _ = disable_raw_mode();
+                }
+                hook(info);
+            }));
+        });
+    }
 
-    pub struct RawModeGuard(());
+    fn registered() -> MutexGuard<'static, Vec<Registered>> {
+        REGISTERED.lock().unwrap_or_else(|e| e.into_inner())
+    }
 
-    impl RawModeGuard {
-        pub fn acquire() -> Result<RawModeGuard> {
-            enable_raw_mode()?;
-            Ok(Self(()))
+    /// Owns the backend of the renderer along with every terminal mode
+    /// that the session turned on, starting with raw mode on the first
+    /// draw or read.
+    ///
+    /// Dropping the guard restores the terminal, which means that the
+    /// cleanup runs on a normal return, on `?` error propagation and
+    /// while unwinding from a panic. A panic hook restores a terminal on
+    /// the console of the process even earlier, so that the message is
+    /// readable.
+    pub struct TerminalGuard<B: Backend> {
+        id: usize,
+        backend: B,
+        modes: Modes,
+    }
+
+    impl<B: Backend> TerminalGuard<B> {
+        /// Take over the backend, leaving the terminal as it is until
+        /// the modes are turned on.
+        pub fn new(backend: B) -> Self {
+            install_panic_hook();
+            let id = NEXT_GUARD.fetch_add(1, Ordering::SeqCst);
+            let modes = Modes::default();
+            if let Some(console) = backend.console() {
+                registered().push(Registered { id, console, modes });
+            }
+            Self { id, backend, modes }
+        }
+
+        /// Enable or disable raw mode, if not already in that state.
+        pub fn set_raw_mode(&mut self, raw: bool) -> Result<()> {
+            if raw && !self.modes.raw {
+                self.backend.enable_raw_mode()?;
+            } else if !raw && self.modes.raw {
+                self.backend.disable_raw_mode()?;
+            }
+            self.set_modes(Modes { raw, ..self.modes });
+            Ok(())
+        }
+
+        /// Enter or leave the alternate screen, if not already in that state.
+        pub fn set_altscreen(&mut self, altscreen: bool) -> Result<()> {
+            if altscreen && !self.modes.altscreen {
+                self.backend.queue(EnterAlternateScreen)?;
+            } else if !altscreen && self.modes.altscreen {
+                self.backend.queue(LeaveAlternateScreen)?;
+            }
+            self.set_modes(Modes {
+                altscreen,
+                ..self.modes
+            });
+            Ok(())
+        }
+
+        /// Start or stop reporting mouse events, if not already in that state.
+        pub fn set_mouse(&mut self, mouse: bool) -> Result<()> {
+            if mouse && !self.modes.mouse {
+                self.backend.queue(EnableMouseCapture)?;
+            } else if !mouse && self.modes.mouse {
+                self.backend.queue(DisableMouseCapture)?;
+            }
+            self.set_modes(Modes {
+                mouse,
+                ..self.modes
+            });
+            Ok(())
+        }
+
+        /// Start or stop marking pastes, if not already in that state, for
+        /// them to be told apart from typing. This is only turned on where
+        /// the backend reads the marked pastes.
+        pub fn set_bracketed_paste(&mut self, bracketed_paste: bool) -> Result<()> {
+            let bracketed_paste = bracketed_paste && self.backend.supports_bracketed_paste();
+            if bracketed_paste && !self.modes.bracketed_paste {
+                self.backend.write_all(ENABLE_BRACKETED_PASTE)?;
+            } else if !bracketed_paste && self.modes.bracketed_paste {
+                self.backend.write_all(DISABLE_BRACKETED_PASTE)?;
+            }
+            self.set_modes(Modes {
+                bracketed_paste,
+                ..self.modes
+            });
+            Ok(())
+        }
+
+        /// Set the shape of the cursor, or give it back to the terminal
+        /// with `None`, if not already in that state.
+        pub fn set_cursor_style(&mut self, cursor_style: Option<CursorStyle>) -> Result<()> {
+            if cursor_style != self.modes.cursor_style {
+                match cursor_style {
+                    Some(style) => write!(self.backend, "\x1b[{} q", style as u8)?,
+                    None => self.backend.write_all(RESET_CURSOR_STYLE)?,
+                }
+            }
+            self.set_modes(Modes {
+                cursor_style,
+                ..self.modes
+            });
+            Ok(())
+        }
+
+        /// Keep the modes, letting the panic hook know of them where it
+        /// restores the terminal of the guard.
+        fn set_modes(&mut self, modes: Modes) {
+            let changed = modes != self.modes;
+            self.modes = modes;
+            if changed && self.backend.console().is_some() {
+                let mut registered = registered();
+                if let Some(guard) = registered.iter_mut().find(|guard| guard.id == self.id) {
+                    guard.modes = modes;
+                }
+            }
+        }
+
+        /// Restore the terminal and have the backend stop the process until
+        /// it is continued. The modes are left off for the next draw or
+        /// read to turn on.
+        pub fn suspend(&mut self) -> Result<()> {
+            self.restore()?;
+            self.backend.suspend()
+        }
+
+        /// Restore the terminal while running something that uses it, such
+        /// as another program. The modes are left off for the next draw or
+        /// read to turn on.
+        pub fn hand_over(&mut self, run: &mut dyn FnMut()) -> Result<()> {
+            self.restore()?;
+            run();
+            Ok(())
+        }
+
+        fn restore(&mut self) -> Result<()> {
+            self.set_altscreen(false)?;
+            self.set_mouse(false)?;
+            self.set_bracketed_paste(false)?;
+            self.set_cursor_style(None)?;
+            self.backend.queue(SetAttribute(Attribute::Reset))?;
+            self.backend.queue(Show)?;
+            self.backend.flush()?;
+            self.set_raw_mode(false)
         }
     }
 
-    impl Drop for RawModeGuard {
+    impl<B: Backend> Deref for TerminalGuard<B> {
+        type Target = B;
+
+        fn deref(&self) -> &B {
+            &self.backend
+        }
+    }
+
+    impl<B: Backend> DerefMut for TerminalGuard<B> {
+        fn deref_mut(&mut self) -> &mut B {
+            &mut self.backend
+        }
+    }
+
+    impl<B: Backend> Drop for TerminalGuard<B> {
         fn drop(&mut self) {
-            disable_raw_mode().unwrap();
+            // Errors can not be reported from here, and panicking
+            // while already unwinding would abort the process.
+            if self.restore().is_err() {
+                let _ = self.set_raw_mode(false);
+            }
+            registered().retain(|guard| guard.id != self.id);
         }
     }
 }
 
-pub struct CrosstermRenderer<'b, W, M, H, F> {
-    guard: RawModeGuard,
-    write: &'b mut W,
+pub struct CrosstermRenderer<B: Backend, M, H, F> {
+    write: TerminalGuard<B>,
     margin: M,
     header: H,
     footer: F,
     draw_state: DrawState,
     max_height: Option<usize>,
+    highlighter: Option<Box<Highlighter>>,
+    decorations: Option<Box<dyn DecorationProvider>>,
+    observer: Option<Box<RenderObserver>>,
+    completion_menu: usize,
+    match_brackets: bool,
+    banner: Vec<String>,
+    placeholder: Option<String>,
+    rulers: Vec<usize>,
+    ruler_style: RulerStyle,
+    diagnostic_messages: bool,
+    synchronized_output: Option<bool>,
+    theme: Theme,
+    highlight_cursor_line: bool,
+    mouse: bool,
+    cursor_style: Option<CursorStyle>,
+    preview: Option<Box<Preview>>,
+    preview_rows: usize,
+    // Lines that the preview gave for a revision of the buffer
+    preview_lines: Option<(usize, Vec<String>)>,
+    anchor: AnchorMode,
+    retain_on_submit: bool,
+    // Rows at the bottom of the terminal that belong to the frame,
+    // or `None` if they have to be found from the cursor
+    bottom_rows: Option<usize>,
+}
+
+/// Where the editor is drawn on the terminal, outside of the fullscreen mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorMode {
+    /// Start from the row that the cursor is on, and grow downwards.
+    Inline,
+    /// Stick to the bottom of the terminal, and grow upwards by scrolling
+    /// the rows above the editor. The rows that a taller frame took up are
+    /// left blank when it shrinks.
+    Bottom,
+    /// Always take up the whole alternate screen, as if the fullscreen mode
+    /// was toggled on, restoring the original screen when finished.
+    Fullscreen,
+}
+
+/// Shape of the cursor, as set with [`CrosstermRenderer::cursor_style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorStyle {
+    /// A blinking block over the character.
+    BlinkingBlock = 1,
+    /// A block over the character that does not blink.
+    SteadyBlock,
+    /// A blinking line under the character.
+    BlinkingUnderline,
+    /// A line under the character that does not blink.
+    SteadyUnderline,
+    /// A blinking bar before the character, as for inserting text.
+    BlinkingBar,
+    /// A bar before the character that does not blink.
+    SteadyBar,
+}
+
+/// How the rulers show the columns that lines should stay within.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RulerStyle {
+    /// Draw a marker at each column on the lines that are shorter.
+    Marker,
+    /// Draw the characters past the first column in the overflow style.
+    Overflow,
+}
+
+/// Transforms a line of the buffer into the styled string that is drawn.
+///
+/// The closure receives the index of the line, its contents and the editor.
+pub type Highlighter = dyn Fn(usize, &str, &Editor) -> String;
+
+/// Produces the text shown below the editor from its contents.
+pub type Preview = dyn Fn(&Editor) -> String;
+
+/// Called after each draw with a report of what it drew.
+pub type RenderObserver = dyn FnMut(&RenderReport);
+
+/// What a draw decided to draw on the terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderDecision {
+    /// Nothing was drawn, since the terminal has no rows for the editor.
+    NoChange,
+    /// No lines were drawn again, only what is below them and the cursor.
+    RedrawCursor,
+    /// These lines were drawn again, along with what is below them.
+    RedrawLines(Vec<usize>),
+    /// The whole frame was drawn.
+    RedrawAll,
+}
+
+/// Report of a draw to the render observer, such as to look into
+/// flicker or slow drawing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderReport {
+    /// What the draw decided to draw.
+    pub decision: RenderDecision,
+    /// Bytes that the draw wrote to the backend, if the backend counts them.
+    pub bytes: Option<u64>,
+}
+
+/// Make the terminal hold off showing what is drawn until the end.
+const BEGIN_SYNCHRONIZED_UPDATE: &[u8] = b"\x1b[?2026h";
+/// Show what was drawn since the beginning of the synchronized update.
+const END_SYNCHRONIZED_UPDATE: &[u8] = b"\x1b[?2026l";
+
+fn hash_of(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Part of a frame that takes up rows of the terminal, in the order
+/// that the parts are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum FramePart {
+    Banner,
+    Header,
+    Text,
+    /// Rows below the text that fill the alternate screen.
+    Fill,
+    Footer,
+    Completions,
+    Search,
+    ValidationError,
+    Confirmation,
+    Preview,
+}
+
+/// Rows that each part of the last frame took up, which every part
+/// registers as it is drawn, so that the height of the frame is always
+/// the rows that were drawn.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+struct Ledger {
+    parts: Vec<(FramePart, usize)>,
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+impl Ledger {
+    /// Record the rows that a part took up.
+    fn register(&mut self, part: FramePart, rows: usize) {
+        if rows > 0 {
+            self.parts.push((part, rows));
+        }
+    }
+
+    /// Forget the parts from `part` on, which are about to be drawn again.
+    fn truncate(&mut self, part: FramePart) {
+        self.parts.retain(|&(drawn, _)| drawn < part);
+    }
+
+    /// Rows that the whole frame took up.
+    fn height(&self) -> usize {
+        self.parts.iter().map(|&(_, rows)| rows).sum()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 /// Contains information about the cursor and the height
 /// of the last frame drawn.
 struct DrawState {
     altscreen: bool,
-    height: usize,
+    ledger: Ledger,
+    // Columns of the terminal
+    columns: usize,
+    // Columns taken up by each row of the banner
+    banner_widths: Vec<usize>,
+    // Columns taken up by each row of the text
+    row_widths: Vec<usize>,
     // Anchor of the box
     anchor: Cursor,
     low: usize,
     high: usize,
-    // Box cursor relative to the anchor
+    // Columns available to the text of each row
+    width: usize,
+    // Box cursor, in rows from the anchor and in columns
+    // from the left edge of the terminal
     cursor: Cursor,
+    // Bracket at the cursor and the one that it pairs with
+    brackets: Vec<Cursor>,
+    // Rows taken up by each line from `low` to `high`
+    line_rows: Vec<usize>,
+    // Lines of the buffer, and the cursor line and selection drawn
+    line_count: usize,
+    focus_ln: usize,
+    selection: Option<(Cursor, Cursor)>,
+    // Whether there were cursors besides the main one
+    cursors: bool,
+    // Hashes of the highlighted contents of each line from `low`
+    styles: Vec<u64>,
 }
 
-impl<W, M, H, F> Renderer for CrosstermRenderer<'_, W, M, H, F>
+impl<B, M, H, F> Renderer for CrosstermRenderer<B, M, H, F>
 where
-    W: Write,
-    M: Margin<W>,
-    H: Header<W>,
-    F: Footer<W>,
+    B: Backend,
+    M: Margin<B>,
+    H: Header<B>,
+    F: Footer<B>,
 {
-    /// Draw the prompt.
+    /// Draw the prompt, as a synchronized update if the terminal supports
+    /// it, and report what was drawn to the observer.
     fn draw(&mut self, data: &Editor) -> Result<()> {
-        if self.draw_state.altscreen {
-            self.write.queue(MoveTo(0, 0))?;
+        self.write.set_raw_mode(true)?;
+        let written = self.write.bytes_written();
+        let synchronized = self
+            .synchronized_output
+            .unwrap_or_else(|| self.write.supports_synchronized_output());
+        let decision = if synchronized {
+            self.write.write_all(BEGIN_SYNCHRONIZED_UPDATE)?;
+            let drawn = self.draw_frame(data);
+            self.write.write_all(END_SYNCHRONIZED_UPDATE)?;
+            self.flush()?;
+            drawn?
         } else {
-            self.move_to_frame_base()?;
+            self.draw_frame(data)?
+        };
+        if let Some(observer) = &mut self.observer {
+            let bytes = written
+                .zip(self.write.bytes_written())
+                .map(|(before, after)| after - before);
+            observer(&RenderReport { decision, bytes });
         }
-
-        let (low, high, term_rows) = self.calculate_draw_range(&data);
-
-        if term_rows == 0 {
-            return Ok(());
-        }
-
-        if data.altscreen && !self.draw_state.altscreen {
-            self.write.queue(EnterAlternateScreen)?;
-        } else if !data.altscreen && self.draw_state.altscreen {
-            self.write.queue(LeaveAlternateScreen)?;
-        }
-
-        self.draw_state = DrawState::default();
-        self.draw_state.altscreen = data.altscreen;
-
-        self.draw_header(&data)?;
-        self.draw_range(&data, low, high, term_rows)?;
-        self.draw_footer(&data)?;
-        self.write.queue(Clear(ClearType::FromCursorDown))?;
-
-        self.draw_cursor(&data)?;
-        self.flush()
+        Ok(())
     }
 
     /// Clear the drawn prompt on the screen.
@@ -111,6 +538,8 @@ where
         }
 
         self.draw_state = DrawState::default();
+        // Whatever is printed next moves the frame.
+        self.bottom_rows = None;
 
         Ok(())
     }
@@ -120,131 +549,824 @@ where
         Ok(())
     }
 
-    fn finish(mut self) -> Result<()> {
+    fn read_event(&mut self) -> Result<Event> {
+        self.write.set_raw_mode(true)?;
+        self.write.read_event()
+    }
+
+    fn read_input(&mut self) -> Result<Input> {
+        self.write.set_raw_mode(true)?;
+        self.write.read_input()
+    }
+
+    fn poll_event(&mut self, timeout: Duration) -> Result<bool> {
+        self.write.set_raw_mode(true)?;
+        self.write.poll_event(timeout)
+    }
+
+    fn print(&mut self, message: &str) -> Result<()> {
         self.clear_draw()?;
+        for line in message.split('\n') {
+            self.cursor_to_left_term_edge()?;
+            self.write.write_all(line.as_bytes())?;
+            self.write.queue(Clear(ClearType::UntilNewLine))?;
+            self.write.write_all(b"\n")?;
+        }
+        Ok(())
+    }
 
-        // if self.draw_state.altscreen {
-        self.write.queue(LeaveAlternateScreen)?;
-        // }
+    fn locate(&mut self, data: &Editor, column: u16, row: u16) -> Result<Option<Cursor>> {
+        if self.draw_state.ledger.height() == 0 {
+            return Ok(None);
+        }
+        // The cursor was left where the last draw put it,
+        // which is how far the frame is from the top of the screen.
+        let frame_top = if self.draw_state.altscreen {
+            0
+        } else {
+            let (_, cursor_row) = self.write.cursor_position()?;
+            let above = self.draw_state.anchor.ln + self.draw_state.cursor.ln;
+            match usize::from(cursor_row).checked_sub(above) {
+                Some(top) => top,
+                None => return Ok(None),
+            }
+        };
 
+        let state = &self.draw_state;
+        let mut text_row = match usize::from(row).checked_sub(frame_top + state.anchor.ln) {
+            Some(text_row) => text_row,
+            None => return Ok(None),
+        };
+        let x = usize::from(column).saturating_sub(state.anchor.col);
+        for (ln, &rows) in (state.low..state.high).zip(&state.line_rows) {
+            if text_row < rows {
+                if ln >= data.line_count() {
+                    break;
+                }
+                let line = data.shown_line(ln);
+                let position =
+                    Position::in_wrapped_line(ln, &line, text_row, x, state.width, data.tab_width);
+                return Ok(Some(position.cursor()));
+            }
+            text_row -= rows;
+        }
+        Ok(None)
+    }
+
+    fn in_margin(&self, column: u16) -> bool {
+        self.draw_state.ledger.height() > 0 && usize::from(column) < self.draw_state.anchor.col
+    }
+
+    fn submit(&mut self, data: &Editor) -> Result<()> {
+        if self.retain_on_submit {
+            if self.draw_state.altscreen {
+                // The main screen comes back with the cursor at the base
+                // of the frame that was drawn before entering fullscreen.
+                self.write.set_altscreen(false)?;
+                self.draw_state = DrawState::default();
+            }
+            let lines: Vec<_> = (0..data.line_count())
+                .map(|ln| data.shown_line(ln))
+                .collect();
+            self.print(&lines.join("\n"))?;
+        }
+        Ok(())
+    }
+
+    fn bell(&mut self) -> Result<()> {
+        self.write.write_all(b"\x07")?;
+        Ok(())
+    }
+
+    fn suspend(&mut self) -> Result<()> {
+        if self.write.supports_suspend() {
+            self.clear_draw()?;
+            self.write.suspend()?;
+        }
+        Ok(())
+    }
+
+    fn hand_over(&mut self, run: &mut dyn FnMut()) -> Result<()> {
+        self.clear_draw()?;
+        self.write.hand_over(run)
+    }
+
+    fn page_size(&self) -> Option<usize> {
+        Some(self.draw_state.high - self.draw_state.low).filter(|&lines| lines > 0)
+    }
+
+    fn first_line(&self) -> Option<usize> {
+        Some(self.draw_state.low).filter(|_| self.draw_state.ledger.height() > 0)
+    }
+
+    fn finish(mut self) -> Result<()> {
+        self.clear_draw()?;
+        // Dropping the guard leaves the alternate screen
+        // and restores the rest of the terminal state.
         self.flush()
     }
 }
 
-impl<'w, W> DefaultRenderer<'w, W> {
+impl<'w, W: Write> DefaultRenderer<CrosstermBackend<&'w mut W>> {
+    /// Render to any output, using `crossterm` for the rest of the terminal.
     pub fn render_to(write: &'w mut W) -> Self {
+        CrosstermRenderer::render_with(CrosstermBackend::new(write))
+    }
+}
+
+impl<B: Backend> DefaultRenderer<B> {
+    /// Render to a backend, such as an
+    /// [`AnsiBackend`](crate::backend::AnsiBackend) over a pseudo-terminal.
+    ///
+    /// The terminal is left as it is until the first draw or read, which
+    /// enables raw mode and fails if it cannot be enabled.
+    pub fn render_with(backend: B) -> Self {
         CrosstermRenderer {
-            guard: RawModeGuard::acquire().unwrap(),
-            write,
+            write: TerminalGuard::new(backend),
             draw_state: DrawState::default(),
             margin: NoStyle,
             header: NoStyle,
             footer: NoStyle,
             max_height: None,
+            highlighter: None,
+            decorations: None,
+            observer: None,
+            completion_menu: 5,
+            match_brackets: false,
+            banner: Vec::new(),
+            placeholder: None,
+            rulers: Vec::new(),
+            ruler_style: RulerStyle::Marker,
+            diagnostic_messages: false,
+            synchronized_output: None,
+            theme: Theme::default(),
+            highlight_cursor_line: false,
+            mouse: false,
+            cursor_style: None,
+            preview: None,
+            preview_rows: 0,
+            preview_lines: None,
+            anchor: AnchorMode::Inline,
+            retain_on_submit: false,
+            bottom_rows: None,
         }
     }
 }
 
-impl<'w, W, M, H, F> CrosstermRenderer<'w, W, M, H, F> {
+impl<B: Backend, M, H, F> CrosstermRenderer<B, M, H, F> {
     pub fn max_height(self, max_height: Option<usize>) -> Self {
         Self { max_height, ..self }
     }
+
+    /// Set the maximum number of completion candidates listed below
+    /// the editor, with `0` hiding the list. Defaults to `5`.
+    pub fn completion_menu(self, completion_menu: usize) -> Self {
+        Self {
+            completion_menu,
+            ..self
+        }
+    }
+
+    /// Show lines of plain text above the header, such as help on
+    /// how to submit. Lines are cut off at the edge of the terminal.
+    pub fn banner(self, banner: &str) -> Self {
+        Self {
+            banner: banner.lines().map(String::from).collect(),
+            ..self
+        }
+    }
+
+    /// Show dimmed text in place of an empty buffer, such as
+    /// how to submit, which goes away as soon as anything is typed.
+    pub fn placeholder(self, placeholder: &str) -> Self {
+        Self {
+            placeholder: Some(placeholder.to_string()),
+            ..self
+        }
+    }
+
+    /// Show rulers at columns that lines should stay within, such as 72
+    /// and 80 for commit messages, counted in terminal columns from the
+    /// start of the line, where wide characters take up two columns and
+    /// tabs go up to the next tab stop. The markers and the overflowing
+    /// text are drawn in the ruler and overflow styles of the theme.
+    pub fn ruler(self, columns: &[usize], ruler_style: RulerStyle) -> Self {
+        let mut rulers = columns.to_vec();
+        rulers.sort_unstable();
+        rulers.dedup();
+        Self {
+            rulers,
+            ruler_style,
+            ..self
+        }
+    }
+
+    /// Show the message of the most severe diagnostic of each line
+    /// after the line, in the color of its severity.
+    pub fn diagnostic_messages(self, diagnostic_messages: bool) -> Self {
+        Self {
+            diagnostic_messages,
+            ..self
+        }
+    }
+
+    /// Draw each frame as a synchronized update, which the terminal shows
+    /// all at once so that redrawing many lines does not tear. Defaults to
+    /// doing so where the backend supports it.
+    pub fn synchronized_output(self, synchronized_output: bool) -> Self {
+        Self {
+            synchronized_output: Some(synchronized_output),
+            ..self
+        }
+    }
+
+    /// Draw the built-in components in the styles of a theme, which is
+    /// also given to the margin and the footer before each frame.
+    pub fn theme(self, theme: Theme) -> Self {
+        Self { theme, ..self }
+    }
+
+    /// Draw the line that the cursor is on in the current line style
+    /// of the theme, which is a subtle background by default.
+    pub fn highlight_cursor_line(self, highlight_cursor_line: bool) -> Self {
+        Self {
+            highlight_cursor_line,
+            ..self
+        }
+    }
+
+    /// Underline the bracket at or before the cursor along with
+    /// the bracket that it pairs with, which is not done for lines
+    /// styled by a highlighter.
+    pub fn match_brackets(self, match_brackets: bool) -> Self {
+        Self {
+            match_brackets,
+            ..self
+        }
+    }
+
+    /// Decide where the editor is drawn on the terminal.
+    pub fn anchor(self, anchor: AnchorMode) -> Self {
+        Self { anchor, ..self }
+    }
+
+    /// Leave the submitted text on the terminal where the editor was,
+    /// like a shell leaves the commands that were entered, instead of
+    /// clearing it along with the rest of the editor.
+    pub fn retain_on_submit(self, retain_on_submit: bool) -> Self {
+        Self {
+            retain_on_submit,
+            ..self
+        }
+    }
+
+    /// Report mouse events, so that clicking moves the cursor,
+    /// dragging selects and the wheel scrolls through the lines.
+    ///
+    /// This keeps the terminal from selecting text by itself,
+    /// which is usually still done with `Shift` held down.
+    pub fn mouse(self, mouse: bool) -> Self {
+        Self { mouse, ..self }
+    }
+
+    /// Set the shape of the cursor while the editor is drawn, which is
+    /// given back to the terminal afterwards. Defaults to leaving the
+    /// cursor as the terminal draws it.
+    pub fn cursor_style(self, cursor_style: CursorStyle) -> Self {
+        Self {
+            cursor_style: Some(cursor_style),
+            ..self
+        }
+    }
+
+    /// Show up to `max_rows` lines of text below the editor, produced by
+    /// a closure from the editor whenever the buffer changes, such as
+    /// a rendered version of the input. Lines are cut off at the edge
+    /// of the terminal.
+    pub fn preview(self, max_rows: usize, preview: impl Fn(&Editor) -> String + 'static) -> Self {
+        Self {
+            preview: Some(Box::new(preview)),
+            preview_rows: max_rows,
+            preview_lines: None,
+            ..self
+        }
+    }
+
+    /// Style each line with a closure before drawing it.
+    ///
+    /// The cursor is still positioned using the unstyled contents,
+    /// so the closure is free to insert escape sequences. Lines that
+    /// contain part of the selection are drawn without highlighting.
+    pub fn highlighter(
+        self,
+        highlighter: impl Fn(usize, &str, &Editor) -> String + 'static,
+    ) -> Self {
+        Self {
+            highlighter: Some(Box::new(highlighter)),
+            ..self
+        }
+    }
+
+    /// Draw the ranges that a provider marks on each line in their
+    /// styles, over the highlighter or the selection, such as to
+    /// underline misspelled words.
+    pub fn decorations(self, decorations: impl DecorationProvider + 'static) -> Self {
+        Self {
+            decorations: Some(Box::new(decorations)),
+            ..self
+        }
+    }
+
+    /// Report what each draw decided to draw to a closure, along with
+    /// the bytes that it wrote if the backend counts them.
+    pub fn observer(self, observer: impl FnMut(&RenderReport) + 'static) -> Self {
+        Self {
+            observer: Some(Box::new(observer)),
+            ..self
+        }
+    }
 }
 
 // region: Swap constructors
-impl<'w, W, M1, H, F> CrosstermRenderer<'w, W, M1, H, F> {
+impl<B: Backend, M1, H, F> CrosstermRenderer<B, M1, H, F> {
     /// Swap out a margin formatter.
-    pub fn margin<M2>(self, margin: M2) -> CrosstermRenderer<'w, W, M2, H, F> {
+    pub fn margin<M2>(self, margin: M2) -> CrosstermRenderer<B, M2, H, F> {
         CrosstermRenderer {
-            guard: self.guard,
             write: self.write,
             draw_state: self.draw_state,
             margin,
             header: self.header,
             footer: self.footer,
             max_height: self.max_height,
+            highlighter: self.highlighter,
+            decorations: self.decorations,
+            observer: self.observer,
+            completion_menu: self.completion_menu,
+            match_brackets: self.match_brackets,
+            banner: self.banner,
+            placeholder: self.placeholder,
+            rulers: self.rulers,
+            ruler_style: self.ruler_style,
+            diagnostic_messages: self.diagnostic_messages,
+            synchronized_output: self.synchronized_output,
+            theme: self.theme,
+            highlight_cursor_line: self.highlight_cursor_line,
+            mouse: self.mouse,
+            cursor_style: self.cursor_style,
+            preview: self.preview,
+            preview_rows: self.preview_rows,
+            preview_lines: self.preview_lines,
+            anchor: self.anchor,
+            retain_on_submit: self.retain_on_submit,
+            bottom_rows: self.bottom_rows,
         }
     }
 }
 
-impl<'w, W, M, H1, F> CrosstermRenderer<'w, W, M, H1, F> {
+impl<B: Backend, M, H1, F> CrosstermRenderer<B, M, H1, F> {
     /// Swap out a header formatter.
-    pub fn header<H2>(self, header: H2) -> CrosstermRenderer<'w, W, M, H2, F> {
+    pub fn header<H2>(self, header: H2) -> CrosstermRenderer<B, M, H2, F> {
         CrosstermRenderer {
-            guard: self.guard,
             write: self.write,
             draw_state: self.draw_state,
             margin: self.margin,
             header,
             footer: self.footer,
             max_height: self.max_height,
+            highlighter: self.highlighter,
+            decorations: self.decorations,
+            observer: self.observer,
+            completion_menu: self.completion_menu,
+            match_brackets: self.match_brackets,
+            banner: self.banner,
+            placeholder: self.placeholder,
+            rulers: self.rulers,
+            ruler_style: self.ruler_style,
+            diagnostic_messages: self.diagnostic_messages,
+            synchronized_output: self.synchronized_output,
+            theme: self.theme,
+            highlight_cursor_line: self.highlight_cursor_line,
+            mouse: self.mouse,
+            cursor_style: self.cursor_style,
+            preview: self.preview,
+            preview_rows: self.preview_rows,
+            preview_lines: self.preview_lines,
+            anchor: self.anchor,
+            retain_on_submit: self.retain_on_submit,
+            bottom_rows: self.bottom_rows,
         }
     }
 }
 
-impl<'w, W, M, H, F1> CrosstermRenderer<'w, W, M, H, F1> {
+impl<B: Backend, M, H, F1> CrosstermRenderer<B, M, H, F1> {
     /// Swap out a footer formatter.
-    pub fn footer<F2>(self, footer: F2) -> CrosstermRenderer<'w, W, M, H, F2> {
+    pub fn footer<F2>(self, footer: F2) -> CrosstermRenderer<B, M, H, F2> {
         CrosstermRenderer {
-            guard: self.guard,
             write: self.write,
             draw_state: self.draw_state,
             margin: self.margin,
             header: self.header,
             footer,
             max_height: self.max_height,
+            highlighter: self.highlighter,
+            decorations: self.decorations,
+            observer: self.observer,
+            completion_menu: self.completion_menu,
+            match_brackets: self.match_brackets,
+            banner: self.banner,
+            placeholder: self.placeholder,
+            rulers: self.rulers,
+            ruler_style: self.ruler_style,
+            diagnostic_messages: self.diagnostic_messages,
+            synchronized_output: self.synchronized_output,
+            theme: self.theme,
+            highlight_cursor_line: self.highlight_cursor_line,
+            mouse: self.mouse,
+            cursor_style: self.cursor_style,
+            preview: self.preview,
+            preview_rows: self.preview_rows,
+            preview_lines: self.preview_lines,
+            anchor: self.anchor,
+            retain_on_submit: self.retain_on_submit,
+            bottom_rows: self.bottom_rows,
         }
     }
 }
 
-impl<'w, W, M, H, F> CrosstermRenderer<'w, W, M, H, F>
+impl<B, M, H, F> CrosstermRenderer<B, M, H, F>
 where
-    W: Write,
-    M: Margin<W>,
-    H: Header<W>,
-    F: Footer<W>,
+    B: Backend,
+    M: Margin<B>,
+    H: Header<B>,
+    F: Footer<B>,
 {
-    fn calculate_draw_range(&self, data: &Editor) -> (usize, usize, usize) {
-        if let Ok((_, rows)) = crossterm::terminal::size() {
+    /// Draw a frame of the prompt, returning what was drawn.
+    fn draw_frame(&mut self, data: &Editor) -> Result<RenderDecision> {
+        if self.draw_state.altscreen {
+            self.write.queue(MoveTo(0, 0))?;
+        } else {
+            self.move_to_frame_base()?;
+        }
+
+        self.margin.set_theme(&self.theme);
+        self.footer.set_theme(&self.theme);
+        self.margin.update(data);
+        self.update_preview(data);
+        let (low, high, term_rows, width) = self.calculate_draw_range(data);
+
+        if term_rows == 0 {
+            return Ok(RenderDecision::NoChange);
+        }
+
+        let fullscreen = self.fullscreen(data);
+        self.write.set_altscreen(fullscreen)?;
+        if fullscreen && !self.draw_state.altscreen {
+            // The alternate screen starts out with the cursor where it was.
+            self.write.queue(MoveTo(0, 0))?;
+        }
+        self.write.set_mouse(self.mouse)?;
+        self.write.set_bracketed_paste(true)?;
+        self.write.set_cursor_style(self.cursor_style)?;
+
+        // Brackets would show through the mask.
+        let brackets = match data.bracket_match() {
+            Some((a, b)) if self.match_brackets && data.mask.is_none() => {
+                vec![a.min(b), a.max(b)]
+            }
+            _ => Vec::new(),
+        };
+        if let Some(lines) = self.redrawn_lines(data, low, high, width, &brackets) {
+            let redrawn: Vec<usize> = lines.iter().map(|&(ln, _)| ln).collect();
+            self.redraw_lines(data, lines, brackets)?;
+            return Ok(match redrawn.is_empty() {
+                true => RenderDecision::RedrawCursor,
+                false => RenderDecision::RedrawLines(redrawn),
+            });
+        }
+
+        self.draw_state = DrawState::default();
+        self.draw_state.altscreen = fullscreen;
+        self.draw_state.width = width;
+        self.draw_state.columns = self.write.size().map_or(0, |(cols, _)| cols.into());
+        self.draw_state.brackets = brackets;
+        self.draw_state.line_count = data.line_count();
+        self.draw_state.focus_ln = data.selection.focus.ln;
+        self.draw_state.selection = data.selection.range();
+        self.draw_state.cursors = !data.cursors().is_empty();
+
+        if self.anchor == AnchorMode::Bottom && !fullscreen {
+            let height = self.frame_height(data, low, high, width);
+            self.make_room(height)?;
+        }
+        self.draw_banner()?;
+        self.draw_header(data)?;
+        self.draw_range(data, low, high, term_rows)?;
+        self.draw_below(data)?;
+        Ok(RenderDecision::RedrawAll)
+    }
+
+    /// Calculate the range of lines to draw, the number of rows
+    /// available to them and the columns available to the text.
+    fn calculate_draw_range(&self, data: &Editor) -> (usize, usize, usize, usize) {
+        if let Ok((cols, rows)) = self.write.size() {
             // Rows of the terminal.
-            let max_height = if !data.altscreen {
+            let max_height = if !self.fullscreen(data) {
                 self.max_height
             } else {
                 None
             };
             let term_rows = max_height
                 .unwrap_or(usize::MAX)
-                .min(rows.try_into().unwrap())
+                .min(rows.into())
+                .saturating_sub(self.banner.len())
                 .saturating_sub(self.header.rows())
-                .saturating_sub(self.footer.rows());
+                .saturating_sub(self.footer.rows(data))
+                .saturating_sub(self.overlay_rows(data));
+            let width = usize::from(cols).saturating_sub(self.margin.width()).max(1);
             if term_rows == 0 {
-                return (0, 0, 0);
+                return (0, 0, 0, width);
             }
+            let rows_of = |i: usize| Self::line_rows(data, i, width);
             // Rows of the data to draw.
             let data_rows = data.line_count();
             // Current line of the data.
             let line = data.selection.focus.ln;
-            if data_rows > term_rows {
-                let (low, high) = if line >= self.draw_state.high {
-                    (line - term_rows + 1, line + 1)
-                } else if line < self.draw_state.low {
-                    (line, line + term_rows)
-                } else {
-                    (self.draw_state.low, self.draw_state.high)
-                };
-                (low, high.min(data_rows), term_rows)
+            // Stops measuring once the rows run out, so that long buffers
+            // cost only as much as the lines that are shown.
+            let fits = |low: usize, high: usize| {
+                (low..high)
+                    .try_fold(0, |sum, i| {
+                        Some(sum + rows_of(i)).filter(|&sum| sum <= term_rows)
+                    })
+                    .is_some()
+            };
+
+            // Without a frame to keep on scrolling from, such as after a
+            // message was printed, the scroll of the editor is kept instead.
+            let (shown_low, shown_high) = match self.draw_state.ledger.height() {
+                0 => (data.scroll(), usize::MAX),
+                _ => (self.draw_state.low, self.draw_state.high),
+            };
+            let low = if fits(0, data_rows) {
+                0
+            } else if line >= shown_high || !fits(shown_low, line + 1) {
+                // Scroll down until the current line fits at the bottom.
+                let mut low = line;
+                while low > 0 && fits(low - 1, line + 1) {
+                    low -= 1;
+                }
+                low
+            } else if line < shown_low {
+                line
             } else {
-                (0, data.line_count(), term_rows)
+                shown_low
+            };
+
+            // Fill the rows with as many lines as possible,
+            // but always draw the current line.
+            let mut high = low + 1;
+            while high < data_rows && fits(low, high + 1) {
+                high += 1;
             }
+            (low, high.max(line + 1).min(data_rows), term_rows, width)
+        } else {
+            (0, data.line_count(), 0, 1)
+        }
+    }
+
+    /// Number of rows that a line takes when wrapped to `width` columns.
+    fn line_rows(data: &Editor, line: usize, width: usize) -> usize {
+        if line < data.line_count() {
+            let text = data.shown_line(line);
+            let end = Position::in_line(line, &text, LineIndex::Char(usize::MAX), data.tab_width);
+            end.wrapped(&text, width, data.tab_width).0 + 1
         } else {
-            (0, data.line_count(), 0)
+            1
         }
     }
 
+    /// Whether the editor is drawn on the alternate screen.
+    fn fullscreen(&self, data: &Editor) -> bool {
+        data.altscreen || self.anchor == AnchorMode::Fullscreen
+    }
+
+    /// Number of rows that a frame drawing the lines from `low` to `high`
+    /// takes up, outside of the fullscreen mode.
+    fn frame_height(&self, data: &Editor, low: usize, high: usize, width: usize) -> usize {
+        self.banner.len()
+            + self.header.rows()
+            + (low..high)
+                .map(|i| Self::line_rows(data, i, width))
+                .sum::<usize>()
+            + self.footer.rows(data)
+            + self.overlay_rows(data)
+    }
+
+    /// Move to where a frame of `height` rows starts at the bottom of the
+    /// terminal, scrolling the rows above up to make room for it.
+    fn make_room(&mut self, height: usize) -> Result<()> {
+        let rows = usize::from(self.write.size()?.1).max(1);
+        // Everything from the cursor down is overwritten like it is inline.
+        let owned = match self.bottom_rows {
+            Some(owned) => owned.min(rows),
+            None => rows - usize::from(self.write.cursor_position()?.1).min(rows - 1),
+        };
+        let height = height.min(rows);
+
+        self.write
+            .queue(MoveTo(0, Self::usize_to_u16(rows - owned)))?;
+        self.write.queue(Clear(ClearType::FromCursorDown))?;
+        if height > owned {
+            // Each new-line on the last row scrolls everything up by a row.
+            self.write.queue(MoveTo(0, Self::usize_to_u16(rows - 1)))?;
+            for _ in owned..height {
+                self.write.write_all(b"\n")?;
+            }
+        }
+        self.bottom_rows = Some(owned.max(height));
+        self.write
+            .queue(MoveTo(0, Self::usize_to_u16(rows - height)))?;
+        Ok(())
+    }
+
+    /// Draw everything below the lines, and position the cursor.
+    fn draw_below(&mut self, data: &Editor) -> Result<()> {
+        self.draw_footer(data)?;
+        self.draw_completions(data)?;
+        self.draw_search(data)?;
+        self.draw_validation_error(data)?;
+        self.draw_confirmation(data)?;
+        self.draw_preview()?;
+        self.write.queue(Clear(ClearType::FromCursorDown))?;
+
+        self.draw_cursor(data)?;
+        self.flush()
+    }
+
+    /// Decide which lines of the last frame have to be drawn again,
+    /// or `None` if the whole frame has to be drawn.
+    fn redrawn_lines(
+        &self,
+        data: &Editor,
+        low: usize,
+        high: usize,
+        width: usize,
+        brackets: &[Cursor],
+    ) -> Option<Vec<(usize, usize)>> {
+        let state = &self.draw_state;
+        let same_frame = state.ledger.height() > 0
+            && !self.fullscreen(data)
+            && !state.altscreen
+            && !state.cursors
+            && data.cursors().is_empty()
+            && (state.low, state.high, state.width) == (low, high, width)
+            && state.line_count == data.line_count()
+            && state.columns == self.write.size().map_or(0, |(cols, _)| cols.into())
+            // A frame at the bottom has to move if its height changes.
+            && (self.anchor == AnchorMode::Inline
+                || self.frame_height(data, low, high, width) == state.ledger.height());
+        if !same_frame {
+            return None;
+        }
+
+        // An edit to a single line only has to be drawn from where it starts,
+        // as long as nothing else about the line changed.
+        let dirty = data.dirty_lines();
+        let focus = data.selection.focus.ln;
+        let mut lines: Vec<(usize, usize)> = match dirty.clone() {
+            Some(dirty) if dirty.len() == 1 && state.focus_ln == focus => {
+                vec![(dirty.start, data.dirty_col())]
+            }
+            dirty => dirty
+                .into_iter()
+                .flatten()
+                .take(high)
+                .map(|ln| (ln, 0))
+                .collect(),
+        };
+
+        // Lines that look different without their contents changing,
+        // where the hint only follows the contents of the cursor line.
+        let mut restyled = Vec::new();
+        if state.focus_ln != focus || lines.iter().all(|&(ln, _)| ln != focus) {
+            restyled.extend(&[state.focus_ln, focus]);
+        }
+        for (start, end) in state.selection.iter().chain(&data.selection.range()) {
+            restyled.extend(start.ln..=end.ln);
+        }
+        restyled.extend(
+            state
+                .brackets
+                .iter()
+                .chain(brackets)
+                .map(|cursor| cursor.ln),
+        );
+        lines.extend(restyled.into_iter().map(|ln| (ln, 0)));
+
+        // Highlighted lines are drawn whole, along with the lines that the
+        // highlighter styles differently without their contents changing,
+        // such as after a string is opened on a line before them. The same
+        // goes for decorated lines.
+        if self.highlighter.is_some() || self.decorations.is_some() {
+            for (_, col) in &mut lines {
+                *col = 0;
+            }
+            for ln in low..high.min(data.line_count()) {
+                let content = self.styled_line(data, ln, brackets).ok()?;
+                if state.styles.get(ln - low) != Some(&hash_of(&content)) {
+                    lines.push((ln, 0));
+                }
+            }
+        }
+
+        lines.retain(|&(ln, _)| low <= ln && ln < high);
+        lines.sort_unstable();
+        lines.dedup_by_key(|&mut (ln, _)| ln);
+
+        // Lines that wrap differently move the rows after them.
+        let rows_kept = lines
+            .iter()
+            .all(|&(ln, _)| Self::line_rows(data, ln, width) == state.line_rows[ln - low]);
+        Some(lines).filter(|_| rows_kept)
+    }
+
+    /// Draw some of the lines of the last frame again, along with
+    /// everything below the lines, which is cheaper than drawing
+    /// the whole frame for small edits to long inputs.
+    fn redraw_lines(
+        &mut self,
+        data: &Editor,
+        lines: Vec<(usize, usize)>,
+        brackets: Vec<Cursor>,
+    ) -> Result<()> {
+        let top = self.draw_state.anchor.ln;
+        let low = self.draw_state.low;
+        let row_of =
+            |state: &DrawState, ln: usize| state.line_rows[..ln - low].iter().sum::<usize>();
+        self.draw_state.brackets = brackets;
+
+        // The frame base is the first row of the banner.
+        let mut row = 0;
+        for (ln, col) in lines {
+            let start = row_of(&self.draw_state, ln);
+            if top + start > row {
+                self.write
+                    .queue(MoveDown(Self::usize_to_u16(top + start - row)))?;
+            }
+            let before = self.draw_state.row_widths.len();
+            let (skipped, rows) = match col {
+                0 => (0, self.draw_line(data, ln)?),
+                col => self.draw_line_from(data, ln, col)?,
+            };
+            let widths = self.draw_state.row_widths.split_off(before);
+            let first = start + skipped;
+            self.draw_state
+                .row_widths
+                .splice(first..first + rows, widths);
+            row = top + first + rows - 1;
+        }
+
+        // What comes below is drawn from the end of the last row,
+        // like after drawing the whole frame.
+        let text_rows: usize = self.draw_state.line_rows.iter().sum();
+        if top + text_rows - 1 > row {
+            self.write
+                .queue(MoveDown(Self::usize_to_u16(top + text_rows - 1 - row)))?;
+            let end = self.draw_state.row_widths.last().copied().unwrap_or(0);
+            self.move_cursor_to_column(end)?;
+        }
+        self.draw_state.ledger.truncate(FramePart::Footer);
+        self.draw_state.focus_ln = data.selection.focus.ln;
+        self.draw_state.selection = data.selection.range();
+        self.draw_state.cursors = !data.cursors().is_empty();
+        self.draw_below(data)
+    }
+
     // Move to the base of the frame (not the anchor).
     fn move_to_frame_base(&mut self) -> Result<()> {
-        let up_offset = self.draw_state.anchor.ln + self.draw_state.cursor.ln;
-        self.write.queue(MoveUp(Self::usize_to_u16(up_offset)))?;
+        let mut up_offset = self.draw_state.anchor.ln + self.draw_state.cursor.ln;
+
+        // The terminal rewraps the rows of the frame if it was resized.
+        let columns = self.draw_state.columns;
+        if let Ok((cols, _)) = self.write.size() {
+            let cols = usize::from(cols).max(1);
+            if columns != 0 && cols != columns {
+                let rows_of = |width: usize| width.div_ceil(cols).max(1);
+                let cursor_ln = self.draw_state.cursor.ln;
+                let banner = &self.draw_state.banner_widths;
+                up_offset = self.draw_state.anchor.ln - banner.len()
+                    + banner.iter().map(|&width| rows_of(width)).sum::<usize>()
+                    + self.draw_state.row_widths[..cursor_ln.min(self.draw_state.row_widths.len())]
+                        .iter()
+                        .map(|&width| rows_of(width))
+                        .sum::<usize>()
+                    + self.draw_state.cursor.col / cols;
+            }
+        }
+
+        if up_offset > 0 {
+            self.write.queue(MoveUp(Self::usize_to_u16(up_offset)))?;
+        }
         self.write.queue(MoveToColumn(0))?;
         Ok(())
     }
@@ -252,60 +1374,507 @@ where
     // Position the cursor right after drawing a frame
     // (assuming no other cursor adjustments made).
     fn draw_cursor(&mut self, data: &Editor) -> Result<()> {
-        // Move to the correct row.
+        // Move to the correct row, accounting for the wrapped lines.
         let line = data.selection.focus.ln;
-        let frame_height = self.draw_state.height;
-        let relative_ln = line - self.draw_state.low;
+        let width = self.draw_state.width;
+        let text = data.shown_line(line);
+        let focus = Position::in_line(
+            line,
+            &text,
+            LineIndex::Char(data.selection.focus.col),
+            data.tab_width,
+        );
+        let (wrap_row, x) = focus.wrapped(&text, width, data.tab_width);
+        let frame_height = self.draw_state.ledger.height();
+        let relative_ln = (self.draw_state.low..line)
+            .map(|i| Self::line_rows(data, i, width))
+            .sum::<usize>()
+            + wrap_row;
         let up_offset = frame_height - 1 - self.draw_state.anchor.ln - relative_ln;
-        // self.move_cursor_up(up_offset)?;
-        self.write.queue(MoveUp(Self::usize_to_u16(up_offset)))?;
+        if up_offset > 0 {
+            self.write.queue(MoveUp(Self::usize_to_u16(up_offset)))?;
+        }
 
-        // Move to the correct column.
-        let col = data.selection.focus.col.min(data.curr_ln_len());
-        let n = self.draw_state.anchor.col + col + 1;
-        self.write.queue(MoveToColumn(Self::usize_to_u16(n)))?;
+        // Move to the correct column, which is after the margin.
+        let col = self.draw_state.anchor.col + x;
+        self.move_cursor_to_column(col)?;
 
         self.draw_state.cursor.ln = relative_ln;
-        self.draw_state.cursor.col = n;
+        self.draw_state.cursor.col = col;
 
         Ok(())
     }
 
+    fn draw_banner(&mut self) -> Result<()> {
+        let columns = match self.draw_state.columns {
+            0 => usize::MAX,
+            columns => columns,
+        };
+        for line in &self.banner {
+            self.write.queue(MoveToColumn(0))?;
+            let width = Self::write_truncated(&mut *self.write, line, columns)?;
+            self.write.queue(Clear(ClearType::UntilNewLine))?;
+            self.write.write_all(b"\n")?;
+            self.draw_state.banner_widths.push(width);
+        }
+        self.draw_state
+            .ledger
+            .register(FramePart::Banner, self.banner.len());
+        self.draw_state.anchor.ln += self.banner.len();
+        Ok(())
+    }
+
+    /// Write as much of a line of plain text as fits in the columns,
+    /// returning how many columns it took up.
+    fn write_truncated(write: &mut impl Write, line: &str, columns: usize) -> Result<usize> {
+        let mut width = 0;
+        // Stop short of the last column so that the row is not wrapped.
+        for c in line.chars() {
+            if width + char_width(c) >= columns {
+                break;
+            }
+            width += char_width(c);
+            write!(write, "{}", c)?;
+        }
+        Ok(width)
+    }
+
     fn draw_header(&mut self, data: &Editor) -> Result<()> {
-        self.draw_state.height += self.header.rows();
+        self.draw_state
+            .ledger
+            .register(FramePart::Header, self.header.rows());
         self.draw_state.anchor.ln += self.header.rows();
 
         self.cursor_to_left_term_edge()?;
-        self.header.draw(self.write, data)?;
+        self.header.draw(&mut self.write, data)?;
         if self.header.rows() > 0 {
-            self.write.write(b"\n")?;
+            self.write.write_all(b"\n")?;
         }
         Ok(())
     }
 
-    /// Draw the line given an index.
-    /// This method does not move the cursor.
-    fn draw_line(&mut self, data: &Editor, line: usize) -> Result<()> {
+    /// Draw the line given an index, wrapping it into as many rows as needed.
+    /// This method does not move the cursor, and returns the number of rows.
+    fn draw_line(&mut self, data: &Editor, line: usize) -> Result<usize> {
         self.cursor_to_left_term_edge()?;
 
-        self.margin.draw(self.write, line, data)?;
-        if line < data.line_count() {
-            data.write_line(line, self.write)?;
+        self.margin.draw(&mut self.write, line, data)?;
+        if line >= data.line_count() {
+            self.write.queue(Clear(ClearType::UntilNewLine))?;
+            self.draw_state.row_widths.push(self.margin.width());
+            return Ok(1);
         }
-        self.write.queue(Clear(ClearType::UntilNewLine))?;
 
-        Ok(())
+        let content = self.styled_line(data, line, &self.draw_state.brackets)?;
+        if self.highlighter.is_some() || self.decorations.is_some() {
+            if let Some(i) = line.checked_sub(self.draw_state.low) {
+                let styles = &mut self.draw_state.styles;
+                if styles.len() <= i {
+                    styles.resize(i + 1, 0);
+                }
+                styles[i] = hash_of(&content);
+            }
+        }
+        self.draw_content(data, line, &String::from_utf8_lossy(&content), 0, 0)
+    }
+
+    /// Write the contents of a line with the highlighter, or with the
+    /// selection and the marks of the brackets and search matches.
+    fn styled_line(&self, data: &Editor, line: usize, brackets: &[Cursor]) -> Result<Vec<u8>> {
+        let mut content = Vec::new();
+        match &self.highlighter {
+            // Search matches and other cursors are only shown on plain lines,
+            // while diagnostics are underlined over the highlighter.
+            Some(highlight)
+                if !data.selection.spans_line(line)
+                    && data.buffer_search().is_none()
+                    && !data
+                        .cursors()
+                        .iter()
+                        .any(|cursor| cursor.focus.ln == line || cursor.spans_line(line)) =>
+            {
+                let styled = highlight(line, &data.shown_line(line), data);
+                let styled = overlay(&styled, &diagnostic_decorations(data, line));
+                content.extend_from_slice(styled.as_bytes());
+            }
+            _ => {
+                let mut marks: Vec<(usize, usize)> = brackets
+                    .iter()
+                    .filter(|cursor| cursor.ln == line)
+                    .map(|cursor| (cursor.col, cursor.col + 1))
+                    .chain(data.search_matches(line))
+                    .collect();
+                marks.sort_unstable();
+                data.write_marked_line(line, &marks, &self.theme, &mut content)?
+            }
+        }
+        if let Some(decorations) = &self.decorations {
+            let decorations = decorations.decorate(line, &data.shown_line(line), data);
+            let decorated = overlay(&String::from_utf8_lossy(&content), &decorations);
+            content = decorated.into_bytes();
+        }
+        Ok(content)
+    }
+
+    /// Draw the line given an index again from a column, which has to be
+    /// drawn on the same rows as before. Returns the row of the line that
+    /// the column is on and the number of rows from there.
+    fn draw_line_from(&mut self, data: &Editor, line: usize, col: usize) -> Result<(usize, usize)> {
+        let text = data.shown_line(line);
+        let col = col.min(text.chars().count());
+        // Combining characters cannot be drawn on their own.
+        let col = prev_grapheme(&text, col + 1);
+        let position = Position::in_line(line, &text, LineIndex::Char(col), data.tab_width);
+        let (row, x) = position.wrapped(&text, self.draw_state.width, data.tab_width);
+        if row > 0 {
+            self.write.queue(MoveDown(Self::usize_to_u16(row)))?;
+        }
+        self.move_cursor_to_column(self.margin.width() + x)?;
+        let tail: String = text.chars().skip(col).collect();
+        let rows = self.draw_content(data, line, &tail, col, x)?;
+        Ok((row, rows))
+    }
+
+    /// Draw the content of a line from the character at `start` starting
+    /// at a column of the row that the cursor is on, followed by the hint
+    /// of the cursor line. Returns the number of rows that were drawn.
+    fn draw_content(
+        &mut self,
+        data: &Editor,
+        line: usize,
+        content: &str,
+        start: usize,
+        mut col: usize,
+    ) -> Result<usize> {
+        // Break the content into rows of visible characters,
+        // copying escape sequences through without counting them.
+        let width = self.draw_state.width;
+        // The style of a highlighted cursor line is started again after
+        // anything that resets it, up to the end of the row.
+        let background = match self.highlight_cursor_line && line == data.selection.focus.ln {
+            true => self.theme.current_line,
+            false => ContentStyle::new(),
+        };
+        queue_style(&mut *self.write, background)?;
+        // Characters past the first ruler are drawn in the overflow style,
+        // over whatever the highlighter styled them in.
+        let overflow_at = match self.ruler_style {
+            RulerStyle::Overflow => self.rulers.first().copied(),
+            RulerStyle::Marker => None,
+        };
+        let overflow = merge(background, self.theme.overflow);
+        // Terminal column of the line that the rulers are measured in,
+        // which does not start over on each row.
+        let text = data.shown_line(line);
+        let mut column =
+            Position::in_line(line, &text, LineIndex::Char(start), data.tab_width).column;
+        let mut overflowed = false;
+        let mut chars = content.chars().peekable();
+        let mut rows = 1;
+        while let Some(c) = chars.next() {
+            let next_column = column + cell_width(c, column, data.tab_width);
+            let overflowing = overflow_at.is_some_and(|at| next_column > at);
+            if c == '\x1b' {
+                let mut seq = String::from(c);
+                if chars.peek() == Some(&'[') {
+                    seq.extend(chars.next());
+                    for c in chars.by_ref() {
+                        seq.push(c);
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // Legacy Windows consoles would print them as text.
+                if self.write.supports_ansi() {
+                    self.write.write_all(seq.as_bytes())?;
+                    if seq == "\x1b[0m" || seq == "\x1b[m" {
+                        queue_style(&mut *self.write, background)?;
+                    }
+                    if overflowing {
+                        queue_style(&mut *self.write, overflow)?;
+                    }
+                }
+                continue;
+            }
+            if overflowing && !overflowed {
+                queue_style(&mut *self.write, overflow)?;
+                overflowed = true;
+            }
+            if col > 0 && col + cell_width(c, col, data.tab_width) > width {
+                // A full row has nothing left to clear.
+                if col < width {
+                    self.write.queue(Clear(ClearType::UntilNewLine))?;
+                }
+                self.write.write_all(b"\n")?;
+                self.draw_state.row_widths.push(self.margin.width() + col);
+                self.cursor_to_left_term_edge()?;
+                self.margin.draw_continuation(&mut self.write, line, data)?;
+                queue_style(&mut *self.write, background)?;
+                if overflowing {
+                    queue_style(&mut *self.write, overflow)?;
+                }
+                rows += 1;
+                col = 0;
+            }
+            let w = cell_width(c, col, data.tab_width);
+            // Tabs are drawn as spaces, since the terminal has its own tab stops.
+            match c {
+                '\t' => write!(self.write, "{:w$}", "", w = w)?,
+                c => write!(self.write, "{}", c)?,
+            }
+            col += w;
+            column = next_column;
+        }
+        self.write.queue(SetAttribute(Attribute::Reset))?;
+        queue_style(&mut *self.write, background)?;
+        let end = col;
+
+        // Other cursors at the end of the line have no character to show them.
+        let line_end = Cursor {
+            ln: line,
+            col: data.line(line).chars().count(),
+        };
+        if data.cursors().iter().any(|cursor| cursor.focus == line_end) && col + 1 < width {
+            queue_style(&mut *self.write, self.theme.selection)?;
+            self.write.write_all(b" ")?;
+            self.write.queue(SetAttribute(Attribute::Reset))?;
+            queue_style(&mut *self.write, background)?;
+            col += 1;
+        }
+
+        // The hint only takes up what is left of the row,
+        // short of the last column so that it is not wrapped.
+        // An empty buffer shows the placeholder in the same way.
+        let hint = match line == data.selection.focus.ln {
+            true => data.hint().or_else(|| match data.char_count() {
+                0 => self.placeholder.clone(),
+                _ => None,
+            }),
+            false => None,
+        };
+        if let Some(hint) = hint {
+            let style = merge(background, self.theme.hint);
+            col = self.draw_suffix(&hint, style, col)?;
+        }
+        let diagnostic = data
+            .diagnostics_on(line)
+            .min_by_key(|diagnostic| diagnostic.severity);
+        if let (Some(diagnostic), true) = (diagnostic, self.diagnostic_messages) {
+            let message = format!("  {}", diagnostic.message);
+            let style = background.with(severity_color(diagnostic.severity));
+            col = self.draw_suffix(&message, style, col)?;
+        }
+
+        // The markers are drawn where the rulers fall after the end of the
+        // content, unless a hint or a message is already drawn over them.
+        if self.ruler_style == RulerStyle::Marker {
+            let markers: Vec<usize> = self
+                .rulers
+                .iter()
+                .filter(|&&ruler| ruler >= column)
+                .map(|&ruler| end + ruler - column)
+                .filter(|&x| x >= col && x + 1 < width)
+                .collect();
+            for x in markers {
+                queue_style(&mut *self.write, background)?;
+                write!(self.write, "{:w$}", "", w = x - col)?;
+                queue_style(&mut *self.write, merge(background, self.theme.ruler))?;
+                self.write.write_all("\u{2502}".as_bytes())?;
+                self.write.queue(SetAttribute(Attribute::Reset))?;
+                col = x + 1;
+            }
+        }
+        self.draw_state.row_widths.push(self.margin.width() + col);
+        // Clearing a full row would erase its last character.
+        if col < width {
+            queue_style(&mut *self.write, background)?;
+            self.write.queue(Clear(ClearType::UntilNewLine))?;
+        }
+        if background != ContentStyle::new() {
+            self.write.queue(SetAttribute(Attribute::Reset))?;
+        }
+
+        Ok(rows)
+    }
+
+    /// Draw text in a style after the content of a row from a column,
+    /// up to its first new-line or what is left of the row. Returns
+    /// the column after the text.
+    fn draw_suffix(&mut self, text: &str, style: ContentStyle, mut col: usize) -> Result<usize> {
+        let width = self.draw_state.width;
+        queue_style(&mut *self.write, style)?;
+        for c in text.chars().take_while(|&c| c != '\n') {
+            let w = char_width(c);
+            if col + w >= width {
+                break;
+            }
+            write!(self.write, "{}", c)?;
+            col += w;
+        }
+        self.write.queue(SetAttribute(Attribute::Reset))?;
+        Ok(col)
     }
 
     fn draw_footer(&mut self, data: &Editor) -> Result<()> {
-        self.draw_state.height += self.footer.rows();
+        let rows = self.footer.rows(data);
+        self.draw_state.ledger.register(FramePart::Footer, rows);
 
-        self.cursor_to_left_term_edge()?;
-        if self.footer.rows() > 0 {
-            self.write.write(b"\n")?;
+        // Without any rows, the cursor stays after the last line
+        // so that clearing below the frame does not erase it.
+        if rows > 0 {
+            self.cursor_to_left_term_edge()?;
+            self.write.write_all(b"\n")?;
         }
         // write!(self.write, "{} {} {}", self.draw_state.low, self.draw_state.high, data.cursor.ln)?;
-        self.footer.draw(self.write, data)?;
+        self.footer.draw(&mut self.write, data)?;
+        Ok(())
+    }
+
+    /// Rows drawn below the footer.
+    fn overlay_rows(&self, data: &Editor) -> usize {
+        self.completion_rows(data)
+            + data.history_search().map_or(0, |_| 1)
+            + data.buffer_search().map_or(0, |_| 1)
+            + data.validation_error().map_or(0, |_| 1)
+            + data.confirmation().map_or(0, |_| 1)
+            + self
+                .preview_lines
+                .as_ref()
+                .map_or(0, |(_, lines)| lines.len())
+    }
+
+    /// Ask the preview for its lines again if the buffer changed.
+    fn update_preview(&mut self, data: &Editor) {
+        let preview = match &self.preview {
+            Some(preview) => preview,
+            None => return,
+        };
+        let revision = data.revision();
+        if self.preview_lines.as_ref().map(|(r, _)| *r) != Some(revision) {
+            let lines = preview(data)
+                .lines()
+                .take(self.preview_rows)
+                .map(String::from)
+                .collect();
+            self.preview_lines = Some((revision, lines));
+        }
+    }
+
+    /// Draw the lines of the preview below everything else.
+    fn draw_preview(&mut self) -> Result<()> {
+        let columns = match self.draw_state.columns {
+            0 => usize::MAX,
+            columns => columns,
+        };
+        let lines = match &self.preview_lines {
+            Some((_, lines)) => lines,
+            None => return Ok(()),
+        };
+        for line in lines {
+            self.write.write_all(b"\n")?;
+            self.write.queue(MoveToColumn(0))?;
+            Self::write_truncated(&mut *self.write, line, columns)?;
+            self.write.queue(Clear(ClearType::UntilNewLine))?;
+        }
+        self.draw_state
+            .ledger
+            .register(FramePart::Preview, lines.len());
+        Ok(())
+    }
+
+    fn completion_rows(&self, data: &Editor) -> usize {
+        data.completion()
+            .map_or(0, |c| c.candidates().len().min(self.completion_menu))
+    }
+
+    /// Draw the candidates of the completion in progress below the footer.
+    fn draw_completions(&mut self, data: &Editor) -> Result<()> {
+        let completion = match data.completion() {
+            Some(completion) => completion,
+            None => return Ok(()),
+        };
+        let rows = self.completion_rows(data);
+        let selected = completion.selected();
+        // Scroll the list so that the selected candidate stays visible.
+        let skip = selected.map_or(0, |i| (i + 1).saturating_sub(rows));
+
+        for (i, candidate) in completion
+            .candidates()
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(rows)
+        {
+            let style = match selected == Some(i) {
+                true => ContentStyle::new().attribute(Attribute::Reverse),
+                false => ContentStyle::new(),
+            };
+            self.draw_row(self.margin.width(), style, &candidate.text)?;
+        }
+
+        self.draw_state
+            .ledger
+            .register(FramePart::Completions, rows);
+        Ok(())
+    }
+
+    /// Draw the prompt of the history or buffer search in progress.
+    fn draw_search(&mut self, data: &Editor) -> Result<()> {
+        if let Some(search) = data.history_search() {
+            let failed = if search.failed() { "failed " } else { "" };
+            let prompt = format!("({}reverse-i-search)`{}': ", failed, search.query());
+            self.draw_row(0, ContentStyle::new(), &prompt)?;
+            self.draw_state.ledger.register(FramePart::Search, 1);
+        }
+        if let Some(search) = data.buffer_search() {
+            let failed = if search.failed() { "failed " } else { "" };
+            let prompt = format!("({}i-search)`{}': ", failed, search.query());
+            self.draw_row(0, ContentStyle::new(), &prompt)?;
+            self.draw_state.ledger.register(FramePart::Search, 1);
+        }
+        Ok(())
+    }
+
+    /// Draw the message of the last submit that was invalid.
+    fn draw_validation_error(&mut self, data: &Editor) -> Result<()> {
+        if let Some(message) = data.validation_error() {
+            let style = ContentStyle::new().with(Color::Red);
+            self.draw_row(self.margin.width(), style, message)?;
+            self.draw_state
+                .ledger
+                .register(FramePart::ValidationError, 1);
+        }
+        Ok(())
+    }
+
+    /// Draw the message that asks to confirm the submit in progress,
+    /// dimmed like the hint.
+    fn draw_confirmation(&mut self, data: &Editor) -> Result<()> {
+        if let Some(message) = data.confirmation() {
+            self.draw_row(self.margin.width(), self.theme.hint, message)?;
+            self.draw_state.ledger.register(FramePart::Confirmation, 1);
+        }
+        Ok(())
+    }
+
+    /// Draw a row of plain text after the last row, in a style after
+    /// `indent` blank columns. Only the first line of the text is drawn,
+    /// and it is cut off at the edge of the terminal so that it takes up
+    /// exactly one row.
+    fn draw_row(&mut self, indent: usize, style: ContentStyle, text: &str) -> Result<()> {
+        let columns = match self.draw_state.columns {
+            0 => usize::MAX,
+            columns => columns,
+        };
+        self.write.write_all(b"\n")?;
+        self.cursor_to_left_term_edge()?;
+        write!(self.write, "{:width$}", "", width = indent)?;
+        queue_style(&mut *self.write, style)?;
+        let line = text.lines().next().unwrap_or("");
+        Self::write_truncated(&mut *self.write, line, columns.saturating_sub(indent))?;
+        self.write.queue(SetAttribute(Attribute::Reset))?;
+        self.write.queue(Clear(ClearType::UntilNewLine))?;
         Ok(())
     }
 
@@ -317,28 +1886,32 @@ where
         term_rows: usize,
     ) -> Result<()> {
         // Print out the contents.
+        self.draw_state.low = low;
+        let mut rows = 0;
         for i in low..high {
-            self.draw_line(&data, i)?;
+            let line_rows = self.draw_line(data, i)?;
+            self.draw_state.line_rows.push(line_rows);
+            rows += line_rows;
             if i < high - 1 {
                 // The last line should not have any new-line attached to it.
-                self.write.write(b"\n")?;
+                self.write.write_all(b"\n")?;
             }
         }
 
         self.draw_state.anchor.col = self.margin.width();
-        self.draw_state.low = low;
         self.draw_state.high = high;
-        self.draw_state.height += high - low;
-        self.draw_state.cursor.ln = high - low - 1;
-        self.draw_state.cursor.col = data.line(high - 1).len();
+        self.draw_state.ledger.register(FramePart::Text, rows);
+        self.draw_state.cursor.ln = rows - 1;
+        self.draw_state.cursor.col = self.draw_state.row_widths.last().copied().unwrap_or(0);
 
-        if data.altscreen {
-            for i in high..low + term_rows {
-                self.write.write(b"\n")?;
-                self.draw_line(&data, i)?;
+        if self.fullscreen(data) {
+            let fill = term_rows.saturating_sub(rows);
+            for i in high..high + fill {
+                self.write.write_all(b"\n")?;
+                self.draw_line(data, i)?;
             }
-            self.draw_state.height += low + term_rows - high;
-            self.draw_state.cursor.ln += low + term_rows - high;
+            self.draw_state.ledger.register(FramePart::Fill, fill);
+            self.draw_state.cursor.ln += fill;
         }
 
         Ok(())
@@ -346,21 +1919,34 @@ where
 
     /// Move the curser to the terminal left margin.
     fn cursor_to_left_term_edge(&mut self) -> Result<()> {
-        self.write.queue(MoveToColumn(0))?;
+        self.move_cursor_to_column(0)
+    }
+
+    /// Move the cursor to a column of its row, counting from zero at the
+    /// left edge of the terminal.
+    fn move_cursor_to_column(&mut self, col: usize) -> Result<()> {
+        self.write.queue(MoveToColumn(Self::usize_to_u16(col)))?;
         Ok(())
     }
 
     #[doc(hidden)]
     fn usize_to_u16(n: usize) -> u16 {
-        n.try_into().unwrap_or(std::u16::MAX)
+        n.try_into().unwrap_or(u16::MAX)
     }
 }
 
-pub type DefaultRenderer<'w, W> = CrosstermRenderer<'w, W, NoStyle, NoStyle, NoStyle>;
+pub type DefaultRenderer<B> = CrosstermRenderer<B, NoStyle, NoStyle, NoStyle>;
 
-impl Default for DefaultRenderer<'static, Stdout> {
+impl Default for DefaultRenderer<CrosstermBackend<Stdout>> {
     fn default() -> Self {
-        let out = Box::new(stdout());
-        CrosstermRenderer::render_to(Box::leak(out))
+        CrosstermRenderer::render_with(CrosstermBackend::stdout())
+    }
+}
+
+impl DefaultRenderer<CrosstermBackend<Stderr>> {
+    /// Render to the standard error, which keeps the standard output
+    /// clean for what the program prints, such as when it is piped.
+    pub fn render_to_stderr() -> Self {
+        CrosstermRenderer::render_with(CrosstermBackend::stderr())
     }
 }
@@ -1,6 +1,18 @@
-use std::io::{self, Write};
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+
+use crate::{
+    backend::Input,
+    editor::{diagnostics::Severity, selection::Cursor, Editor},
+    util::trimmed,
+};
+use decoration::Decoration;
+use theme::{merge, queue_style, Theme};
 
-use crate::{editor::Editor, util::trimmed};
+/// Styled ranges drawn over the lines.
+pub mod decoration;
 
 /// Full renderer.
 pub mod full;
@@ -8,67 +20,231 @@ pub mod full;
 /// Preset styles.
 pub mod styles;
 
+/// Styles of the built-in components.
+pub mod theme;
+
 use crate::Result;
 
 pub trait Renderer {
     fn draw(&mut self, data: &Editor) -> Result<()>;
     fn clear_draw(&mut self) -> Result<()>;
     fn flush(&mut self) -> Result<()>;
+    /// Read the next event from the terminal that is rendered to.
+    fn read_event(&mut self) -> Result<Event>;
+    /// Read the next input from the terminal that is rendered to, which
+    /// tells pastes apart from typing where the terminal does. Defaults to
+    /// reading an event.
+    fn read_input(&mut self) -> Result<Input> {
+        self.read_event().map(Input::Event)
+    }
+    /// Wait up to `timeout` for an event to be available to read.
+    fn poll_event(&mut self, timeout: Duration) -> Result<bool> {
+        let _ = timeout;
+        Ok(false)
+    }
+    /// Number of lines that were shown by the last draw,
+    /// which is how far the editor moves by a page.
+    fn page_size(&self) -> Option<usize> {
+        None
+    }
+    /// First line of the buffer that was shown by the last draw,
+    /// which is how far the editor is scrolled.
+    fn first_line(&self) -> Option<usize> {
+        None
+    }
+    /// Find the position in the buffer that the last draw showed at a
+    /// column and row of the terminal, such as where the mouse clicked.
+    fn locate(&mut self, data: &Editor, column: u16, row: u16) -> Result<Option<Cursor>> {
+        let _ = (data, column, row);
+        Ok(None)
+    }
+    /// Whether a column of the terminal is in the margin of the last draw.
+    fn in_margin(&self, column: u16) -> bool {
+        let _ = column;
+        false
+    }
+    /// Print a message above the drawn prompt, which is redrawn afterwards.
+    fn print(&mut self, message: &str) -> Result<()>;
+    /// Ring the bell of the terminal, such as when an edit is rejected.
+    /// Defaults to doing nothing.
+    fn bell(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Hand the terminal back to the shell and stop the process, like
+    /// job control does, taking the terminal again once the process is
+    /// continued so that the next draw redraws everything.
+    /// Defaults to doing nothing.
+    fn suspend(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Hand the terminal over to something that uses it, such as another
+    /// program, taking it back afterwards so that the next draw redraws
+    /// everything. Defaults to running it right away.
+    fn hand_over(&mut self, run: &mut dyn FnMut()) -> Result<()> {
+        run();
+        Ok(())
+    }
+    /// Called when the input is submitted, right before finishing.
+    fn submit(&mut self, data: &Editor) -> Result<()> {
+        let _ = data;
+        Ok(())
+    }
     fn finish(self) -> Result<()>;
 }
 
 use crossterm::{
-    style::{Color, ResetColor, SetBackgroundColor},
+    event::Event,
+    style::{Attribute, Color, ContentStyle, SetAttribute, Stylize},
     QueueableCommand,
 };
 use ropey::RopeSlice;
 
+/// Color that diagnostics of a severity are drawn in.
+pub(crate) fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Error => Color::Red,
+        Severity::Warning => Color::Yellow,
+        Severity::Info => Color::Blue,
+    }
+}
+
+/// Decorations that underline the diagnostics of a line in the color of
+/// their severity, the most severe last so that it is drawn over the rest.
+pub(crate) fn diagnostic_decorations(editor: &Editor, line_idx: usize) -> Vec<Decoration> {
+    let len = trimmed(editor.buf.line(line_idx)).len_chars();
+    let mut diagnostics: Vec<_> = editor.diagnostics_on(line_idx).collect();
+    diagnostics.sort_by_key(|diagnostic| std::cmp::Reverse(diagnostic.severity));
+    diagnostics
+        .into_iter()
+        .filter_map(|diagnostic| {
+            let (from, to) = diagnostic.columns(line_idx, len)?;
+            let style = ContentStyle::new()
+                .with(severity_color(diagnostic.severity))
+                .attribute(Attribute::Underlined);
+            Some(Decoration::new(from, to, style))
+        })
+        .collect()
+}
+
+fn write_rope(write: &mut dyn Write, rope: RopeSlice<'_>, mask: Option<char>) -> io::Result<()> {
+    match mask {
+        Some(mask) => (0..rope.len_chars()).try_for_each(|_| write!(write, "{}", mask)),
+        None => rope
+            .chunks()
+            .map(|c| c.as_bytes())
+            .try_for_each(|c| write.write_all(c)),
+    }
+}
+
 impl Editor {
     pub fn write_line(&self, line_idx: usize, write: &mut dyn Write) -> Result<()> {
-        fn write_rope(write: &mut dyn Write, rope: RopeSlice<'_>) -> io::Result<()> {
-            rope.chunks()
-                .map(|c| c.as_bytes())
-                .try_for_each(|c| write.write_all(c))
+        let line = trimmed(self.buf.line(line_idx));
+        if let Some((start, end)) = self.selection.range() {
+            if start.ln <= line_idx && line_idx <= end.ln {
+                let len = line.len_chars();
+                let from = if line_idx == start.ln {
+                    start.col.min(len)
+                } else {
+                    0
+                };
+                let to = if line_idx == end.ln {
+                    end.col.min(len)
+                } else {
+                    len
+                };
+                write_rope(write, line.slice(..from), self.mask)?;
+                write.queue(SetAttribute(Attribute::Reverse))?;
+                write_rope(write, line.slice(from..to), self.mask)?;
+                write.queue(SetAttribute(Attribute::NoReverse))?;
+                write_rope(write, line.slice(to..), self.mask)?;
+                return Ok(());
+            }
         }
+        write_rope(write, line, self.mask)?;
+        Ok(())
+    }
 
-        if let Some(anchor) = self.selection.anchor {
-            let (mut start, mut end) = (
-                self.selection.focus.min(anchor),
-                self.selection.focus.max(anchor),
-            );
-            let line = trimmed(self.buf.line(line_idx));
-            if start.ln < line_idx && line_idx < end.ln {
-                write.queue(SetBackgroundColor(Color::DarkGrey))?;
-                write_rope(write, line)?;
-                write.queue(ResetColor)?;
-                return Ok(());
-            } else if start.ln == end.ln && line_idx == start.ln {
-                write_rope(write, line.slice(..start.col))?;
-                write.queue(SetBackgroundColor(Color::DarkGrey))?;
-                write!(write, "{}", crossterm::style::Attribute::Bold)?;
-                write_rope(write, line.slice(start.col..end.col))?;
-                write.queue(ResetColor)?;
-                write_rope(write, line.slice(end.col..))?;
+    /// Write a line like [`Editor::write_line`] in the styles of a theme,
+    /// marking the sorted column ranges of `marks`, such as brackets or
+    /// search matches. The other cursors are drawn along with their
+    /// selections, and diagnostics are underlined in the color of their
+    /// severity.
+    pub fn write_marked_line(
+        &self,
+        line_idx: usize,
+        marks: &[(usize, usize)],
+        theme: &Theme,
+        write: &mut dyn Write,
+    ) -> Result<()> {
+        let line = trimmed(self.buf.line(line_idx));
+        let len = line.len_chars();
+        let diagnosed: Vec<(usize, usize, Severity)> = self
+            .diagnostics_on(line_idx)
+            .filter_map(|diagnostic| {
+                let (from, to) = diagnostic.columns(line_idx, len)?;
+                Some((from, to, diagnostic.severity))
+            })
+            .collect();
+        let selected: Vec<(usize, usize)> = std::iter::once(&self.selection)
+            .chain(self.cursors())
+            .filter_map(|selection| match selection.range() {
+                Some((start, end)) if start.ln <= line_idx && line_idx <= end.ln => {
+                    let from = if line_idx == start.ln { start.col } else { 0 };
+                    let to = if line_idx == end.ln { end.col } else { len };
+                    Some((from.min(len), to.min(len)))
+                }
+                Some(_) => None,
+                // The main cursor is drawn by the terminal.
+                None if selection == &self.selection => None,
+                None => match selection.focus {
+                    Cursor { ln, col } if ln == line_idx && col < len => Some((col, col + 1)),
+                    _ => None,
+                },
+            })
+            .collect();
 
-                write.queue(ResetColor)?;
-                return Ok(());
-            } else if line_idx == start.ln {
-                start.col = start.col.clamp(0, line.len_chars());
-                write_rope(write, line.slice(..start.col))?;
-                write.queue(SetBackgroundColor(Color::DarkGrey))?;
-                write_rope(write, line.slice(start.col..))?;
-                write.queue(ResetColor)?;
-                return Ok(());
-            } else if line_idx == end.ln {
-                end.col = end.col.clamp(0, line.len_chars());
-                write.queue(SetBackgroundColor(Color::DarkGrey))?;
-                write_rope(write, line.slice(..end.col))?;
-                write.queue(ResetColor)?;
-                write_rope(write, line.slice(end.col..))?;
-                return Ok(());
+        // Draw the runs of characters between the edges of the
+        // selection and the marks, each with its own attributes.
+        let mut edges: Vec<usize> = marks
+            .iter()
+            .flat_map(|&(start, end)| vec![start, end])
+            .chain(selected.iter().flat_map(|&(from, to)| vec![from, to]))
+            .chain(diagnosed.iter().flat_map(|&(from, to, _)| vec![from, to]))
+            .chain(vec![0, len])
+            .filter(|&col| col <= len)
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        for run in edges.windows(2) {
+            let (from, to) = (run[0], run[1]);
+            let reversed = selected
+                .iter()
+                .any(|&(start, end)| start <= from && to <= end);
+            let severity = diagnosed
+                .iter()
+                .filter(|&&(start, end, _)| start <= from && to <= end)
+                .map(|&(_, _, severity)| severity)
+                .min();
+            let marked = marks.iter().any(|&(start, end)| start <= from && to <= end);
+            let mut style = ContentStyle::new();
+            if reversed {
+                style = merge(style, theme.selection);
+            }
+            if marked {
+                style = merge(style, theme.search_match);
+            }
+            if let Some(severity) = severity {
+                let underline = ContentStyle::new()
+                    .with(severity_color(severity))
+                    .attribute(Attribute::Underlined);
+                style = merge(style, underline);
+            }
+            queue_style(write, style)?;
+            write_rope(write, line.slice(from..to), self.mask)?;
+            if style != ContentStyle::new() {
+                write.queue(SetAttribute(Attribute::Reset))?;
             }
         }
-        write_rope(write, trimmed(self.buf.line(line_idx)))?;
         Ok(())
     }
 }
@@ -17,8 +17,8 @@ impl<W: Write> Header<W> for ClassicHeader<'_> {
     }
 
     fn draw(&mut self, w: &mut W, _: &Editor) -> Result<()> {
-        w.write("      ╭─── ".as_bytes())?;
-        w.write(self.message.as_bytes())?;
+        w.write_all("      ╭─── ".as_bytes())?;
+        w.write_all(self.message.as_bytes())?;
         w.queue(Clear(ClearType::UntilNewLine))?;
         Ok(())
     }
@@ -32,6 +32,7 @@ impl ClassicGutter {
 
     const DELIM: &'static str = " │ ";
     const DELIM_BOLD: &'static str = " ┃ ";
+    const DELIM_READ_ONLY: &'static str = " ┆ ";
 }
 
 impl<W: Write> Margin<W> for ClassicGutter {
@@ -46,8 +47,10 @@ impl<W: Write> Margin<W> for ClassicGutter {
             write!(write, "{:>width$}", line_idx + 1, width = Self::WIDTH)?;
         }
 
-        write.write(
-            if line_idx == data.selection.focus.ln {
+        write.write_all(
+            if data.is_read_only(line_idx) {
+                Self::DELIM_READ_ONLY
+            } else if line_idx == data.selection.focus.ln {
                 Self::DELIM_BOLD
             } else {
                 Self::DELIM
@@ -57,12 +60,25 @@ impl<W: Write> Margin<W> for ClassicGutter {
 
         Ok(())
     }
+
+    fn draw_continuation(&mut self, write: &mut W, line_idx: usize, data: &Editor) -> Result<()> {
+        write!(write, "{:>width$}", "↪", width = Self::WIDTH)?;
+        write.write_all(
+            if line_idx == data.selection.focus.ln {
+                Self::DELIM_BOLD
+            } else {
+                Self::DELIM
+            }
+            .as_bytes(),
+        )?;
+        Ok(())
+    }
 }
 
 pub struct ClassicFooter;
 
 impl<W: Write> Footer<W> for ClassicFooter {
-    fn rows(&self) -> usize {
+    fn rows(&self, _: &Editor) -> usize {
         1
     }
 
@@ -75,6 +91,9 @@ impl<W: Write> Footer<W> for ClassicFooter {
             data.selection.focus.ln,
             data.selection.focus.col.min(data.curr_ln_len())
         )?;
+        if let Some(mode) = data.vi_mode() {
+            write!(w, " ─── {}", mode)?;
+        }
         w.queue(Clear(ClearType::UntilNewLine))?;
         Ok(())
     }
@@ -1,7 +1,7 @@
 use super::{Footer, Header, Margin};
 use crate::{renderer::Editor, Result};
 use crossterm::{
-    style::Colorize,
+    style::Stylize,
     terminal::{Clear, ClearType},
     QueueableCommand,
 };
@@ -46,8 +46,8 @@ impl<W: Write> Margin<W> for FancyGutter {
                 "{}  ",
                 "       ".on_dark_grey()
             )?;
-        } else if line_idx + 1 == data.line_count() && data.line(line_idx).len() == 0 {
-            if line_idx == data.selection.focus.ln as usize {
+        } else if line_idx + 1 == data.line_count() && data.line(line_idx).is_empty() {
+            if line_idx == data.selection.focus.ln {
                 write!(
                     write,
                     "{} {}",
@@ -62,7 +62,13 @@ impl<W: Write> Margin<W> for FancyGutter {
                     Self::MSG.dark_grey()
                 )?;
             }
-        } else if line_idx == data.selection.focus.ln as usize {
+        } else if data.is_read_only(line_idx) {
+            write!(
+                write,
+                "{}  ",
+                format!(" {:>5} ", line_idx + 1).dark_grey()
+            )?;
+        } else if line_idx == data.selection.focus.ln {
             write!(
                 write,
                 "{} ",
@@ -78,28 +84,37 @@ impl<W: Write> Margin<W> for FancyGutter {
 
         Ok(())
     }
+
+    fn draw_continuation(&mut self, write: &mut W, _: usize, _: &Editor) -> Result<()> {
+        write!(
+            write,
+            "{}  ",
+            format!(" {:>5} ", "↪").black().on_dark_grey()
+        )?;
+        Ok(())
+    }
 }
 
 pub struct FancyFooter;
 
 impl<W: Write> Footer<W> for FancyFooter {
-    fn rows(&self) -> usize {
+    fn rows(&self, _: &Editor) -> usize {
         1
     }
 
     fn draw(&mut self, w: &mut W, data: &Editor) -> Result<()> {
         write!(
             w,
-            "{}{}{}{}",
+            "{} Lines: {:>3}  Chars: {:>3}  Ln {}, Col {} ",
             "  info ".black().on_dark_grey(),
-            format!(" Lines: {:>3} ", data.line_count()),
-            format!(" Chars: {:>3} ", data.char_count()),
-            format!(
-                " Ln {}, Col {} ",
-                data.selection.focus.ln,
-                data.selection.focus.col.min(data.curr_ln().len())
-            )
+            data.line_count(),
+            data.char_count(),
+            data.selection.focus.ln,
+            data.selection.focus.col.min(data.curr_ln().len())
         )?;
+        if let Some(mode) = data.vi_mode() {
+            write!(w, "{}", format!(" {} ", mode).black().on_dark_grey())?;
+        }
 
         w.queue(Clear(ClearType::UntilNewLine))?;
         Ok(())
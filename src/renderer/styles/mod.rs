@@ -1,7 +1,17 @@
 use std::io::Write;
 
-use super::Editor;
-use crate::Result;
+use crossterm::{
+    cursor::MoveToColumn,
+    style::{Attribute, Color, ContentStyle, SetAttribute, Stylize},
+    terminal::{Clear, ClearType},
+    QueueableCommand,
+};
+
+use super::{
+    theme::{queue_style, Theme},
+    Editor,
+};
+use crate::{backend::Backend, editor::flags::LineFlag, util::char_width, Result};
 
 pub mod classic;
 pub mod fancy;
@@ -41,6 +51,29 @@ impl<W: Write> Header<W> for Box<dyn Header<W>> {
 pub trait Margin<W> {
     fn width(&self) -> usize;
     fn draw(&mut self, write: &mut W, line_idx: usize, data: &Editor) -> Result<()>;
+
+    /// Draw the margin of a row that continues a wrapped line.
+    /// Defaults to leaving the margin blank.
+    fn draw_continuation(&mut self, write: &mut W, line_idx: usize, data: &Editor) -> Result<()>
+    where
+        W: Write,
+    {
+        let _ = (line_idx, data);
+        write!(write, "{:width$}", "", width = self.width())?;
+        Ok(())
+    }
+
+    /// Update the margin before each frame is drawn, such as to fit
+    /// the number of lines. Defaults to doing nothing.
+    fn update(&mut self, data: &Editor) {
+        let _ = data;
+    }
+
+    /// Take on the styles of the theme of the renderer, which is
+    /// given before each frame. Defaults to doing nothing.
+    fn set_theme(&mut self, theme: &Theme) {
+        let _ = theme;
+    }
 }
 
 impl<W: Write> Margin<W> for Box<dyn Margin<W>> {
@@ -50,6 +83,15 @@ impl<W: Write> Margin<W> for Box<dyn Margin<W>> {
     fn draw(&mut self, write: &mut W, line_idx: usize, data: &Editor) -> Result<()> {
         (**self).draw(write, line_idx, data)
     }
+    fn draw_continuation(&mut self, write: &mut W, line_idx: usize, data: &Editor) -> Result<()> {
+        (**self).draw_continuation(write, line_idx, data)
+    }
+    fn update(&mut self, data: &Editor) {
+        (**self).update(data)
+    }
+    fn set_theme(&mut self, theme: &Theme) {
+        (**self).set_theme(theme)
+    }
 }
 
 impl<W> Margin<W> for NoStyle {
@@ -62,22 +104,127 @@ impl<W> Margin<W> for NoStyle {
     }
 }
 
+/// Margin drawn from a closure that returns the contents of the margin
+/// of a line along with the number of columns that they take up.
+///
+/// The contents can be styled with escape sequences, since only the
+/// declared widths are used to pad every line to the width of the gutter.
+pub struct Gutter<F> {
+    width: usize,
+    gutter: F,
+}
+
+impl<F: Fn(usize, &Editor) -> (String, usize)> Gutter<F> {
+    /// Create a gutter that takes up `width` columns, which should be
+    /// at least as wide as the contents of any line.
+    pub fn new(width: usize, gutter: F) -> Self {
+        Self { width, gutter }
+    }
+}
+
+impl<W: Write, F: Fn(usize, &Editor) -> (String, usize)> Margin<W> for Gutter<F> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn draw(&mut self, write: &mut W, line_idx: usize, data: &Editor) -> Result<()> {
+        let (content, width) = (self.gutter)(line_idx, data);
+        write.write_all(content.as_bytes())?;
+        write.queue(SetAttribute(Attribute::Reset))?;
+        let pad = self.width.saturating_sub(width);
+        write!(write, "{:pad$}", "", pad = pad)?;
+        Ok(())
+    }
+}
+
+/// Margin of right-aligned line numbers, which grows as the buffer
+/// gets more digits of lines. The numbers are drawn in the gutter style
+/// of the theme and the number of the cursor line in its cursor line
+/// style, while lines flagged with an error or a warning are drawn in
+/// red or yellow.
+#[derive(Debug, Clone, Default)]
+pub struct LineNumbers {
+    digits: usize,
+    theme: Theme,
+}
+
+impl LineNumbers {
+    /// Create line numbers, which are sized on the first draw.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<W: Write> Margin<W> for LineNumbers {
+    fn width(&self) -> usize {
+        self.digits + 2
+    }
+
+    fn draw(&mut self, write: &mut W, line_idx: usize, data: &Editor) -> Result<()> {
+        if line_idx >= data.line_count() {
+            write!(write, "{:width$}", "", width = self.digits + 2)?;
+            return Ok(());
+        }
+        let number = format!("{:>width$} ", line_idx + 1, width = self.digits + 1);
+        let flags = data.line_flags(line_idx);
+        let style = if flags.contains(&LineFlag::Error) {
+            ContentStyle::new().with(Color::Red)
+        } else if flags.contains(&LineFlag::Warning) {
+            ContentStyle::new().with(Color::Yellow)
+        } else if line_idx == data.selection.focus.ln {
+            self.theme.cursor_line_number
+        } else {
+            self.theme.gutter
+        };
+        queue_style(write, style)?;
+        write.write_all(number.as_bytes())?;
+        write.queue(SetAttribute(Attribute::Reset))?;
+        Ok(())
+    }
+
+    fn update(&mut self, data: &Editor) {
+        self.digits = data.line_count().to_string().len();
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = *theme;
+    }
+}
+
+/// Rows drawn below the editor.
+///
+/// Since 0.4.0, [`Footer::rows`] is given the editor so that the number
+/// of rows can follow the contents, and footers from before only have to
+/// take the extra argument.
 pub trait Footer<W> {
-    fn rows(&self) -> usize;
+    /// Number of rows that the footer takes up below the editor.
+    ///
+    /// Rows after the first one are drawn by moving to the start
+    /// of the next row, and each row has to fit in the terminal.
+    fn rows(&self, data: &Editor) -> usize;
     fn draw(&mut self, write: &mut W, data: &Editor) -> Result<()>;
+
+    /// Take on the styles of the theme of the renderer, which is
+    /// given before each frame. Defaults to doing nothing.
+    fn set_theme(&mut self, theme: &Theme) {
+        let _ = theme;
+    }
 }
 
 impl<W: Write> Footer<W> for Box<dyn Footer<W>> {
-    fn rows(&self) -> usize {
-        (**self).rows()
+    fn rows(&self, data: &Editor) -> usize {
+        (**self).rows(data)
     }
     fn draw(&mut self, write: &mut W, data: &Editor) -> Result<()> {
         (**self).draw(write, data)
     }
+    fn set_theme(&mut self, theme: &Theme) {
+        (**self).set_theme(theme)
+    }
 }
 
 impl<W> Footer<W> for NoStyle {
-    fn rows(&self) -> usize {
+    fn rows(&self, _: &Editor) -> usize {
         0
     }
 
@@ -85,3 +232,102 @@ impl<W> Footer<W> for NoStyle {
         Ok(())
     }
 }
+
+/// Footer that draws the lines returned by a closure, such as the mode,
+/// the position of the cursor or help text.
+///
+/// The lines are drawn as text in the status line style of the theme,
+/// cut off at the edge of the terminal.
+pub struct StatusLine<F> {
+    lines: F,
+    style: ContentStyle,
+}
+
+impl<F: Fn(&Editor) -> Vec<String>> StatusLine<F> {
+    /// Create a status line of the lines returned by a closure.
+    pub fn new(lines: F) -> Self {
+        Self {
+            lines,
+            style: ContentStyle::new(),
+        }
+    }
+}
+
+/// Create a status line of the lines returned by a closure, the way that
+/// it was made when it was a tuple struct.
+#[deprecated(since = "0.4.0", note = "use `StatusLine::new` instead")]
+#[allow(non_snake_case)]
+pub fn StatusLine<F: Fn(&Editor) -> Vec<String>>(lines: F) -> StatusLine<F> {
+    StatusLine::new(lines)
+}
+
+impl<B, F> Footer<B> for StatusLine<F>
+where
+    B: Backend,
+    F: Fn(&Editor) -> Vec<String>,
+{
+    fn rows(&self, data: &Editor) -> usize {
+        (self.lines)(data).len()
+    }
+
+    fn draw(&mut self, write: &mut B, data: &Editor) -> Result<()> {
+        let width = write.size().map_or(usize::MAX, |(cols, _)| cols.into());
+        for (i, line) in (self.lines)(data).iter().enumerate() {
+            if i > 0 {
+                write.write_all(b"\n")?;
+                write.queue(MoveToColumn(0))?;
+            }
+            queue_style(write, self.style)?;
+            let mut col = 0;
+            // Stop short of the last column so that the row is not wrapped.
+            for c in line.chars().take_while(|&c| c != '\n') {
+                col += char_width(c);
+                if col >= width {
+                    break;
+                }
+                write!(write, "{}", c)?;
+            }
+            write.queue(Clear(ClearType::UntilNewLine))?;
+            write.queue(SetAttribute(Attribute::Reset))?;
+        }
+        Ok(())
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.style = theme.status_line;
+    }
+}
+
+/// Footer that shows the `line:col` position of the cursor, counting
+/// from one and counting graphemes as single characters, in the status
+/// line style of the theme.
+#[derive(Debug, Clone, Default)]
+pub struct CursorPosition {
+    style: ContentStyle,
+}
+
+impl CursorPosition {
+    /// Create a footer of the position of the cursor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<W: Write> Footer<W> for CursorPosition {
+    fn rows(&self, _: &Editor) -> usize {
+        1
+    }
+
+    fn draw(&mut self, write: &mut W, data: &Editor) -> Result<()> {
+        let position = data.cursor_position();
+        queue_style(write, self.style)?;
+        write!(write, "{}:{}", position.ln + 1, position.grapheme + 1)?;
+        write.queue(Clear(ClearType::UntilNewLine))?;
+        write.queue(SetAttribute(Attribute::Reset))?;
+        Ok(())
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.style = theme.status_line;
+    }
+}
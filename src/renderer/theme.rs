@@ -0,0 +1,148 @@
+use std::io::Write;
+
+use crossterm::{
+    style::{
+        Attribute, Color, ContentStyle, SetAttributes, SetBackgroundColor, SetForegroundColor,
+        SetUnderlineColor, Stylize,
+    },
+    QueueableCommand,
+};
+
+use crate::Result;
+
+/// Styles of the parts of the prompt that the built-in components draw,
+/// which is given to the renderer and passed on to its margin and footer.
+///
+/// The default theme draws the prompt the way it looks without one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Line numbers in the gutter.
+    pub gutter: ContentStyle,
+    /// Number of the line that the cursor is on.
+    pub cursor_line_number: ContentStyle,
+    /// Line that the cursor is on, where the renderer highlights it.
+    pub current_line: ContentStyle,
+    /// Selected text, and the other cursors.
+    pub selection: ContentStyle,
+    /// Hint and placeholder after the cursor line.
+    pub hint: ContentStyle,
+    /// Matching brackets and matches of the buffer search.
+    pub search_match: ContentStyle,
+    /// Rows of the status line.
+    pub status_line: ContentStyle,
+    /// Markers of the rulers.
+    pub ruler: ContentStyle,
+    /// Characters past the first ruler, when the rulers restyle them.
+    pub overflow: ContentStyle,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            gutter: ContentStyle::new().with(Color::DarkGrey),
+            cursor_line_number: ContentStyle::new()
+                .with(Color::White)
+                .attribute(Attribute::Bold),
+            current_line: ContentStyle::new().on(Color::AnsiValue(236)),
+            selection: ContentStyle::new().attribute(Attribute::Reverse),
+            hint: ContentStyle::new().with(Color::DarkGrey),
+            search_match: ContentStyle::new().attribute(Attribute::Underlined),
+            status_line: ContentStyle::new(),
+            ruler: ContentStyle::new().with(Color::DarkGrey),
+            overflow: ContentStyle::new().with(Color::Red),
+        }
+    }
+}
+
+impl Theme {
+    /// Create the default theme.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the style of the line numbers in the gutter.
+    pub fn gutter(self, gutter: ContentStyle) -> Self {
+        Self { gutter, ..self }
+    }
+
+    /// Set the style of the number of the line that the cursor is on.
+    pub fn cursor_line_number(self, cursor_line_number: ContentStyle) -> Self {
+        Self {
+            cursor_line_number,
+            ..self
+        }
+    }
+
+    /// Set the style of the line that the cursor is on, where the
+    /// renderer highlights it.
+    pub fn current_line(self, current_line: ContentStyle) -> Self {
+        Self {
+            current_line,
+            ..self
+        }
+    }
+
+    /// Set the style of selected text and of the other cursors.
+    pub fn selection(self, selection: ContentStyle) -> Self {
+        Self { selection, ..self }
+    }
+
+    /// Set the style of the hint and the placeholder.
+    pub fn hint(self, hint: ContentStyle) -> Self {
+        Self { hint, ..self }
+    }
+
+    /// Set the style of matching brackets and search matches.
+    pub fn search_match(self, search_match: ContentStyle) -> Self {
+        Self {
+            search_match,
+            ..self
+        }
+    }
+
+    /// Set the style of the rows of the status line.
+    pub fn status_line(self, status_line: ContentStyle) -> Self {
+        Self {
+            status_line,
+            ..self
+        }
+    }
+
+    /// Set the style of the markers of the rulers.
+    pub fn ruler(self, ruler: ContentStyle) -> Self {
+        Self { ruler, ..self }
+    }
+
+    /// Set the style of the characters past the first ruler.
+    pub fn overflow(self, overflow: ContentStyle) -> Self {
+        Self { overflow, ..self }
+    }
+}
+
+/// Combine two styles, where the colors of `over` take precedence.
+pub(crate) fn merge(base: ContentStyle, over: ContentStyle) -> ContentStyle {
+    ContentStyle {
+        foreground_color: over.foreground_color.or(base.foreground_color),
+        background_color: over.background_color.or(base.background_color),
+        underline_color: over.underline_color.or(base.underline_color),
+        attributes: base.attributes | over.attributes,
+    }
+}
+
+/// Queue the commands that start drawing in a style,
+/// which lasts until the attributes are reset.
+pub(crate) fn queue_style(write: &mut dyn Write, style: ContentStyle) -> Result<()> {
+    if let Some(color) = style.foreground_color {
+        write.queue(SetForegroundColor(color))?;
+    }
+    if let Some(color) = style.background_color {
+        write.queue(SetBackgroundColor(color))?;
+    }
+    if let Some(color) = style.underline_color {
+        write.queue(SetUnderlineColor(color))?;
+    }
+    if !style.attributes.is_empty() {
+        write.queue(SetAttributes(style.attributes))?;
+    }
+    Ok(())
+}
@@ -1,5 +1,39 @@
 use ropey::RopeSlice;
- 
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+/// Character indices of the matches of a needle in a rope, found
+/// without joining its chunks. Like [`str::match_indices`], the matches
+/// do not overlap.
+pub(crate) fn match_indices(rope: RopeSlice, needle: &str) -> Vec<usize> {
+    let mut found = Vec::new();
+    if needle.is_empty() {
+        return found;
+    }
+    // The end of the chunks before, for matches across chunk boundaries.
+    let mut carry = String::new();
+    // Byte index of the start of the carry, and of where the next match
+    // can start.
+    let (mut start, mut next) = (0, 0);
+    for chunk in rope.chunks() {
+        carry.push_str(chunk);
+        // Matches cannot start in what was left out of the carry.
+        let mut from = next.max(start) - start;
+        while let Some(i) = carry[from..].find(needle) {
+            found.push(rope.byte_to_char(start + from + i));
+            from += i + needle.len();
+        }
+        next = start + from;
+        let mut keep = carry.len().saturating_sub(needle.len() - 1).max(from);
+        while !carry.is_char_boundary(keep) {
+            keep -= 1;
+        }
+        carry.drain(..keep);
+        start += keep;
+    }
+    found
+}
+
 pub(crate) fn trimmed(rope: RopeSlice) -> RopeSlice {
     let rope_len = rope.len_chars();
     if rope_len == 0 {
@@ -10,3 +44,82 @@ pub(crate) fn trimmed(rope: RopeSlice) -> RopeSlice {
         rope
     }
 }
+
+/// Number of terminal columns that a character takes up.
+pub(crate) fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+/// Number of terminal columns that a character drawn at column `x`
+/// takes up, where tabs go up to the next multiple of `tab_width`.
+pub(crate) fn cell_width(c: char, x: usize, tab_width: usize) -> usize {
+    match c {
+        '\t' => tab_width - x % tab_width,
+        c => char_width(c),
+    }
+}
+
+/// Character index of the first character that does not fit in `width`
+/// columns of the line, extending past its end if the line is shorter.
+pub(crate) fn col_at_width(line: &str, width: usize, tab_width: usize) -> usize {
+    let mut x = 0;
+    for (col, c) in line.chars().enumerate() {
+        x += cell_width(c, x, tab_width);
+        if x > width {
+            return col;
+        }
+    }
+    line.chars().count() + (width - x)
+}
+
+/// Character index of the grapheme boundary before `col`.
+pub(crate) fn prev_grapheme(line: &str, col: usize) -> usize {
+    let mut prev = 0;
+    for len in line.graphemes(true).map(|g| g.chars().count()) {
+        if prev + len >= col {
+            break;
+        }
+        prev += len;
+    }
+    prev
+}
+
+/// Character index of the grapheme boundary after `col`.
+pub(crate) fn next_grapheme(line: &str, col: usize) -> usize {
+    let mut next = 0;
+    for len in line.graphemes(true).map(|g| g.chars().count()) {
+        next += len;
+        if next > col {
+            break;
+        }
+    }
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use ropey::Rope;
+
+    use super::*;
+
+    #[test]
+    fn matches_across_chunks() {
+        let text = "añb ".repeat(2000);
+        let rope = Rope::from_str(&text);
+        assert!(rope.chunks().count() > 1);
+        for needle in ["añb", "b a", "ñb añ", "b"] {
+            let expected: Vec<usize> = text
+                .match_indices(needle)
+                .map(|(byte, _)| text[..byte].chars().count())
+                .collect();
+            assert_eq!(match_indices(rope.slice(..), needle), expected);
+        }
+    }
+
+    #[test]
+    fn matches_do_not_overlap() {
+        let rope = Rope::from_str("aaaaa");
+        assert_eq!(match_indices(rope.slice(..), "aa"), [0, 2]);
+        assert!(match_indices(rope.slice(..), "").is_empty());
+    }
+}
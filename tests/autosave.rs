@@ -0,0 +1,78 @@
+use std::{cell::RefCell, env, fs, path::PathBuf, rc::Rc};
+
+use minime::{
+    backend::TestBackend,
+    crossterm::event::{Event, KeyCode, KeyEvent},
+    editor::{autosave::Autosave, keybindings::NormalKeybinding, Editor},
+    renderer::full::CrosstermRenderer,
+};
+
+fn recovery_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("minime-test-{}-{}.txt", name, std::process::id()))
+}
+
+/// Read the queued events, returning what the recovery file held after
+/// every draw.
+fn saved(backend: &mut TestBackend, autosave: Autosave) -> Vec<Option<String>> {
+    let path = autosave.path().to_path_buf();
+    let saved = Rc::new(RefCell::new(Vec::new()));
+    let observed = saved.clone();
+    let renderer = CrosstermRenderer::render_with(backend)
+        .observer(move |_| observed.borrow_mut().push(fs::read_to_string(&path).ok()));
+    // The read fails once the events run out.
+    let _ = Editor::default()
+        .autosave(autosave)
+        .read(NormalKeybinding, renderer);
+    let saved = saved.borrow().clone();
+    saved
+}
+
+#[test]
+fn saves_after_every_few_edits_and_removes_the_file_once_submitted() {
+    let path = recovery_path("every-edits");
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("abcde");
+    backend.push_event(Event::Key(KeyEvent::from(KeyCode::Esc)));
+
+    let saved = saved(&mut backend, Autosave::new(&path).every_edits(2));
+    let saved: Vec<_> = saved.iter().map(Option::as_deref).collect();
+    assert_eq!(
+        saved[..6],
+        [
+            None,
+            None,
+            Some("ab"),
+            Some("ab"),
+            Some("abcd"),
+            Some("abcd")
+        ]
+    );
+    assert!(!path.exists());
+}
+
+#[test]
+fn a_read_that_the_terminal_fails_leaves_the_file_to_recover() {
+    let path = recovery_path("failed");
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("abc");
+
+    saved(&mut backend, Autosave::new(&path));
+    let autosave = Autosave::new(&path);
+    assert_eq!(autosave.recovered().unwrap().as_deref(), Some("abc"));
+    autosave.discard().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn only_the_user_can_read_the_recovery_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = recovery_path("permissions");
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("a");
+
+    saved(&mut backend, Autosave::new(&path));
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    Autosave::new(&path).discard().unwrap();
+    assert_eq!(mode & 0o777, 0o600);
+}
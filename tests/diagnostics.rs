@@ -0,0 +1,26 @@
+use minime::{
+    backend::TestBackend,
+    editor::{
+        diagnostics::{Diagnostic, Severity},
+        keybindings::NormalKeybinding,
+        selection::Cursor,
+        Editor,
+    },
+    renderer::full::CrosstermRenderer,
+};
+
+#[test]
+fn diagnostics_are_underlined_over_the_highlighter() {
+    let mut backend = TestBackend::new(20, 4);
+    let mut editor = Editor::default().initial_text("hello world");
+    let start = Cursor { ln: 0, col: 0 };
+    let end = Cursor { ln: 0, col: 5 };
+    editor.set_diagnostics(vec![Diagnostic::new(start..end, Severity::Error, "")]);
+    let renderer = CrosstermRenderer::render_with(&mut backend)
+        .highlighter(|_, line: &str, _: &Editor| format!("\x1b[34m{}\x1b[0m", line));
+    // The read fails once the keys run out, leaving the screen as drawn.
+    let _ = editor.read(NormalKeybinding, renderer);
+
+    let output = String::from_utf8_lossy(backend.output()).into_owned();
+    assert!(output.contains("\x1b[34m\x1b[38;5;9m\x1b[4mhello\x1b[0m\x1b[34m world"));
+}
@@ -0,0 +1,20 @@
+use minime::{
+    backend::TestBackend,
+    editor::{filter::Compose, keybindings::NormalKeybinding, Editor},
+    renderer::full::CrosstermRenderer,
+};
+
+#[test]
+fn paste_forgets_the_pending_dead_key() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("'");
+    backend.push_paste("x");
+    backend.push_keys("e");
+    let mut editor = Editor::default().input_filter(Compose::new().add('\'', 'e', 'é'));
+    // The read fails once the keys run out.
+    let _ = editor.read(
+        NormalKeybinding,
+        CrosstermRenderer::render_with(&mut backend),
+    );
+    assert_eq!(editor.contents(), "xe");
+}
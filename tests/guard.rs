@@ -0,0 +1,59 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use minime::{
+    backend::TestBackend,
+    editor::{keybindings::NormalKeybinding, Editor},
+    renderer::full::{AnchorMode, CrosstermRenderer, CursorStyle},
+};
+
+/// Check that the alternate screen, the mouse, bracketed paste and the
+/// cursor style were turned on, and that they are off again along with
+/// raw mode.
+fn assert_restored(backend: &TestBackend) {
+    let output = String::from_utf8_lossy(backend.output());
+    for mode in &["1049", "1000", "2004"] {
+        assert!(output.contains(&format!("\x1b[?{}h", mode)));
+    }
+    assert!(output.contains("\x1b[6 q"));
+    assert_eq!(backend.cursor_style(), 0);
+    assert!(!backend.raw_mode());
+    assert!(!backend.altscreen());
+    assert!(!backend.mouse_capture());
+    assert!(!backend.bracketed_paste());
+    assert!(backend.cursor_visible());
+}
+
+#[test]
+fn terminal_is_restored_when_reading_fails() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("abc");
+    // Reading fails once the keys run out.
+    let renderer = CrosstermRenderer::render_with(&mut backend)
+        .anchor(AnchorMode::Fullscreen)
+        .mouse(true)
+        .cursor_style(CursorStyle::SteadyBar);
+    let read = Editor::default().read(NormalKeybinding, renderer);
+    assert!(read.is_err());
+    assert!(String::from_utf8_lossy(backend.output()).contains("abc"));
+    assert_restored(&backend);
+}
+
+#[test]
+fn terminal_is_restored_when_drawing_panics() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("abc");
+    let unwound = panic::catch_unwind(AssertUnwindSafe(|| {
+        let renderer = CrosstermRenderer::render_with(&mut backend)
+            .anchor(AnchorMode::Fullscreen)
+            .mouse(true)
+            .cursor_style(CursorStyle::SteadyBar)
+            .highlighter(|_, line, _| {
+                assert!(!line.contains('c'), "highlighting failed");
+                line.to_string()
+            });
+        Editor::default().read(NormalKeybinding, renderer)
+    }));
+    assert!(unwound.is_err());
+    assert!(String::from_utf8_lossy(backend.output()).contains("ab"));
+    assert_restored(&backend);
+}
@@ -0,0 +1,51 @@
+use minime::{
+    backend::TestBackend,
+    editor::{history::History, keybindings::NormalKeybinding, Editor},
+    renderer::full::CrosstermRenderer,
+};
+
+#[test]
+fn initial_text_is_cut_down_to_the_limits() {
+    let editor = Editor::default()
+        .max_lines(2)
+        .max_line_length(3)
+        .initial_text("abcdef\nghi\njkl");
+    assert_eq!(editor.contents(), "abc\nghi");
+
+    let editor = Editor::default()
+        .initial_text("abcdef\nghi\njkl")
+        .max_lines(2)
+        .max_line_length(3);
+    assert_eq!(editor.contents(), "abc\nghi");
+    assert_eq!(editor.selection.focus.ln, 1);
+}
+
+#[test]
+fn recalled_history_is_cut_down_to_the_limits() {
+    let mut history = History::new(10);
+    history.push("abcdef");
+    let mut editor = Editor::default().max_line_length(3).history(history);
+    assert!(editor.history_previous());
+    assert_eq!(editor.contents(), "abc");
+}
+
+#[test]
+fn set_contents_are_cut_down_to_the_limits() {
+    let mut editor = Editor::default().max_lines(2);
+    editor.set_contents("abc\ndef\nghi".as_bytes()).unwrap();
+    assert_eq!(editor.contents(), "abc\ndef");
+}
+
+#[test]
+fn bell_rings_when_an_edit_goes_beyond_a_limit() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("abcd");
+    let mut editor = Editor::default().max_line_length(3).limit_bell(true);
+    // The read fails once the keys run out.
+    let _ = editor.read(
+        NormalKeybinding,
+        CrosstermRenderer::render_with(&mut backend),
+    );
+    assert_eq!(editor.contents(), "abc");
+    assert_eq!(backend.output().iter().filter(|&&b| b == 0x07).count(), 1);
+}
@@ -0,0 +1,77 @@
+use minime::{
+    backend::TestBackend,
+    crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
+    editor::{keybindings::EmacsKeybinding, Editor},
+    renderer::full::CrosstermRenderer,
+};
+
+fn ctrl(c: char) -> Event {
+    Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+}
+
+/// Push `Ctrl-X` and a key.
+fn chord(backend: &mut TestBackend, key: Event) {
+    backend.push_event(ctrl('x'));
+    backend.push_event(key);
+}
+
+fn read(backend: &mut TestBackend) -> Editor {
+    let mut editor = Editor::default();
+    // The read fails once the events run out.
+    let _ = editor.read(
+        EmacsKeybinding,
+        CrosstermRenderer::render_with(&mut *backend).mouse(true),
+    );
+    editor
+}
+
+#[test]
+fn pastes_are_replayed() {
+    let mut backend = TestBackend::new(20, 4);
+    chord(&mut backend, Event::Key(KeyCode::Char('(').into()));
+    backend.push_paste("ab");
+    backend.push_keys("c");
+    chord(&mut backend, Event::Key(KeyCode::Char(')').into()));
+    chord(&mut backend, Event::Key(KeyCode::Char('e').into()));
+    assert_eq!(read(&mut backend).contents(), "abcabc");
+}
+
+#[test]
+fn clicks_are_replayed() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("hello");
+    chord(&mut backend, Event::Key(KeyCode::Char('(').into()));
+    backend.push_event(Event::Mouse(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column: 0,
+        row: 0,
+        modifiers: KeyModifiers::NONE,
+    }));
+    backend.push_keys("X");
+    chord(&mut backend, Event::Key(KeyCode::Char(')').into()));
+    backend.push_event(ctrl('e'));
+    chord(&mut backend, Event::Key(KeyCode::Char('e').into()));
+    assert_eq!(read(&mut backend).contents(), "XXhello");
+}
+
+#[test]
+fn clicks_past_the_end_of_a_shortened_buffer_are_replayed_at_the_end() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("a\nb\nc");
+    chord(&mut backend, Event::Key(KeyCode::Char('(').into()));
+    backend.push_event(Event::Mouse(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column: 1,
+        row: 2,
+        modifiers: KeyModifiers::NONE,
+    }));
+    chord(&mut backend, Event::Key(KeyCode::Char(')').into()));
+    for _ in 0..4 {
+        backend.push_event(Event::Key(KeyCode::Backspace.into()));
+    }
+    chord(&mut backend, Event::Key(KeyCode::Char('e').into()));
+    backend.push_keys("X");
+    assert_eq!(read(&mut backend).contents(), "aX");
+}
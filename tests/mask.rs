@@ -0,0 +1,29 @@
+use minime::{
+    backend::TestBackend,
+    editor::{keybindings::NormalKeybinding, Editor},
+    renderer::full::CrosstermRenderer,
+};
+
+fn read_masked(backend: &mut TestBackend, editor: Editor) {
+    let renderer = CrosstermRenderer::render_with(&mut *backend).match_brackets(true);
+    // The read fails once the keys run out, leaving the screen as drawn.
+    let _ = editor.mask(Some('*')).read(NormalKeybinding, renderer);
+}
+
+#[test]
+fn hints_are_not_shown_through_the_mask() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("ab");
+    let editor = Editor::default().hinter(|_: &Editor| Some("cdef".to_string()));
+    read_masked(&mut backend, editor);
+    assert_eq!(backend.row(0), "**");
+}
+
+#[test]
+fn matching_brackets_are_not_underlined_through_the_mask() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("(a)");
+    read_masked(&mut backend, Editor::default());
+    assert_eq!(backend.row(0), "***");
+    assert!(!String::from_utf8_lossy(backend.output()).contains("\x1b[4m"));
+}
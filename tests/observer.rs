@@ -0,0 +1,92 @@
+use std::{cell::RefCell, rc::Rc};
+
+use minime::{
+    backend::TestBackend,
+    crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    editor::{keybindings::NormalKeybinding, Editor},
+    renderer::full::{CrosstermRenderer, RenderDecision, RenderReport},
+};
+
+/// Read the queued events, returning the report of every draw.
+fn reports(backend: &mut TestBackend) -> Vec<RenderReport> {
+    let reports = Rc::new(RefCell::new(Vec::new()));
+    let observed = reports.clone();
+    let renderer = CrosstermRenderer::render_with(backend)
+        .observer(move |report| observed.borrow_mut().push(report.clone()));
+    // The read fails once the events run out.
+    let _ = Editor::default().read(NormalKeybinding, renderer);
+    let reports = reports.borrow().clone();
+    reports
+}
+
+fn decisions(reports: &[RenderReport]) -> Vec<RenderDecision> {
+    reports
+        .iter()
+        .map(|report| report.decision.clone())
+        .collect()
+}
+
+#[test]
+fn typing_a_character_redraws_only_its_line() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("a\nb");
+    assert_eq!(
+        decisions(&reports(&mut backend)),
+        [
+            RenderDecision::RedrawAll,
+            RenderDecision::RedrawLines(vec![0]),
+            RenderDecision::RedrawAll,
+            RenderDecision::RedrawLines(vec![1]),
+        ]
+    );
+}
+
+#[test]
+fn moving_the_cursor_along_a_line_redraws_only_that_line() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("ab");
+    backend.push_event(Event::Key(KeyEvent::from(KeyCode::Left)));
+    let decisions = decisions(&reports(&mut backend));
+    assert_eq!(
+        decisions.last(),
+        Some(&RenderDecision::RedrawLines(vec![0]))
+    );
+}
+
+#[test]
+fn draws_report_the_bytes_that_they_wrote() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("ab");
+    let reports = reports(&mut backend);
+    assert!(reports.iter().all(|report| report.bytes > Some(0)));
+    // What is written outside of the draws, such as to restore the
+    // terminal, is not counted.
+    let bytes: u64 = reports.iter().filter_map(|report| report.bytes).sum();
+    assert!(bytes <= backend.output().len() as u64);
+}
+
+#[test]
+fn moving_a_line_redraws_only_the_swapped_lines() {
+    let mut backend = TestBackend::new(20, 6);
+    backend.push_keys("a\nb\nc\nd");
+    backend.push_event(Event::Key(KeyEvent::from(KeyCode::Up)));
+    backend.push_event(Event::Key(KeyEvent::new(KeyCode::Up, KeyModifiers::ALT)));
+    let decisions = decisions(&reports(&mut backend));
+    assert_eq!(
+        decisions.last(),
+        Some(&RenderDecision::RedrawLines(vec![1, 2]))
+    );
+    assert_eq!(backend.screen()[..4], ["a", "c", "b", "d"]);
+}
+
+#[test]
+fn lines_are_duplicated_and_deleted_with_alt_shift() {
+    let mut backend = TestBackend::new(20, 6);
+    backend.push_keys("a\nb");
+    let alt_shift = KeyModifiers::ALT | KeyModifiers::SHIFT;
+    backend.push_event(Event::Key(KeyEvent::new(KeyCode::Down, alt_shift)));
+    backend.push_event(Event::Key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE)));
+    backend.push_event(Event::Key(KeyEvent::new(KeyCode::Char('K'), alt_shift)));
+    reports(&mut backend);
+    assert_eq!(backend.screen()[..3], ["a", "b", ""]);
+}
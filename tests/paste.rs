@@ -0,0 +1,117 @@
+#![cfg(unix)]
+
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, Read, Write},
+    os::unix::{io::FromRawFd, process::CommandExt},
+    process::{self, Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use minime::{
+    editor::{keybindings::NormalKeybinding, Editor, ReadResult},
+    renderer::full::CrosstermRenderer,
+};
+
+/// Set for the process that reads on the pseudo-terminal, to the file
+/// that it leaves the outcome of the read in.
+const RESULT_VAR: &str = "MINIME_PASTE_RESULT";
+
+/// Open a pseudo-terminal of 80 by 24 cells, as `(master, slave)`.
+fn open_pty() -> (File, File) {
+    let (mut master, mut slave) = (0, 0);
+    let size = libc::winsize {
+        ws_row: 24,
+        ws_col: 80,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: the descriptors are only used once `openpty` opened them.
+    unsafe {
+        let null = std::ptr::null_mut();
+        let opened = libc::openpty(&mut master, &mut slave, null, null as _, null as _);
+        assert_eq!(opened, 0, "{}", io::Error::last_os_error());
+        libc::ioctl(master, libc::TIOCSWINSZ as _, &size);
+        (File::from_raw_fd(master), File::from_raw_fd(slave))
+    }
+}
+
+/// Run this test again in a process of its own on the slave of the
+/// pseudo-terminal, which it takes as its controlling terminal, for
+/// crossterm to read from like from a real terminal.
+fn spawn_on(slave: File, result: &str) -> process::Child {
+    let mut command = Command::new(env::current_exe().unwrap());
+    command
+        .args([
+            "--exact",
+            "pastes_are_read_from_the_terminal",
+            "--nocapture",
+        ])
+        .env(RESULT_VAR, result)
+        .stdin(Stdio::from(slave.try_clone().unwrap()))
+        .stdout(Stdio::from(slave.try_clone().unwrap()))
+        .stderr(Stdio::from(slave));
+    // SAFETY: only calls that are safe between `fork` and `exec` are made.
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() < 0 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    command.spawn().unwrap()
+}
+
+#[test]
+fn pastes_are_read_from_the_terminal() {
+    if let Ok(result) = env::var(RESULT_VAR) {
+        let read = Editor::default().read(NormalKeybinding, CrosstermRenderer::default());
+        fs::write(result, format!("{:?}", read.map_err(|e| e.to_string()))).unwrap();
+        return;
+    }
+
+    let result = env::temp_dir().join(format!("minime-paste-{}", process::id()));
+    let (mut master, slave) = open_pty();
+    let mut child = spawn_on(slave, result.to_str().unwrap());
+
+    let (sender, output) = mpsc::channel();
+    let mut reader = master.try_clone().unwrap();
+    thread::spawn(move || {
+        let mut bytes = [0; 1024];
+        while let Ok(n @ 1..=usize::MAX) = reader.read(&mut bytes) {
+            if sender.send(bytes[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+
+    // An empty line would submit the input if the paste was typed.
+    let paste = b"\x1b[200~one\r\rtwo\x1b[201~\r\r";
+    let (mut written, mut pasted, mut reports) = (Vec::new(), false, 0);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while child.try_wait().unwrap().is_none() {
+        assert!(Instant::now() < deadline, "the read did not end");
+        if let Ok(bytes) = output.recv_timeout(Duration::from_millis(10)) {
+            written.extend(bytes);
+        }
+        let text = String::from_utf8_lossy(&written);
+        // Answer every request for the position of the cursor.
+        while reports < text.matches("\x1b[6n").count() {
+            master.write_all(b"\x1b[1;1R").unwrap();
+            reports += 1;
+        }
+        if !pasted && text.contains("\x1b[?2004h") {
+            master.write_all(paste).unwrap();
+            pasted = true;
+        }
+    }
+
+    let read = fs::read_to_string(&result).unwrap();
+    let _ = fs::remove_file(&result);
+    let submitted = Ok::<_, String>(ReadResult::Submitted("one\n\ntwo".to_string()));
+    assert_eq!(read, format!("{:?}", submitted));
+}
@@ -0,0 +1,39 @@
+use minime::{
+    backend::TestBackend,
+    editor::{keybindings::NormalKeybinding, Editor},
+    renderer::{full::CrosstermRenderer, styles::Gutter},
+};
+
+/// Type the keys into an editor drawn behind a prompt of two double-width
+/// characters and a `>`, which takes up five columns.
+fn type_behind_prompt(backend: &mut TestBackend, keys: &str) {
+    backend.push_keys(keys);
+    let renderer = CrosstermRenderer::render_with(&mut *backend)
+        .margin(Gutter::new(5, |_, _| ("提示>".to_string(), 5)));
+    // The read fails once the keys run out, leaving the screen as drawn.
+    let _ = Editor::default().read(NormalKeybinding, renderer);
+}
+
+#[test]
+fn text_starts_after_the_width_of_the_prompt() {
+    let mut backend = TestBackend::new(20, 4);
+    type_behind_prompt(&mut backend, "ab");
+    assert_eq!(backend.row(0), "提示>ab");
+    assert_eq!(backend.cursor(), (7, 0));
+}
+
+#[test]
+fn wrapped_rows_are_indented_by_the_width_of_the_prompt() {
+    let mut backend = TestBackend::new(10, 4);
+    type_behind_prompt(&mut backend, "abcdefg");
+    assert_eq!(backend.screen()[..2], ["提示>abcde", "     fg"]);
+    assert_eq!(backend.cursor(), (7, 1));
+}
+
+#[test]
+fn wide_character_that_does_not_fit_after_the_prompt_wraps() {
+    let mut backend = TestBackend::new(10, 4);
+    type_behind_prompt(&mut backend, "abcd你");
+    assert_eq!(backend.screen()[..2], ["提示>abcd", "     你"]);
+    assert_eq!(backend.cursor(), (7, 1));
+}
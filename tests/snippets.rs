@@ -0,0 +1,34 @@
+use minime::{
+    backend::TestBackend,
+    crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    editor::{keybindings::NormalKeybinding, snippets::Snippets, Editor},
+    renderer::full::CrosstermRenderer,
+};
+
+/// Type the keys with a snippet of `fn`, then undo once.
+fn type_and_undo(keys: &str) -> String {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys(keys);
+    backend.push_event(Event::Key(KeyEvent::new(
+        KeyCode::Char('z'),
+        KeyModifiers::CONTROL,
+    )));
+    let mut editor = Editor::default().snippets(Snippets::new().add("fn", "function"));
+    // The read fails once the keys run out.
+    let _ = editor.read(
+        NormalKeybinding,
+        CrosstermRenderer::render_with(&mut backend),
+    );
+    editor.contents()
+}
+
+#[test]
+fn space_that_expands_a_snippet_is_undone_with_it() {
+    assert_eq!(type_and_undo("a fn "), "a fn");
+}
+
+#[test]
+fn typing_after_an_expansion_is_undone_on_its_own() {
+    assert_eq!(type_and_undo("a fn\tx"), "a function");
+    assert_eq!(type_and_undo("a fn x"), "a function ");
+}
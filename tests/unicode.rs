@@ -0,0 +1,100 @@
+use minime::{
+    backend::TestBackend,
+    crossterm::event::{Event, KeyCode, KeyEvent},
+    editor::{keybindings::NormalKeybinding, vi::EditMode, Editor},
+    renderer::full::{CrosstermRenderer, RulerStyle},
+};
+
+/// Type the text, then press `Left` a number of times.
+fn type_and_go_left(backend: &mut TestBackend, text: &str, left: usize) -> Editor {
+    backend.push_keys(text);
+    for _ in 0..left {
+        backend.push_event(Event::Key(KeyEvent::from(KeyCode::Left)));
+    }
+    let mut editor = Editor::default();
+    // The read fails once the keys run out, leaving the screen as drawn.
+    let _ = editor.read(
+        NormalKeybinding,
+        CrosstermRenderer::render_with(&mut *backend),
+    );
+    editor
+}
+
+#[test]
+fn wide_characters_take_up_two_columns() {
+    let mut backend = TestBackend::new(20, 4);
+    type_and_go_left(&mut backend, "你好👍", 0);
+    assert_eq!(backend.row(0), "你好👍");
+    assert_eq!(backend.cursor(), (6, 0));
+}
+
+#[test]
+fn cursor_moves_over_wide_characters_by_their_width() {
+    let mut backend = TestBackend::new(20, 4);
+    let editor = type_and_go_left(&mut backend, "a你好", 1);
+    assert_eq!(editor.selection.focus.col, 2);
+    assert_eq!(backend.cursor(), (3, 0));
+}
+
+#[test]
+fn combining_marks_take_up_no_columns() {
+    let mut backend = TestBackend::new(20, 4);
+    type_and_go_left(&mut backend, "e\u{301}x", 0);
+    assert_eq!(backend.row(0), "e\u{301}x");
+    assert_eq!(backend.cursor(), (2, 0));
+}
+
+#[test]
+fn cursor_moves_over_a_character_and_its_combining_mark_at_once() {
+    let mut backend = TestBackend::new(20, 4);
+    let editor = type_and_go_left(&mut backend, "e\u{301}x", 2);
+    assert_eq!(editor.selection.focus.col, 0);
+    assert_eq!(backend.cursor(), (0, 0));
+}
+
+/// Type the text in the insert mode of vi, then press `Esc` and the keys
+/// of the normal mode.
+fn vi_keys(text: &str, keys: &str) -> Editor {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys(text);
+    backend.push_event(Event::Key(KeyEvent::from(KeyCode::Esc)));
+    backend.push_keys(keys);
+    let mut editor = Editor::default().edit_mode(EditMode::Vi);
+    // The read fails once the keys run out.
+    let _ = editor.read(
+        NormalKeybinding,
+        CrosstermRenderer::render_with(&mut backend),
+    );
+    editor
+}
+
+#[test]
+fn vi_moves_over_a_character_and_its_combining_mark_at_once() {
+    assert_eq!(vi_keys("e\u{301}xe\u{301}", "hh").selection.focus.col, 0);
+    assert_eq!(vi_keys("e\u{301}xe\u{301}", "0l").selection.focus.col, 2);
+    assert_eq!(vi_keys("e\u{301}xe\u{301}", "0ll").selection.focus.col, 3);
+}
+
+#[test]
+fn vi_deletes_a_character_along_with_its_combining_mark() {
+    assert_eq!(vi_keys("ae\u{301}b", "hx").contents(), "ab");
+    assert_eq!(vi_keys("ae\u{301}", "x").contents(), "a");
+}
+
+#[test]
+fn wide_character_past_the_last_column_wraps() {
+    let mut backend = TestBackend::new(5, 4);
+    type_and_go_left(&mut backend, "abcd你", 0);
+    assert_eq!(backend.screen()[..2], ["abcd", "你"]);
+    assert_eq!(backend.cursor(), (2, 1));
+}
+
+#[test]
+fn rulers_are_measured_in_columns() {
+    let mut backend = TestBackend::new(20, 4);
+    backend.push_keys("你a");
+    let renderer = CrosstermRenderer::render_with(&mut backend).ruler(&[4], RulerStyle::Marker);
+    // The read fails once the keys run out.
+    let _ = Editor::default().read(NormalKeybinding, renderer);
+    assert_eq!(backend.row(0), "你a \u{2502}");
+}
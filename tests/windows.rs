@@ -0,0 +1,58 @@
+#![cfg(windows)]
+
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    rc::Rc,
+};
+
+use minime::{
+    backend::{Backend, CrosstermBackend},
+    crossterm::ansi_support::supports_ansi,
+};
+
+/// Output that can still be looked at once it is moved into a backend.
+#[derive(Clone, Default)]
+struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl SharedOutput {
+    fn bytes(&self) -> Vec<u8> {
+        self.0.borrow().clone()
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn escape_sequences_are_used_where_the_console_processes_them() {
+    let backend = CrosstermBackend::new(io::sink());
+    assert_eq!(backend.supports_ansi(), supports_ansi());
+    if !supports_ansi() {
+        assert!(!backend.supports_synchronized_output());
+    }
+}
+
+#[test]
+fn writes_are_only_held_back_where_escape_sequences_are_processed() {
+    let output = SharedOutput::default();
+    let mut backend = CrosstermBackend::new(output.clone());
+    backend.write_all(b"ab").unwrap();
+    if supports_ansi() {
+        // Writes are collected until the output is flushed.
+        assert!(output.bytes().is_empty());
+    } else {
+        // Legacy consoles move the cursor through WinAPI calls right away,
+        // so the text before the movement has to be written before it.
+        assert_eq!(output.bytes(), b"ab");
+    }
+    backend.flush().unwrap();
+    assert_eq!(output.bytes(), b"ab");
+}